@@ -0,0 +1,88 @@
+//! Async version of the driver, built on embedded-hal-async.
+//!
+//! Mirrors [`Bq34z100g1Driver`](crate::Bq34z100g1Driver) method by method: both drivers are generated from the same
+//! macro, so the protocol sequences (block selection, checksums, read back, unsealing, resets and the calibration
+//! loops) are shared, only the bus and delay calls are awaited here. The traits are the async twins of the blocking
+//! ones, declared from the same macros: [`Bq34z100g1Async`] for register, Control() and security access,
+//! [`GaugeStatusAsync`] for the readings of every gauge implementing it, [`GaugeConfiguratorAsync`],
+//! [`GaugeCalibratorAsync`] and [`FlashStreamProgrammerAsync`]. [`MeasurementsAsync`] is the twin of
+//! [`Measurements`](crate::Measurements).
+use core::marker::PhantomData;
+
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+use crate::registers::*;
+use crate::snapshot::{self, SnapshotImage};
+use crate::{
+    Bq34Z100Error, BusOperation, BusTiming, ControlCommand, ControlStatus, DeciKelvin, DeviceInfo, Flags, FlagsB,
+    GaugeSnapshot, MilliampHours, Milliamps, Millivolts, MilliwattHours, Milliwatts, Minutes, PackConfiguration,
+    RetryPolicy, RetryStatistics, Scale, SecurityKey, SecurityMode, SnapshotGroups,
+};
+#[cfg(feature = "write")]
+use crate::error::{verify_stored, verify_stored_bytes};
+#[cfg(feature = "write")]
+use crate::image::DataFlashImage;
+#[cfg(feature = "write")]
+use crate::pack_configuration::PACK_CONFIGURATION_MASK;
+#[cfg(feature = "write")]
+use crate::transaction::{DataFlashTransaction, TransactionReport};
+#[cfg(feature = "write")]
+use crate::{
+    mean_and_variance, sense_resistor_gains, Capabilities, DataFlash, DataFlashParameter, DataFlashValue,
+    PackConfigurationB, PackConfigurationC,
+};
+
+crate::gauge_driver!(
+    [async] [.await]
+    Bq34z100g1AsyncDriver: Bq34z100g1Async, FlashStreamProgrammerAsync, GaugeConfiguratorAsync, GaugeCalibratorAsync;
+    MeasurementsAsync
+);
+
+pub struct Bq34z100g1AsyncDriver<I2C, Delay> {
+    i2c: I2C,
+    delay: Delay,
+    address: u8,
+    unseal_key: SecurityKey,
+    timing: BusTiming,
+    retry_policy: RetryPolicy,
+    retry_statistics: RetryStatistics,
+    scale: Option<Scale>,
+    device: Option<DeviceInfo>,
+    selected_block: Option<(u8, u8)>, // data flash (subclass, block), only used for the context of errors
+    unsealed_known: bool,             // set by the typestate driver, unsealed() skips the key until the next reset
+    #[cfg(feature = "write")]
+    flash_block_data: [u8; 32],
+}
+
+impl<I2C, Delay> Bq34z100g1AsyncDriver<I2C, Delay> {
+    /// Creates a new driver, address defaults to [`BQ34Z100_G1_ADDRESS`](crate::BQ34Z100_G1_ADDRESS) if None is given.
+    /// The bus can also be a shared one, eg. one of the embedded-hal-bus device wrappers.
+    pub fn new(i2c: I2C, delay: Delay, address: Option<u8>) -> Self {
+        return Bq34z100g1AsyncDriver {
+            i2c,
+            delay,
+            address: address.unwrap_or(BQ34Z100_G1_ADDRESS),
+            unseal_key: SecurityKey::DEFAULT_UNSEAL,
+            timing: BusTiming::DATASHEET,
+            retry_policy: RetryPolicy::NONE,
+            retry_statistics: RetryStatistics::default(),
+            scale: None,
+            device: None,
+            selected_block: None,
+            unsealed_known: false,
+            #[cfg(feature = "write")]
+            flash_block_data: [0; 32],
+        };
+    }
+}
+
+crate::gauge_traits!([async] Bq34z100g1Async, FlashStreamProgrammerAsync, GaugeConfiguratorAsync, GaugeCalibratorAsync);
+
+crate::status::gauge_status!([async] [.await] GaugeStatusAsync for Bq34z100g1Async);
+
+crate::units::measurements!(
+    [async] [.await]
+    /// Typed view on the readings of an async gauge, see
+    /// [`Bq34z100g1AsyncDriver::measurements`](crate::Bq34z100g1AsyncDriver::measurements)
+    MeasurementsAsync over GaugeStatusAsync
+);
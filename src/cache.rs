@@ -0,0 +1,503 @@
+//! Driver that caches the values which only change with the data flash.
+//!
+//! [`Bq34z100g1CachedDriver`] wraps a [`Bq34z100g1Driver`] and answers DEVICE_TYPE, FW_VERSION, HW_VERSION, CHEM_ID,
+//! DF_VERSION, SerialNumber(), PackConfiguration() and DesignCapacity() from memory after the first read. The values
+//! are dropped whenever the driver writes data flash, resets the gauge or programs a flash stream. A reset by someone
+//! else shows up as a changed RESET_DATA count or as a security mode dropping in CONTROL_STATUS without a seal(), both
+//! are checked whenever the wrapper reads them. [`Bq34z100g1CachedDriver::check_reset`] does so on request, eg. once
+//! per cycle of a monitoring loop.
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+use crate::registers::*;
+#[cfg(feature = "flashstream")]
+use crate::FlashStreamProgrammer;
+use crate::{
+    Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, ControlCommand, ControlStatus, DeviceInfo, GaugeStatus, Measurements,
+    SecurityKey, SecurityMode,
+};
+#[cfg(feature = "write")]
+use crate::image::DataFlashImage;
+#[cfg(feature = "write")]
+use crate::transaction::{DataFlashTransaction, TransactionReport};
+#[cfg(feature = "write")]
+use crate::{
+    DataFlashParameter, DataFlashValue, GaugeCalibrator, GaugeConfigurator, PackConfiguration, PackConfigurationB,
+    PackConfigurationC,
+};
+
+/// Counters of the cached reads since the driver was created or the statistics were reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CacheStatistics {
+    pub hits: u32,          // reads answered from the cache
+    pub misses: u32,        // reads of a cacheable value that went to the gauge
+    pub invalidations: u32, // times the cached values were dropped
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct StaticValues {
+    device_type: Option<u16>,
+    fw_version: Option<u16>,
+    hw_version: Option<u16>,
+    chem_id: Option<u16>,
+    df_version: Option<u16>,
+    serial_number: Option<u16>,
+    pack_configuration: Option<u16>,
+    design_capacity: Option<u16>,
+}
+
+impl StaticValues {
+    fn subcommand(&mut self, command: ControlCommand) -> Option<&mut Option<u16>> {
+        return match command {
+            ControlCommand::DeviceType => Some(&mut self.device_type),
+            ControlCommand::FwVersion => Some(&mut self.fw_version),
+            ControlCommand::HwVersion => Some(&mut self.hw_version),
+            ControlCommand::ChemId => Some(&mut self.chem_id),
+            ControlCommand::DfVersion => Some(&mut self.df_version),
+            _ => None,
+        };
+    }
+
+    fn standard_command(&mut self, address: u8) -> Option<&mut Option<u16>> {
+        return match address {
+            SERIAL_NUMBER => Some(&mut self.serial_number),
+            PACK_CONFIGURATION => Some(&mut self.pack_configuration),
+            DESIGN_CAPACITY => Some(&mut self.design_capacity),
+            _ => None,
+        };
+    }
+}
+
+/// Subcommands that write data flash or restart the firmware
+fn invalidates(command: ControlCommand) -> bool {
+    return matches!(command, ControlCommand::Reset | ControlCommand::CcOffsetSave | ControlCommand::EnterRom);
+}
+
+fn mode_rank(mode: SecurityMode) -> u8 {
+    return match mode {
+        SecurityMode::Sealed => 0,
+        SecurityMode::Unsealed => 1,
+        SecurityMode::FullAccess => 2,
+    };
+}
+
+pub struct Bq34z100g1CachedDriver<I2C, Delay> {
+    driver: Bq34z100g1Driver<I2C, Delay>,
+    values: StaticValues,
+    reset_count: Option<u16>,   // last RESET_DATA seen
+    mode: Option<SecurityMode>, // last mode seen in CONTROL_STATUS since the last mode change of the driver
+    statistics: CacheStatistics,
+}
+
+impl<I2C, Delay> Bq34z100g1Driver<I2C, Delay> {
+    /// Wraps the driver into one caching the static values, see [`Bq34z100g1CachedDriver`]
+    pub fn into_cached(self) -> Bq34z100g1CachedDriver<I2C, Delay> {
+        return Bq34z100g1CachedDriver::new(self);
+    }
+}
+
+impl<I2C, Delay> Bq34z100g1CachedDriver<I2C, Delay> {
+    pub fn new(driver: Bq34z100g1Driver<I2C, Delay>) -> Self {
+        return Bq34z100g1CachedDriver {
+            driver,
+            values: StaticValues::default(),
+            reset_count: None,
+            mode: None,
+            statistics: CacheStatistics::default(),
+        };
+    }
+
+    /// Drops all cached values, the next reads go to the gauge again
+    pub fn invalidate(&mut self) {
+        self.values = StaticValues::default();
+        self.reset_count = None;
+        self.mode = None;
+        self.statistics.invalidations = self.statistics.invalidations.wrapping_add(1);
+    }
+
+    pub fn statistics(&self) -> CacheStatistics {
+        return self.statistics;
+    }
+
+    pub fn reset_statistics(&mut self) {
+        self.statistics = CacheStatistics::default();
+    }
+
+    pub fn driver(&self) -> &Bq34z100g1Driver<I2C, Delay> {
+        return &self.driver;
+    }
+
+    /// The wrapped driver, writes or resets done through it are not seen, call invalidate() afterwards
+    pub fn driver_mut(&mut self) -> &mut Bq34z100g1Driver<I2C, Delay> {
+        return &mut self.driver;
+    }
+
+    /// Gives up the caching and hands back the plain driver
+    pub fn into_inner(self) -> Bq34z100g1Driver<I2C, Delay> {
+        return self.driver;
+    }
+
+    fn cached(&mut self, value: Option<u16>) -> Option<u16> {
+        match value {
+            Some(_) => self.statistics.hits = self.statistics.hits.wrapping_add(1),
+            None => self.statistics.misses = self.statistics.misses.wrapping_add(1),
+        }
+        return value;
+    }
+
+    /// Invalidates if the count changed, returns whether it did
+    fn observe_reset_count(&mut self, count: u16) -> bool {
+        let reset = self.reset_count.is_some_and(|previous| previous != count);
+        if reset {
+            self.invalidate();
+        }
+        self.reset_count = Some(count);
+        return reset;
+    }
+
+    /// A reset returns the gauge to the mode it boots in, so a drop not caused by the driver is taken as one
+    fn observe_mode(&mut self, mode: SecurityMode) {
+        if self.mode.is_some_and(|previous| mode_rank(mode) < mode_rank(previous)) {
+            self.invalidate();
+        }
+        self.mode = Some(mode);
+    }
+}
+
+impl<I2C, DELAY, E: embedded_hal::i2c::Error> Bq34z100g1CachedDriver<I2C, DELAY>
+where
+    I2C: I2c<Error = E>,
+    DELAY: DelayNs,
+{
+    /// Reads RESET_DATA and drops the cached values if the gauge was reset since the last read, returns whether it was
+    pub fn check_reset(&mut self) -> Result<bool, Bq34Z100Error<E>> {
+        let count = self.driver.send_control(ControlCommand::ResetData)?.unwrap_or_default();
+        return Ok(self.observe_reset_count(count));
+    }
+
+    /// The readings as typed units, see [`Bq34z100g1Driver::measurements`]
+    pub fn measurements(&mut self) -> Result<Measurements<'_, Self, E>, Bq34Z100Error<E>> {
+        let scale = self.driver.readings_scale()?;
+        return Ok(Measurements::with_detected_scale(self, scale));
+    }
+
+    /// Leaves the security mode tracking to the next CONTROL_STATUS read after the driver changed the mode
+    fn mode_changed<T>(&mut self, result: Result<T, Bq34Z100Error<E>>) -> Result<T, Bq34Z100Error<E>> {
+        self.mode = None;
+        return result;
+    }
+
+    #[cfg(any(feature = "write", feature = "flashstream"))]
+    fn invalidated<T>(&mut self, result: Result<T, Bq34Z100Error<E>>) -> Result<T, Bq34Z100Error<E>> {
+        self.invalidate();
+        return result;
+    }
+}
+
+impl<I2C, DELAY, E: embedded_hal::i2c::Error> Bq34z100g1<E> for Bq34z100g1CachedDriver<I2C, DELAY>
+where
+    I2C: I2c<Error = E>,
+    DELAY: DelayNs,
+{
+    fn read_2_register_as_u16(&mut self, address: u8) -> Result<u16, Bq34Z100Error<E>> {
+        let cached = self.values.standard_command(address).map(|value| *value);
+        let Some(cached) = cached else {
+            return self.driver.read_2_register_as_u16(address);
+        };
+        if let Some(value) = self.cached(cached) {
+            return Ok(value);
+        }
+        let value = self.driver.read_2_register_as_u16(address)?;
+        if let Some(slot) = self.values.standard_command(address) {
+            *slot = Some(value);
+        }
+        return Ok(value);
+    }
+
+    fn read_1_register_as_u8(&mut self, address: u8) -> Result<u8, Bq34Z100Error<E>> {
+        return self.driver.read_1_register_as_u8(address);
+    }
+
+    fn read_control(&mut self, address_lsb: u8, address_msb: u8) -> Result<u16, Bq34Z100Error<E>> {
+        let value = self.driver.read_control(address_lsb, address_msb)?;
+        let subcommand = u16::from_le_bytes([address_lsb, address_msb]);
+        if ControlCommand::ALL.iter().any(|command| command.subcommand() == subcommand && invalidates(*command)) {
+            self.invalidate();
+        }
+        return Ok(value);
+    }
+
+    fn read_registers(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Bq34Z100Error<E>> {
+        return self.driver.read_registers(address, buffer);
+    }
+
+    fn write_reg(&mut self, address: u8, value: u8) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.write_reg(address, value);
+        // the checksum commits the block data
+        if address == BLOCK_DATA_CHECKSUM {
+            self.invalidate();
+        }
+        return result;
+    }
+
+    #[cfg(feature = "write")]
+    fn read_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>> {
+        return self.driver.read_flash_block(sub_class, offset);
+    }
+
+    #[cfg(feature = "write")]
+    fn write_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.write_flash_block(sub_class, offset);
+        return self.invalidated(result);
+    }
+
+    #[cfg(feature = "write")]
+    fn flash_block_checksum(&mut self) -> Result<u8, Bq34Z100Error<E>> {
+        return self.driver.flash_block_checksum();
+    }
+
+    fn unsealed(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.unsealed();
+        return self.mode_changed(result);
+    }
+
+    fn unseal(&mut self, key: SecurityKey) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.unseal(key);
+        return self.mode_changed(result);
+    }
+
+    fn full_access(&mut self, key: SecurityKey) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.full_access(key);
+        return self.mode_changed(result);
+    }
+
+    fn seal(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.seal();
+        return self.mode_changed(result);
+    }
+
+    fn security_mode(&mut self) -> Result<SecurityMode, Bq34Z100Error<E>> {
+        return Ok(self.control_status_decoded()?.security_mode());
+    }
+
+    fn identify(&mut self) -> Result<DeviceInfo, Bq34Z100Error<E>> {
+        let device = DeviceInfo::new(self.device_type()?, self.fw_version()?, self.hw_version()?, self.df_version()?);
+        // kept in the driver for the firmware checks of its updates
+        self.driver.device = Some(device);
+        return Ok(device);
+    }
+
+    fn ready(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.unsealed()?;
+        self.it_enable()?;
+        return Ok(());
+    }
+
+    fn send_control(&mut self, command: ControlCommand) -> Result<Option<u16>, Bq34Z100Error<E>> {
+        let cached = self.values.subcommand(command).map(|value| *value);
+        if let Some(cached) = cached {
+            if let Some(value) = self.cached(cached) {
+                return Ok(Some(value));
+            }
+        }
+        let response = self.driver.send_control(command);
+        if invalidates(command) {
+            self.invalidate();
+        }
+        if command == ControlCommand::Sealed {
+            self.mode = None;
+        }
+        let response = response?;
+        match (command, response) {
+            (ControlCommand::ResetData, Some(count)) => {
+                self.observe_reset_count(count);
+            }
+            (ControlCommand::ControlStatus, Some(status)) => {
+                self.observe_mode(ControlStatus::from(status).security_mode())
+            }
+            (_, Some(value)) => {
+                if let Some(slot) = self.values.subcommand(command) {
+                    *slot = Some(value);
+                }
+            }
+            (_, None) => {}
+        }
+        return Ok(response);
+    }
+
+    fn board_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::BoardOffset)?;
+        return Ok(());
+    }
+
+    fn cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::CcOffset)?;
+        return Ok(());
+    }
+
+    fn cc_offset_save(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::CcOffsetSave)?;
+        return Ok(());
+    }
+
+    fn set_fullsleep(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::SetFullsleep)?;
+        return Ok(());
+    }
+
+    fn set_hibernate(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::SetHibernate)?;
+        return Ok(());
+    }
+
+    fn clear_hibernate(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::ClearHibernate)?;
+        return Ok(());
+    }
+
+    fn sealed(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::Sealed)?;
+        return Ok(());
+    }
+
+    fn it_enable(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::ItEnable)?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "write")]
+    fn cal_enable(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::CalEnable)?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "write")]
+    fn reset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::Reset)?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "write")]
+    fn exit_cal(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::ExitCal)?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "write")]
+    fn enter_cal(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        self.send_control(ControlCommand::EnterCal)?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "write")]
+    fn offset_cal(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+        return Ok(self.send_control(ControlCommand::OffsetCal)?.unwrap_or_default() as i16);
+    }
+}
+
+// the updates and calibrations of the driver all end with data flash written or the gauge reset
+#[cfg(feature = "write")]
+macro_rules! invalidating {
+    ($($name:ident($($arg:ident: $type:ty),*) -> $value:ty),* $(,)?) => {
+        $(
+            fn $name(&mut self, $($arg: $type),*) -> Result<$value, Bq34Z100Error<E>> {
+                let result = self.driver.$name($($arg),*);
+                return self.invalidated(result);
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "flashstream")]
+impl<I2C, DELAY, E: embedded_hal::i2c::Error> FlashStreamProgrammer<E> for Bq34z100g1CachedDriver<I2C, DELAY>
+where
+    I2C: I2c<Error = E>,
+    DELAY: DelayNs,
+{
+    fn write_flash_stream_i2c(&mut self, line: &str, dryrun: bool) -> Result<(), Bq34Z100Error<E>> {
+        if dryrun {
+            return self.driver.write_flash_stream_i2c(line, dryrun);
+        }
+        let result = self.driver.write_flash_stream_i2c(line, dryrun);
+        return self.invalidated(result);
+    }
+}
+
+#[cfg(feature = "write")]
+impl<I2C, DELAY, E: embedded_hal::i2c::Error> GaugeConfigurator<E> for Bq34z100g1CachedDriver<I2C, DELAY>
+where
+    I2C: I2c<Error = E>,
+    DELAY: DelayNs,
+{
+    invalidating! {
+        update_design_capacity(capacity: u16) -> (),
+        update_q_max(capacity: i16) -> (),
+        update_design_energy(energy: i16, scale: u8) -> (),
+        update_cell_charge_voltage_range(t1_t2: u16, t2_t3: u16, t3_t4: u16) -> (),
+        update_number_of_series_cells(cells: u8) -> (),
+        update_pack_configuration(config: PackConfiguration) -> (),
+        update_pack_configuration_b(config: PackConfigurationB) -> (),
+        update_pack_configuration_c(config: PackConfigurationC) -> (),
+        update_charge_termination_parameters(
+            taper_current: i16,
+            min_taper_capacity: i16,
+            cell_taper_voltage: i16,
+            taper_window: u8,
+            tca_set: i8,
+            tca_clear: i8,
+            fc_set: i8,
+            fc_clear: i8
+        ) -> (),
+        set_led_mode(led_config: u8) -> (),
+        write_parameter(parameter: &DataFlashParameter, value: DataFlashValue) -> (),
+        restore_data_flash(image: &DataFlashImage) -> (),
+        restore_data_flash_strict(image: &DataFlashImage) -> (),
+    }
+
+    fn pack_configuration_b(&mut self) -> Result<PackConfigurationB, Bq34Z100Error<E>> {
+        return self.driver.pack_configuration_b();
+    }
+
+    fn pack_configuration_c(&mut self) -> Result<PackConfigurationC, Bq34Z100Error<E>> {
+        return self.driver.pack_configuration_c();
+    }
+
+    fn read_parameter(&mut self, parameter: &DataFlashParameter) -> Result<DataFlashValue, Bq34Z100Error<E>> {
+        return self.driver.read_parameter(parameter);
+    }
+
+    fn dump_data_flash(&mut self) -> Result<DataFlashImage, Bq34Z100Error<E>> {
+        return self.driver.dump_data_flash();
+    }
+
+    fn commit_transaction<const N: usize>(
+        &mut self,
+        transaction: &DataFlashTransaction<N>,
+    ) -> Result<TransactionReport<N>, Bq34Z100Error<E>> {
+        if transaction.is_empty() {
+            return self.driver.commit_transaction(transaction);
+        }
+        let result = self.driver.commit_transaction(transaction);
+        return self.invalidated(result);
+    }
+}
+
+#[cfg(feature = "write")]
+impl<I2C, DELAY, E: embedded_hal::i2c::Error> GaugeCalibrator<E> for Bq34z100g1CachedDriver<I2C, DELAY>
+where
+    I2C: I2c<Error = E>,
+    DELAY: DelayNs,
+{
+    invalidating! {
+        exit_calibration() -> (),
+        calibrate_cc_offset() -> (),
+        calibrate_board_offset() -> (),
+        measure_cc_offset() -> i16,
+        calibrate_voltage_divider(applied_voltage: f32) -> (),
+        calibrate_sense_resistor(applied_current: i16) -> (),
+    }
+
+    fn enter_calibration(&mut self) -> Result<(), Bq34Z100Error<E>> {
+        let result = self.driver.enter_calibration();
+        return self.mode_changed(result);
+    }
+}
@@ -0,0 +1,163 @@
+//! Control() subcommands of the gauge.
+//!
+//! A subcommand is written LSB first to Control(), the gauge then needs some time to execute it. Status and version
+//! subcommands latch a result word that is read back from Control(), the others only act. Most subcommands are ignored
+//! by a sealed gauge without any error on the bus, [`ControlCommand::required_mode`] tells which ones.
+use crate::registers::*;
+use crate::SecurityMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControlCommand {
+    ControlStatus,
+    DeviceType,
+    FwVersion,
+    HwVersion,
+    ResetData,
+    PrevMacwrite,
+    ChemId,
+    /// Starts the board offset calibration, the gauge has to be in calibration mode
+    BoardOffset,
+    /// Starts the coulomb counter offset calibration, the gauge has to be in calibration mode
+    CcOffset,
+    /// Stores the calibrated offsets in data flash
+    CcOffsetSave,
+    DfVersion,
+    SetFullsleep,
+    SetHibernate,
+    ClearHibernate,
+    StaticChemChecksum,
+    StaticDfChecksum,
+    InstructionFlashChecksum,
+    Sealed,
+    ItEnable,
+    /// Toggles whether calibration mode can be entered
+    CalEnable,
+    Reset,
+    ExitCal,
+    EnterCal,
+    /// Internal coulomb counter offset, only valid in calibration mode
+    OffsetCal,
+    /// Leaves the firmware for the ROM used to program flash stream files
+    EnterRom,
+}
+
+impl ControlCommand {
+    pub const ALL: [ControlCommand; 25] = [
+        ControlCommand::ControlStatus,
+        ControlCommand::DeviceType,
+        ControlCommand::FwVersion,
+        ControlCommand::HwVersion,
+        ControlCommand::ResetData,
+        ControlCommand::PrevMacwrite,
+        ControlCommand::ChemId,
+        ControlCommand::BoardOffset,
+        ControlCommand::CcOffset,
+        ControlCommand::CcOffsetSave,
+        ControlCommand::DfVersion,
+        ControlCommand::SetFullsleep,
+        ControlCommand::SetHibernate,
+        ControlCommand::ClearHibernate,
+        ControlCommand::StaticChemChecksum,
+        ControlCommand::StaticDfChecksum,
+        ControlCommand::InstructionFlashChecksum,
+        ControlCommand::Sealed,
+        ControlCommand::ItEnable,
+        ControlCommand::CalEnable,
+        ControlCommand::Reset,
+        ControlCommand::ExitCal,
+        ControlCommand::EnterCal,
+        ControlCommand::OffsetCal,
+        ControlCommand::EnterRom,
+    ];
+
+    /// Word written to Control()
+    pub fn subcommand(self) -> u16 {
+        let subcommand = match self {
+            ControlCommand::ControlStatus => CONTROL_STATUS,
+            ControlCommand::DeviceType => DEVICE_TYPE,
+            ControlCommand::FwVersion => FW_VERSION,
+            ControlCommand::HwVersion => HW_VERSION,
+            ControlCommand::ResetData => RESET_DATA,
+            ControlCommand::PrevMacwrite => PREV_MACWRITE,
+            ControlCommand::ChemId => CHEM_ID,
+            ControlCommand::BoardOffset => BOARD_OFFSET,
+            ControlCommand::CcOffset => CC_OFFSET,
+            ControlCommand::CcOffsetSave => CC_OFFSET_SAVE,
+            ControlCommand::DfVersion => DF_VERSION,
+            ControlCommand::SetFullsleep => SET_FULLSLEEP,
+            ControlCommand::SetHibernate => SET_HIBERNATE,
+            ControlCommand::ClearHibernate => CLEAR_HIBERNATE,
+            ControlCommand::StaticChemChecksum => STATIC_CHEM_CHKSUM,
+            ControlCommand::StaticDfChecksum => STATIC_DF_CHKSUM,
+            ControlCommand::InstructionFlashChecksum => INSTRUCTION_FLASH_CHKSUM,
+            ControlCommand::Sealed => SEALED,
+            ControlCommand::ItEnable => IT_ENABLE,
+            ControlCommand::CalEnable => CAL_ENABLE,
+            ControlCommand::Reset => RESET,
+            ControlCommand::ExitCal => EXIT_CAL,
+            ControlCommand::EnterCal => ENTER_CAL,
+            ControlCommand::OffsetCal => OFFSET_CAL,
+            ControlCommand::EnterRom => return ENTER_ROM,
+        };
+        return subcommand as u16;
+    }
+
+    /// Whether the gauge latches a result word to be read back from Control()
+    pub fn returns_data(self) -> bool {
+        return matches!(
+            self,
+            ControlCommand::ControlStatus
+                | ControlCommand::DeviceType
+                | ControlCommand::FwVersion
+                | ControlCommand::HwVersion
+                | ControlCommand::ResetData
+                | ControlCommand::PrevMacwrite
+                | ControlCommand::ChemId
+                | ControlCommand::DfVersion
+                | ControlCommand::StaticChemChecksum
+                | ControlCommand::StaticDfChecksum
+                | ControlCommand::InstructionFlashChecksum
+                | ControlCommand::OffsetCal
+        );
+    }
+
+    /// Lowest security mode the gauge executes the subcommand in
+    pub fn required_mode(self) -> SecurityMode {
+        return match self {
+            ControlCommand::EnterRom => SecurityMode::FullAccess,
+            ControlCommand::BoardOffset
+            | ControlCommand::CcOffset
+            | ControlCommand::CcOffsetSave
+            | ControlCommand::ItEnable
+            | ControlCommand::CalEnable
+            | ControlCommand::Reset
+            | ControlCommand::ExitCal
+            | ControlCommand::EnterCal
+            | ControlCommand::OffsetCal => SecurityMode::Unsealed,
+            // SEALED is accepted in any mode, sealing a sealed gauge does nothing
+            _ => SecurityMode::Sealed,
+        };
+    }
+
+    /// Whether the gauge executes the subcommand in the given mode
+    pub fn allowed_in(self, mode: SecurityMode) -> bool {
+        return match self.required_mode() {
+            SecurityMode::Sealed => true,
+            SecurityMode::Unsealed => mode != SecurityMode::Sealed,
+            SecurityMode::FullAccess => mode == SecurityMode::FullAccess,
+        };
+    }
+
+    /// Execution time on top of the Control() processing time of the bus timing, before the gauge answers again
+    pub fn processing_time_us(self) -> u32 {
+        return match self {
+            // the checksums are calculated over the whole flash
+            ControlCommand::StaticChemChecksum
+            | ControlCommand::StaticDfChecksum
+            | ControlCommand::InstructionFlashChecksum => 50_000,
+            // the firmware restarts and reloads the data flash
+            ControlCommand::Reset => 150_000,
+            _ => 0,
+        };
+    }
+}
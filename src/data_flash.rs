@@ -0,0 +1,509 @@
+//! Data flash parameters of the bq34z100-G1.
+//!
+//! The named parameters of the data flash summary of the technical reference manual are [`DataFlashParameter`]
+//! constants of [`DataFlash`], with their subclass, offset, type, unit, range and factory default. [`DataFlash::ALL`]
+//! lists them in subclass and offset order. Offsets are counted from the start of the subclass, values are stored big
+//! endian.
+//!
+//! This is a partial map of the data flash, not every entry of the manual has a constant: besides the reserved bytes
+//! and the Ra tables (R_a0, R_a0x), the gaps between the offsets of IT Cfg, Power and Data hold entries of the manual
+//! that are not named here. They are only reachable as raw blocks, through `read_flash_block()` or a
+//! [`DataFlashImage`](crate::DataFlashImage), which covers every block of [`DataFlash::SUBCLASSES`], the list of
+//! every subclass with its size.
+//! The hex types of the manual (H1, H2, H4) are given as the unsigned type of the same size, as are the pack voltages
+//! it lists as I2 with a range up to 65535 mV.
+#[cfg(feature = "write")]
+use core::ops::Range;
+
+use crate::registers::*;
+use crate::xemics;
+
+/// Encoding of a data flash parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataFlashType {
+    I1,
+    I2,
+    U1,
+    U2,
+    U4,
+    /// Xemics float, see [`crate::xemics`]
+    F4,
+    /// String of the given size, the first byte holds the length of the text
+    S(u8),
+}
+
+impl DataFlashType {
+    /// Number of bytes in data flash
+    pub const fn size(self) -> u8 {
+        return match self {
+            DataFlashType::I1 | DataFlashType::U1 => 1,
+            DataFlashType::I2 | DataFlashType::U2 => 2,
+            DataFlashType::U4 | DataFlashType::F4 => 4,
+            DataFlashType::S(size) => size,
+        };
+    }
+}
+
+/// Text of a string parameter, at most 31 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataFlashText {
+    len: u8,
+    bytes: [u8; FLASH_BLOCK_SIZE - 1],
+}
+
+impl DataFlashText {
+    /// None if the text is longer than 31 bytes
+    pub const fn new(text: &str) -> Option<Self> {
+        let text = text.as_bytes();
+        if text.len() >= FLASH_BLOCK_SIZE {
+            return None;
+        }
+        let mut bytes = [0; FLASH_BLOCK_SIZE - 1];
+        let mut i = 0;
+        while i < text.len() {
+            bytes[i] = text[i];
+            i += 1;
+        }
+        return Some(DataFlashText { len: text.len() as u8, bytes });
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        return &self.bytes[..self.len as usize];
+    }
+
+    /// None if the gauge holds no valid UTF-8
+    pub fn as_str(&self) -> Option<&str> {
+        return core::str::from_utf8(self.as_bytes()).ok();
+    }
+}
+
+/// Value of a parameter, also used for its range limits and default
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataFlashValue {
+    Integer(i64),
+    Float(f32),
+    Text(DataFlashText),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataFlashParameter {
+    pub name: &'static str,
+    pub sub_class: u8,
+    pub offset: u8,
+    pub data_type: DataFlashType,
+    pub unit: &'static str, // empty if the value has none
+    pub min: DataFlashValue,
+    pub max: DataFlashValue,
+    pub default: DataFlashValue,
+}
+
+impl DataFlashParameter {
+    #[allow(clippy::too_many_arguments)]
+    const fn integer(
+        name: &'static str,
+        sub_class: u8,
+        offset: u8,
+        data_type: DataFlashType,
+        unit: &'static str,
+        min: i64,
+        max: i64,
+        default: i64,
+    ) -> Self {
+        return DataFlashParameter {
+            name,
+            sub_class,
+            offset,
+            data_type,
+            unit,
+            min: DataFlashValue::Integer(min),
+            max: DataFlashValue::Integer(max),
+            default: DataFlashValue::Integer(default),
+        };
+    }
+
+    const fn float(
+        name: &'static str,
+        sub_class: u8,
+        offset: u8,
+        unit: &'static str,
+        min: f32,
+        max: f32,
+        default: f32,
+    ) -> Self {
+        return DataFlashParameter {
+            name,
+            sub_class,
+            offset,
+            data_type: DataFlashType::F4,
+            unit,
+            min: DataFlashValue::Float(min),
+            max: DataFlashValue::Float(max),
+            default: DataFlashValue::Float(default),
+        };
+    }
+
+    const fn text(name: &'static str, sub_class: u8, offset: u8, size: u8, default: &'static str) -> Self {
+        return DataFlashParameter {
+            name,
+            sub_class,
+            offset,
+            data_type: DataFlashType::S(size),
+            unit: "",
+            min: DataFlashValue::Integer(0),
+            max: DataFlashValue::Integer(size as i64 - 1), // length of the text
+            default: DataFlashValue::Text(match DataFlashText::new(default) {
+                Some(default) => default,
+                None => panic!("default text too long"),
+            }),
+        };
+    }
+
+    pub const fn size(&self) -> u8 {
+        return self.data_type.size();
+    }
+
+    /// Block of the subclass the parameter starts in
+    pub const fn block(&self) -> u8 {
+        return self.offset / FLASH_BLOCK_SIZE as u8;
+    }
+
+    /// Whether the integer is within min and max, always false for floats and texts
+    pub fn accepts(&self, value: i64) -> bool {
+        return match (self.min, self.max) {
+            (DataFlashValue::Integer(min), DataFlashValue::Integer(max)) => (min..=max).contains(&value),
+            _ => false,
+        };
+    }
+
+    /// Big endian data flash bytes of the value, None if it has the wrong kind or is out of range
+    pub fn encode(&self, value: DataFlashValue, bytes: &mut [u8]) -> Option<()> {
+        let bytes = &mut bytes[..self.size() as usize];
+        match (self.data_type, value) {
+            (DataFlashType::F4, DataFlashValue::Float(value)) => {
+                let (DataFlashValue::Float(min), DataFlashValue::Float(max)) = (self.min, self.max) else {
+                    return None;
+                };
+                if !(min..=max).contains(&value) {
+                    return None;
+                }
+                bytes.copy_from_slice(&xemics::encode(value)?.to_be_bytes());
+            }
+            (DataFlashType::S(_), DataFlashValue::Text(text)) => {
+                let text = text.as_bytes();
+                if text.len() >= bytes.len() {
+                    return None;
+                }
+                bytes.fill(0);
+                bytes[0] = text.len() as u8;
+                bytes[1..=text.len()].copy_from_slice(text);
+            }
+            (DataFlashType::F4 | DataFlashType::S(_), _) => return None,
+            (_, DataFlashValue::Integer(value)) if self.accepts(value) => {
+                let size = bytes.len();
+                bytes.copy_from_slice(&value.to_be_bytes()[8 - size..]);
+            }
+            _ => return None,
+        }
+        return Some(());
+    }
+
+    /// Value of the big endian data flash bytes, a text length beyond the size is cut
+    pub fn decode(&self, bytes: &[u8]) -> DataFlashValue {
+        let bytes = &bytes[..self.size() as usize];
+        let unsigned = bytes.iter().fold(0_u64, |value, &byte| value << 8 | byte as u64);
+        return match self.data_type {
+            DataFlashType::I1 => DataFlashValue::Integer(unsigned as i8 as i64),
+            DataFlashType::I2 => DataFlashValue::Integer(unsigned as i16 as i64),
+            DataFlashType::U1 | DataFlashType::U2 | DataFlashType::U4 => DataFlashValue::Integer(unsigned as i64),
+            DataFlashType::F4 => DataFlashValue::Float(xemics::decode(unsigned as u32)),
+            DataFlashType::S(size) => {
+                let len = bytes[0].min(size - 1) as usize;
+                let mut text = DataFlashText { len: len as u8, bytes: [0; FLASH_BLOCK_SIZE - 1] };
+                text.bytes[..len].copy_from_slice(&bytes[1..=len]);
+                DataFlashValue::Text(text)
+            }
+        };
+    }
+
+    #[cfg(feature = "write")]
+    /// Stored value as reported by NotStored, integers as such, floats and texts by their first four bytes
+    pub(crate) fn reported(&self, bytes: &[u8]) -> i32 {
+        if let DataFlashValue::Integer(value) = self.decode(bytes) {
+            return value as i32;
+        }
+        return bytes.iter().take(4).fold(0_u32, |value, &byte| value << 8 | byte as u32) as i32;
+    }
+
+    #[cfg(feature = "write")]
+    /// Parts of the parameter in consecutive blocks, as offset in the subclass and range of the parameter bytes
+    pub(crate) fn block_parts(&self) -> impl Iterator<Item = (u8, Range<usize>)> {
+        let (offset, size) = (self.offset as usize, self.size() as usize);
+        return (offset / FLASH_BLOCK_SIZE..=(offset + size - 1) / FLASH_BLOCK_SIZE).map(move |block| {
+            let start = (block * FLASH_BLOCK_SIZE).max(offset) - offset;
+            let end = ((block + 1) * FLASH_BLOCK_SIZE).min(offset + size) - offset;
+            return ((offset + start) as u8, start..end);
+        });
+    }
+}
+
+/// Subclass of the data flash and the number of bytes it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataFlashSubclass {
+    pub id: u8,
+    pub name: &'static str,
+    pub size: u8,
+    /// The gauge writes the subclass itself while it runs (learned state, lifetime data, Ra tables)
+    pub updated_by_gauge: bool,
+}
+
+impl DataFlashSubclass {
+    const fn new(id: u8, name: &'static str, size: u8) -> Self {
+        return DataFlashSubclass { id, name, size, updated_by_gauge: false };
+    }
+
+    const fn updated_by_gauge(id: u8, name: &'static str, size: u8) -> Self {
+        return DataFlashSubclass { id, name, size, updated_by_gauge: true };
+    }
+
+    /// Number of blocks the subclass spans, the last one may be partly reserved
+    pub const fn blocks(&self) -> u8 {
+        return self.size.div_ceil(FLASH_BLOCK_SIZE as u8);
+    }
+}
+
+/// Namespace of the data flash parameters
+pub struct DataFlash;
+
+use DataFlashSubclass as S;
+
+impl DataFlash {
+    /// Every subclass of the data flash summary in id order, including those without named parameters
+    pub const SUBCLASSES: &'static [DataFlashSubclass] = &[
+        S::new(SUBCLASS_SAFETY, "Safety", 10),
+        S::new(SUBCLASS_CHARGE_INHIBIT_CFG, "Charge Inhibit Cfg", 6),
+        S::new(SUBCLASS_CHARGE, "Charge", 4),
+        S::new(SUBCLASS_CHARGE_TERMINATION, "Charge Termination", 13),
+        S::new(SUBCLASS_DATA, "Data", 60),
+        S::new(SUBCLASS_DISCHARGE, "Discharge", 18),
+        S::new(SUBCLASS_MANUFACTURER_DATA, "Manufacturer Data", 12),
+        S::new(SUBCLASS_MANUFACTURER_INFO, "Manufacturer Info", 32),
+        S::updated_by_gauge(SUBCLASS_LIFETIME_DATA, "Lifetime Data", 12),
+        S::updated_by_gauge(SUBCLASS_LIFETIME_TEMP_SAMPLES, "Lifetime Temp Samples", 2),
+        S::new(SUBCLASS_REGISTERS, "Registers", 8),
+        S::updated_by_gauge(SUBCLASS_LIFETIME_RESOLUTION, "Lifetime Resolution", 5),
+        S::new(SUBCLASS_LED_DISPLAY, "LED Display", 1),
+        S::new(SUBCLASS_POWER, "Power", 13),
+        S::new(SUBCLASS_IT_CFG, "IT Cfg", 85),
+        S::new(SUBCLASS_CURRENT_THRESHOLDS, "Current Thresholds", 12),
+        S::updated_by_gauge(SUBCLASS_STATE, "State", 15),
+        S::new(SUBCLASS_OCVA_TABLE, "OCVa Table", 2),
+        // resistance of the impedance track model, 15 grid points and a status word
+        S::updated_by_gauge(SUBCLASS_RA0, "R_a0", 32),
+        S::updated_by_gauge(SUBCLASS_RA0X, "R_a0x", 32),
+        S::new(SUBCLASS_CALIBRATION_DATA, "Data", 16),
+        S::new(SUBCLASS_CALIBRATION_CURRENT, "Current", 3),
+        S::new(SUBCLASS_CODES, "Codes", 8),
+    ];
+
+    pub fn subclass(id: u8) -> Option<&'static DataFlashSubclass> {
+        return DataFlash::SUBCLASSES.iter().find(|subclass| subclass.id == id);
+    }
+}
+
+macro_rules! parameters {
+    ($($(#[$meta:meta])* $constant:ident = $parameter:expr;)*) => {
+        impl DataFlash {
+            $(
+                $(#[$meta])*
+                pub const $constant: DataFlashParameter = $parameter;
+            )*
+
+            pub const ALL: &'static [DataFlashParameter] = &[$(DataFlash::$constant),*];
+        }
+    };
+}
+
+use DataFlashParameter as P;
+use DataFlashType::{I1, I2, U1, U2, U4};
+
+parameters! {
+    // Configuration: Safety
+    OT_CHG = P::integer("OT Chg", SUBCLASS_SAFETY, 0, I2, "0.1°C", 0, 1200, 550);
+    OT_CHG_TIME = P::integer("OT Chg Time", SUBCLASS_SAFETY, 2, U1, "s", 0, 60, 2);
+    OT_CHG_RECOVERY = P::integer("OT Chg Recovery", SUBCLASS_SAFETY, 3, I2, "0.1°C", 0, 1200, 500);
+    OT_DSG = P::integer("OT Dsg", SUBCLASS_SAFETY, 5, I2, "0.1°C", 0, 1200, 600);
+    OT_DSG_TIME = P::integer("OT Dsg Time", SUBCLASS_SAFETY, 7, U1, "s", 0, 60, 2);
+    OT_DSG_RECOVERY = P::integer("OT Dsg Recovery", SUBCLASS_SAFETY, 8, I2, "0.1°C", 0, 1200, 550);
+
+    // Configuration: Charge Inhibit Cfg
+    CHG_INHIBIT_TEMP_LOW = P::integer("Chg Inhibit Temp Low", SUBCLASS_CHARGE_INHIBIT_CFG, 0, I2, "0.1°C", -400, 1200, 0);
+    CHG_INHIBIT_TEMP_HIGH =
+        P::integer("Chg Inhibit Temp High", SUBCLASS_CHARGE_INHIBIT_CFG, 2, I2, "0.1°C", -400, 1200, 450);
+    TEMP_HYS = P::integer("Temp Hys", SUBCLASS_CHARGE_INHIBIT_CFG, 4, I2, "0.1°C", 0, 100, 50);
+
+    // Configuration: Charge
+    SUSPEND_TEMPERATURE_LOW = P::integer("Suspend Temperature Low", SUBCLASS_CHARGE, 0, I2, "0.1°C", -400, 1200, -50);
+    SUSPEND_TEMPERATURE_HIGH = P::integer("Suspend Temperature High", SUBCLASS_CHARGE, 2, I2, "0.1°C", -400, 1200, 550);
+
+    // Configuration: Charge Termination
+    TAPER_CURRENT = P::integer("Taper Current", SUBCLASS_CHARGE_TERMINATION, 0, I2, "mA", 0, 1000, 100);
+    MIN_TAPER_CAPACITY = P::integer("Min Taper Capacity", SUBCLASS_CHARGE_TERMINATION, 2, I2, "mAh", 0, 1000, 25);
+    CELL_TAPER_VOLTAGE = P::integer("Cell Taper Voltage", SUBCLASS_CHARGE_TERMINATION, 4, I2, "mV", 0, 1000, 100);
+    CURRENT_TAPER_WINDOW = P::integer("Current Taper Window", SUBCLASS_CHARGE_TERMINATION, 6, U1, "s", 0, 60, 40);
+    TCA_SET = P::integer("TCA Set %", SUBCLASS_CHARGE_TERMINATION, 7, I1, "%", -1, 100, 99);
+    TCA_CLEAR = P::integer("TCA Clear %", SUBCLASS_CHARGE_TERMINATION, 8, I1, "%", -1, 100, 95);
+    FC_SET = P::integer("FC Set %", SUBCLASS_CHARGE_TERMINATION, 9, I1, "%", -1, 100, -1);
+    FC_CLEAR = P::integer("FC Clear %", SUBCLASS_CHARGE_TERMINATION, 10, I1, "%", -1, 100, 98);
+    DODATEOC_DELTA_T = P::integer("DODatEOC Delta T", SUBCLASS_CHARGE_TERMINATION, 11, I2, "0.1°C", 0, 1000, 100);
+
+    // Configuration: Data
+    /// Day + Month * 32 + (Year - 1980) * 256
+    MANUFACTURE_DATE = P::integer("Manufacture Date", SUBCLASS_DATA, 0, U2, "", 0, 65535, 0);
+    SERIAL_NUMBER = P::integer("Serial Number", SUBCLASS_DATA, 2, U2, "", 0, 0xffff, 0x0001);
+    CYCLE_COUNT = P::integer("Cycle Count", SUBCLASS_DATA, 6, U2, "", 0, 65535, 0);
+    CC_THRESHOLD = P::integer("CC Threshold", SUBCLASS_DATA, 8, I2, "mAh", 100, 32767, 900);
+    MAX_ERROR_LIMIT = P::integer("Max Error Limit", SUBCLASS_DATA, 10, U1, "%", 0, 100, 100);
+    DESIGN_CAPACITY = P::integer("Design Capacity", SUBCLASS_DATA, 11, I2, "mAh", 0, 32767, 1000);
+    /// In units of Design Energy Scale mWh
+    DESIGN_ENERGY = P::integer("Design Energy", SUBCLASS_DATA, 13, I2, "mWh", 0, 32767, 5400);
+    SOH_LOAD_I = P::integer("SOH Load I", SUBCLASS_DATA, 15, I2, "mA", -32767, 0, -400);
+    CELL_CHARGE_VOLTAGE_T1_T2 = P::integer("Cell Charge Voltage T1-T2", SUBCLASS_DATA, 17, U2, "mV", 0, 4600, 4200);
+    CELL_CHARGE_VOLTAGE_T2_T3 = P::integer("Cell Charge Voltage T2-T3", SUBCLASS_DATA, 19, U2, "mV", 0, 4600, 4200);
+    CELL_CHARGE_VOLTAGE_T3_T4 = P::integer("Cell Charge Voltage T3-T4", SUBCLASS_DATA, 21, U2, "mV", 0, 4600, 4100);
+    CHARGE_CURRENT_T1_T2 = P::integer("Charge Current T1-T2", SUBCLASS_DATA, 23, U1, "%", 0, 100, 10);
+    CHARGE_CURRENT_T2_T3 = P::integer("Charge Current T2-T3", SUBCLASS_DATA, 24, U1, "%", 0, 100, 50);
+    CHARGE_CURRENT_T3_T4 = P::integer("Charge Current T3-T4", SUBCLASS_DATA, 25, U1, "%", 0, 100, 30);
+    JEITA_T1 = P::integer("JEITA T1", SUBCLASS_DATA, 26, I1, "°C", -128, 127, 0);
+    JEITA_T2 = P::integer("JEITA T2", SUBCLASS_DATA, 27, I1, "°C", -128, 127, 10);
+    JEITA_T3 = P::integer("JEITA T3", SUBCLASS_DATA, 28, I1, "°C", -128, 127, 45);
+    JEITA_T4 = P::integer("JEITA T4", SUBCLASS_DATA, 29, I1, "°C", -128, 127, 55);
+    DESIGN_ENERGY_SCALE = P::integer("Design Energy Scale", SUBCLASS_DATA, 30, U1, "", 1, 10, 1);
+    DEVICE_NAME = P::text("Device Name", SUBCLASS_DATA, 31, 12, "bq34z100-G1");
+    MANUFACTURER_NAME = P::text("Manufacturer Name", SUBCLASS_DATA, 43, 12, "Texas Inst.");
+    DEVICE_CHEMISTRY = P::text("Device Chemistry", SUBCLASS_DATA, 55, 5, "LION");
+
+    // Configuration: Discharge
+    SOC1_SET_THRESHOLD = P::integer("SOC1 Set Threshold", SUBCLASS_DISCHARGE, 0, U2, "mAh", 0, 65535, 150);
+    SOC1_CLEAR_THRESHOLD = P::integer("SOC1 Clear Threshold", SUBCLASS_DISCHARGE, 2, U2, "mAh", 0, 65535, 175);
+    SOCF_SET_THRESHOLD = P::integer("SOCF Set Threshold", SUBCLASS_DISCHARGE, 4, U2, "mAh", 0, 65535, 75);
+    SOCF_CLEAR_THRESHOLD = P::integer("SOCF Clear Threshold", SUBCLASS_DISCHARGE, 6, U2, "mAh", 0, 65535, 100);
+    BL_SET_VOLT_THRESHOLD = P::integer("BL Set Volt Threshold", SUBCLASS_DISCHARGE, 8, I2, "mV", 0, 16800, 2800);
+    BL_SET_VOLT_TIME = P::integer("BL Set Volt Time", SUBCLASS_DISCHARGE, 10, U1, "s", 0, 60, 2);
+    BL_CLEAR_VOLT_THRESHOLD = P::integer("BL Clear Volt Threshold", SUBCLASS_DISCHARGE, 11, I2, "mV", 0, 16800, 2900);
+    BH_SET_VOLT_THRESHOLD = P::integer("BH Set Volt Threshold", SUBCLASS_DISCHARGE, 13, I2, "mV", 0, 16800, 4300);
+    BH_VOLT_TIME = P::integer("BH Volt Time", SUBCLASS_DISCHARGE, 15, U1, "s", 0, 60, 2);
+    BH_CLEAR_VOLT_THRESHOLD = P::integer("BH Clear Volt Threshold", SUBCLASS_DISCHARGE, 16, I2, "mV", 0, 16800, 4200);
+
+    // Configuration: Manufacturer Data
+    PACK_LOT_CODE = P::integer("Pack Lot Code", SUBCLASS_MANUFACTURER_DATA, 0, U2, "", 0, 0xffff, 0);
+    PCB_LOT_CODE = P::integer("PCB Lot Code", SUBCLASS_MANUFACTURER_DATA, 2, U2, "", 0, 0xffff, 0);
+    FIRMWARE_VERSION = P::integer("Firmware Version", SUBCLASS_MANUFACTURER_DATA, 4, U2, "", 0, 0xffff, 0);
+    HARDWARE_REVISION = P::integer("Hardware Revision", SUBCLASS_MANUFACTURER_DATA, 6, U2, "", 0, 0xffff, 0);
+    CELL_REVISION = P::integer("Cell Revision", SUBCLASS_MANUFACTURER_DATA, 8, U2, "", 0, 0xffff, 0);
+    DF_CONFIG_VERSION = P::integer("DF Config Version", SUBCLASS_MANUFACTURER_DATA, 10, U2, "", 0, 0xffff, 0);
+
+    // Configuration: Manufacturer Info
+    MANUFACTURER_INFO = P::text("Manufacturer Info", SUBCLASS_MANUFACTURER_INFO, 0, 32, "");
+
+    // Configuration: Lifetime Data
+    LIFETIME_MAX_TEMP = P::integer("Lifetime Max Temp", SUBCLASS_LIFETIME_DATA, 0, I2, "0.1°C", 0, 1400, 300);
+    LIFETIME_MIN_TEMP = P::integer("Lifetime Min Temp", SUBCLASS_LIFETIME_DATA, 2, I2, "0.1°C", -600, 1400, 200);
+    LIFETIME_MAX_CHG_CURRENT = P::integer("Lifetime Max Chg Current", SUBCLASS_LIFETIME_DATA, 4, I2, "mA", -32767, 32767, 0);
+    LIFETIME_MAX_DSG_CURRENT = P::integer("Lifetime Max Dsg Current", SUBCLASS_LIFETIME_DATA, 6, I2, "mA", -32767, 32767, 0);
+    LIFETIME_MAX_PACK_VOLTAGE = P::integer("Lifetime Max Pack Voltage", SUBCLASS_LIFETIME_DATA, 8, U2, "mV", 0, 65535, 0);
+    LIFETIME_MIN_PACK_VOLTAGE =
+        P::integer("Lifetime Min Pack Voltage", SUBCLASS_LIFETIME_DATA, 10, U2, "mV", 0, 65535, 65535);
+
+    // Configuration: Lifetime Temp Samples
+    LT_FLASH_CNT = P::integer("LT Flash Cnt", SUBCLASS_LIFETIME_TEMP_SAMPLES, 0, U2, "", 0, 65535, 0);
+
+    // Configuration: Registers
+    PACK_CONFIGURATION = P::integer("Pack Configuration", SUBCLASS_REGISTERS, 0, U2, "", 0, 0xffff, 0x0161);
+    PACK_CONFIGURATION_B = P::integer("Pack Configuration B", SUBCLASS_REGISTERS, 2, U1, "", 0, 0xff, 0xa7);
+    PACK_CONFIGURATION_C = P::integer("Pack Configuration C", SUBCLASS_REGISTERS, 3, U1, "", 0, 0xff, 0x30);
+    LED_COMM_CONFIGURATION = P::integer("LED_Comm Configuration", SUBCLASS_REGISTERS, 4, U1, "", 0, 0xff, 0x00);
+    ALERT_CONFIGURATION = P::integer("Alert Configuration", SUBCLASS_REGISTERS, 5, U2, "", 0, 0xffff, 0x0000);
+    NUMBER_OF_SERIES_CELLS = P::integer("Number of Series Cell", SUBCLASS_REGISTERS, 7, U1, "", 1, 100, 1);
+
+    // Configuration: Lifetime Resolution
+    LT_TEMP_RES = P::integer("LT Temp Res", SUBCLASS_LIFETIME_RESOLUTION, 0, U1, "0.1°C", 0, 255, 10);
+    LT_CUR_RES = P::integer("LT Cur Res", SUBCLASS_LIFETIME_RESOLUTION, 1, U1, "mA", 0, 255, 100);
+    LT_V_RES = P::integer("LT V Res", SUBCLASS_LIFETIME_RESOLUTION, 2, U1, "mV", 0, 255, 25);
+    LT_UPDATE_TIME = P::integer("LT Update Time", SUBCLASS_LIFETIME_RESOLUTION, 3, U2, "s", 0, 65535, 60);
+
+    // Configuration: LED Display
+    LED_HOLD_TIME = P::integer("LED Hold Time", SUBCLASS_LED_DISPLAY, 0, U1, "s", 0, 255, 4);
+
+    // Configuration: Power
+    FLASH_UPDATE_OK_CELL_VOLT =
+        P::integer("Flash Update OK Cell Volt", SUBCLASS_POWER, 0, I2, "mV", 0, 4200, 2800);
+    SLEEP_CURRENT = P::integer("Sleep Current", SUBCLASS_POWER, 2, I2, "mA", 0, 100, 10);
+    SHUTDOWN_V = P::integer("Shutdown V", SUBCLASS_POWER, 10, U2, "mV", 0, 65535, 0);
+    FS_WAIT = P::integer("FS Wait", SUBCLASS_POWER, 12, U1, "s", 0, 255, 0);
+
+    // Gas Gauging: IT Cfg
+    LOAD_SELECT = P::integer("Load Select", SUBCLASS_IT_CFG, 0, U1, "", 0, 255, 1);
+    LOAD_MODE = P::integer("Load Mode", SUBCLASS_IT_CFG, 1, U1, "", 0, 255, 0);
+    RES_CURRENT = P::integer("Res Current", SUBCLASS_IT_CFG, 37, I2, "mA", 0, 1000, 10);
+    MAX_RES_FACTOR = P::integer("Max Res Factor", SUBCLASS_IT_CFG, 42, U1, "", 0, 255, 15);
+    MIN_RES_FACTOR = P::integer("Min Res Factor", SUBCLASS_IT_CFG, 43, U1, "", 0, 255, 3);
+    RA_FILTER = P::integer("Ra Filter", SUBCLASS_IT_CFG, 45, U2, "", 0, 1000, 800);
+    MIN_PASSED_CHARGE_FOR_QMAX =
+        P::integer("Min % Passed Charge for Qmax", SUBCLASS_IT_CFG, 47, U1, "%", 0, 100, 37);
+    QMAX_FILTER = P::integer("Qmax Filter", SUBCLASS_IT_CFG, 48, U1, "", 0, 255, 96);
+    TERMINATE_VOLTAGE = P::integer("Terminate Voltage", SUBCLASS_IT_CFG, 53, U2, "mV", 0, 65535, 3000);
+    TERM_V_DELTA = P::integer("Term V Delta", SUBCLASS_IT_CFG, 55, U2, "mV", 0, 65535, 200);
+    RESRELAX_TIME = P::integer("ResRelax Time", SUBCLASS_IT_CFG, 58, U2, "s", 0, 65534, 500);
+    USER_RATE_MA = P::integer("User Rate-mA", SUBCLASS_IT_CFG, 62, I2, "mA", 0, 9000, 0);
+    USER_RATE_PWR = P::integer("User Rate-Pwr", SUBCLASS_IT_CFG, 64, I2, "mW/cW", 0, 14000, 0);
+    RESERVE_CAP_MAH = P::integer("Reserve Cap-mAh", SUBCLASS_IT_CFG, 66, I2, "mAh", 0, 9000, 0);
+    RESERVE_ENERGY = P::integer("Reserve Energy", SUBCLASS_IT_CFG, 68, I2, "mWh/cWh", 0, 14000, 0);
+    MAX_SCALE_BACK_GRID = P::integer("Max Scale Back Grid", SUBCLASS_IT_CFG, 72, U1, "", 0, 15, 4);
+    MAX_DELTAV = P::integer("Max DeltaV", SUBCLASS_IT_CFG, 73, U2, "mV", 0, 65535, 200);
+    MIN_DELTAV = P::integer("Min DeltaV", SUBCLASS_IT_CFG, 75, U2, "mV", 0, 65535, 0);
+    MAX_SIM_RATE = P::integer("Max Sim Rate", SUBCLASS_IT_CFG, 77, U1, "C/rate", 0, 255, 1);
+    MIN_SIM_RATE = P::integer("Min Sim Rate", SUBCLASS_IT_CFG, 78, U1, "C/rate", 0, 255, 20);
+    RA_MAX_DELTA = P::integer("Ra Max Delta", SUBCLASS_IT_CFG, 79, U2, "", 0, 65535, 44);
+    TRACE_RESISTANCE = P::integer("Trace Resistance", SUBCLASS_IT_CFG, 81, U2, "mΩ", 0, 65535, 0);
+    DOWNSTREAM_RESISTANCE = P::integer("Downstream Resistance", SUBCLASS_IT_CFG, 83, U2, "mΩ", 0, 65535, 0);
+
+    // Gas Gauging: Current Thresholds
+    DSG_CURRENT_THRESHOLD = P::integer("Dsg Current Threshold", SUBCLASS_CURRENT_THRESHOLDS, 0, I2, "mA", 0, 2000, 60);
+    CHG_CURRENT_THRESHOLD = P::integer("Chg Current Threshold", SUBCLASS_CURRENT_THRESHOLDS, 2, I2, "mA", 0, 2000, 75);
+    QUIT_CURRENT = P::integer("Quit Current", SUBCLASS_CURRENT_THRESHOLDS, 4, I2, "mA", 0, 1000, 40);
+    DSG_RELAX_TIME = P::integer("Dsg Relax Time", SUBCLASS_CURRENT_THRESHOLDS, 6, U2, "s", 0, 8191, 60);
+    CHG_RELAX_TIME = P::integer("Chg Relax Time", SUBCLASS_CURRENT_THRESHOLDS, 8, U1, "s", 0, 255, 60);
+    QUIT_RELAX_TIME = P::integer("Quit Relax Time", SUBCLASS_CURRENT_THRESHOLDS, 9, U1, "s", 0, 63, 1);
+    MAX_IR_CORRECT = P::integer("Max IR Correct", SUBCLASS_CURRENT_THRESHOLDS, 10, U2, "mV", 0, 1000, 400);
+
+    // Gas Gauging: State
+    QMAX_CELL_0 = P::integer("Qmax Cell 0", SUBCLASS_STATE, 0, I2, "mAh", 0, 32767, 1000);
+    UPDATE_STATUS = P::integer("Update Status", SUBCLASS_STATE, 2, U1, "", 0, 0x06, 0x00);
+    CELL_V_AT_CHG_TERM = P::integer("Cell V at Chg Term", SUBCLASS_STATE, 3, I2, "mV", 0, 5000, 4200);
+    AVG_I_LAST_RUN = P::integer("Avg I Last Run", SUBCLASS_STATE, 5, I2, "mA", -32768, 32767, -299);
+    AVG_P_LAST_RUN = P::integer("Avg P Last Run", SUBCLASS_STATE, 7, I2, "mW", -32768, 32767, -1131);
+    CELL_DELTA_VOLTAGE = P::integer("Cell Delta Voltage", SUBCLASS_STATE, 9, I2, "mV", -32768, 32767, 2);
+    T_RISE = P::integer("T Rise", SUBCLASS_STATE, 11, I2, "", 0, 32767, 20);
+    T_TIME_CONSTANT = P::integer("T Time Constant", SUBCLASS_STATE, 13, I2, "", 0, 32767, 1000);
+
+    // Ra Tables: OCVa Table
+    CHEM_ID = P::integer("Chem ID", SUBCLASS_OCVA_TABLE, 0, U2, "", 0, 0xffff, 0x0100);
+
+    // Calibration: Data
+    CC_GAIN = P::float("CC Gain", SUBCLASS_CALIBRATION_DATA, 0, "mΩ", 1.0e-1, 4.0e1, 0.4768);
+    CC_DELTA = P::float("CC Delta", SUBCLASS_CALIBRATION_DATA, 4, "mΩ", 2.98e4, 1.19e7, 567744.56);
+    CC_OFFSET = P::integer("CC Offset", SUBCLASS_CALIBRATION_DATA, 8, I2, "", -32768, 32767, -1667);
+    BOARD_OFFSET = P::integer("Board Offset", SUBCLASS_CALIBRATION_DATA, 10, I1, "", -128, 127, 0);
+    INT_TEMP_OFFSET = P::integer("Int Temp Offset", SUBCLASS_CALIBRATION_DATA, 11, I1, "0.1°C", -128, 127, 0);
+    EXT_TEMP_OFFSET = P::integer("Ext Temp Offset", SUBCLASS_CALIBRATION_DATA, 12, I1, "0.1°C", -128, 127, 0);
+    VOLTAGE_DIVIDER = P::integer("Voltage Divider", SUBCLASS_CALIBRATION_DATA, 14, U2, "mV", 0, 65535, 5000);
+
+    // Calibration: Current
+    FILTER = P::integer("Filter", SUBCLASS_CALIBRATION_CURRENT, 0, U1, "", 0, 255, 239);
+    DEADBAND = P::integer("Deadband", SUBCLASS_CALIBRATION_CURRENT, 1, U1, "mA", 0, 255, 5);
+    CC_DEADBAND = P::integer("CC Deadband", SUBCLASS_CALIBRATION_CURRENT, 2, U1, "294 nV", 0, 255, 34);
+
+    // Security: Codes
+    SEALED_TO_UNSEALED = P::integer("Sealed to Unsealed", SUBCLASS_CODES, 0, U4, "", 0, 0xffff_ffff, 0x3672_0414);
+    UNSEALED_TO_FULL = P::integer("Unsealed to Full", SUBCLASS_CODES, 4, U4, "", 0, 0xffff_ffff, 0xffff_ffff);
+}
@@ -0,0 +1,98 @@
+//! Identification of the gauge variant and the features its firmware supports.
+//!
+//! All variants report DEVICE_TYPE 0x0100, they are told apart by FW_VERSION. Data flash offsets that moved between
+//! firmware releases are listed as [`Capabilities`], operations relying on them are refused on other firmware.
+use core::fmt::{Display, Formatter};
+
+use bitflags::bitflags;
+
+pub(crate) const BQ34Z100_DEVICE_TYPE: u16 = 0x0100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Original bq34z100
+    Bq34z100,
+    Bq34z100G1,
+    Bq34z100R2,
+    /// DEVICE_TYPE or FW_VERSION not in the table, only the standard commands are used
+    Unknown,
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let name = match self {
+            Variant::Bq34z100 => "bq34z100",
+            Variant::Bq34z100G1 => "bq34z100-G1",
+            Variant::Bq34z100R2 => "bq34z100-R2",
+            Variant::Unknown => "unknown",
+        };
+        return f.write_str(name);
+    }
+}
+
+bitflags! {
+    /// Firmware dependent features
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Capabilities: u8 {
+        /// LED_Comm Configuration at offset 4 of the Registers subclass
+        const LED_CONFIGURATION = 1 << 0;
+        /// Pack Configuration B and C at offset 2 and 3 of the Registers subclass
+        const PACK_CONFIGURATION_BC = 1 << 1;
+    }
+}
+
+/// FW_VERSION range, variant and supported features
+const FIRMWARE_TABLE: [(u16, u16, Variant, Capabilities); 3] = [
+    (0x0000, 0x0015, Variant::Bq34z100, Capabilities::LED_CONFIGURATION),
+    (
+        0x0016,
+        0x0017,
+        Variant::Bq34z100G1,
+        Capabilities::LED_CONFIGURATION.union(Capabilities::PACK_CONFIGURATION_BC),
+    ),
+    (0x0018, 0x00ff, Variant::Bq34z100R2, Capabilities::PACK_CONFIGURATION_BC),
+];
+
+/// Result of [`identify`](crate::Bq34z100g1::identify)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    pub variant: Variant,
+    pub device_type: u16,
+    pub fw_version: u16,
+    pub hw_version: u16,
+    pub df_version: u16,
+}
+
+impl DeviceInfo {
+    pub fn new(device_type: u16, fw_version: u16, hw_version: u16, df_version: u16) -> DeviceInfo {
+        let variant = match DeviceInfo::lookup(device_type, fw_version) {
+            Some((_, _, variant, _)) => variant,
+            None => Variant::Unknown,
+        };
+        return DeviceInfo { variant, device_type, fw_version, hw_version, df_version };
+    }
+
+    fn lookup(device_type: u16, fw_version: u16) -> Option<(u16, u16, Variant, Capabilities)> {
+        if device_type != BQ34Z100_DEVICE_TYPE {
+            return None;
+        }
+        return FIRMWARE_TABLE.into_iter().find(|(first, last, _, _)| (*first..=*last).contains(&fw_version));
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        return match DeviceInfo::lookup(self.device_type, self.fw_version) {
+            Some((_, _, _, capabilities)) => capabilities,
+            None => Capabilities::empty(),
+        };
+    }
+
+    pub fn supports(&self, capability: Capabilities) -> bool {
+        return self.capabilities().contains(capability);
+    }
+
+    /// Firmware as (major, minor), FW_VERSION is BCD coded, eg. 0x0017 is 0.17
+    pub fn firmware(&self) -> (u8, u8) {
+        let bcd = |byte: u8| (byte >> 4) * 10 + (byte & 0x0f);
+        return (bcd((self.fw_version >> 8) as u8), bcd(self.fw_version as u8));
+    }
+}
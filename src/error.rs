@@ -0,0 +1,249 @@
+//! Errors of the driver with the transaction or data flash location they happened at.
+use core::fmt::{Debug, Display, Formatter};
+
+use embedded_hal::i2c::ErrorKind;
+
+#[cfg(feature = "flashstream")]
+use crate::flashstream::FlashStreamParseError;
+use crate::registers::{BLOCK_DATA, DATA_FLASH_BLOCK, DATA_FLASH_CLASS};
+#[cfg(feature = "write")]
+use crate::DataFlashParameter;
+use crate::{Capabilities, DeviceInfo, PackConfigurationError, RetryOperations, SecurityMode, Variant};
+
+/// The transaction a bus error happened in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BusOperation {
+    /// Standard command read
+    Read { command: u8 },
+    /// Register write outside of the block data
+    Write { register: u8 },
+    /// Control() subcommand or key word
+    Control { subcommand: u16 },
+    /// Offset is counted from the start of the subclass
+    DataFlashRead { sub_class: u8, offset: u8 },
+    DataFlashWrite { sub_class: u8, offset: u8 },
+    /// Write or compare line of a flash stream file, address as given in the file
+    FlashStream { address: u8, register: u8 },
+}
+
+impl BusOperation {
+    /// Kind of transaction the retry policy is looked up for
+    pub(crate) fn retry_operations(&self) -> RetryOperations {
+        return match self {
+            BusOperation::Read { .. } | BusOperation::DataFlashRead { .. } => RetryOperations::READ,
+            BusOperation::Write { .. } | BusOperation::DataFlashWrite { .. } => RetryOperations::WRITE,
+            BusOperation::Control { .. } => RetryOperations::CONTROL,
+            BusOperation::FlashStream { .. } => RetryOperations::FLASH_STREAM,
+        };
+    }
+
+    /// Write of a single register, block data is attributed to the selected data flash block if there is one
+    pub(crate) fn register_write(register: u8, selected_block: Option<(u8, u8)>) -> BusOperation {
+        return match selected_block {
+            Some((sub_class, block)) if (BLOCK_DATA..BLOCK_DATA + 32).contains(&register) => {
+                BusOperation::DataFlashWrite { sub_class, offset: block * 32 + (register - BLOCK_DATA) }
+            }
+            _ => BusOperation::Write { register },
+        };
+    }
+}
+
+/// Data flash block selected by a write of DataFlashClass() or DataFlashBlock(), as (subclass, block)
+pub(crate) fn select_block(selected_block: Option<(u8, u8)>, register: u8, value: u8) -> Option<(u8, u8)> {
+    return match register {
+        DATA_FLASH_CLASS => Some((value, 0)),
+        DATA_FLASH_BLOCK => selected_block.map(|(sub_class, _)| (sub_class, value)),
+        _ => selected_block,
+    };
+}
+
+impl Display for BusOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        return match self {
+            BusOperation::Read { command } => write!(f, "read of command 0x{command:02x}"),
+            BusOperation::Write { register } => write!(f, "write of register 0x{register:02x}"),
+            BusOperation::Control { subcommand } => write!(f, "control 0x{subcommand:04x}"),
+            BusOperation::DataFlashRead { sub_class, offset } => {
+                write!(f, "data flash read of subclass {sub_class} offset {offset}")
+            }
+            BusOperation::DataFlashWrite { sub_class, offset } => {
+                write!(f, "data flash write of subclass {sub_class} offset {offset}")
+            }
+            BusOperation::FlashStream { address, register } => {
+                write!(f, "flash stream access of 0x{address:02x} register 0x{register:02x}")
+            }
+        };
+    }
+}
+
+#[derive(Debug)]
+pub enum Bq34Z100Error<E> {
+    /// A data flash parameter read back after the reset differs from the written one
+    NotStored {
+        parameter: &'static str,
+        sub_class: u8,
+        offset: u8,
+        expected: i32,
+        actual: i32,
+    },
+    I2C {
+        error: E,
+        operation: BusOperation,
+    },
+    XemicsConversionError,
+    #[cfg(feature = "flashstream")]
+    FlashStreamError {
+        error: FlashStreamParseError,
+    },
+    ChecksumError {
+        register: u8,
+        expected: u8,
+        actual: u8
+    },
+    SecurityAccessDenied {
+        requested: SecurityMode,
+        actual: SecurityMode,
+    },
+    InvalidPackConfiguration {
+        error: PackConfigurationError,
+    },
+    /// The operation needs a capability the identified firmware does not have
+    Unsupported {
+        capability: Capabilities,
+        variant: Variant,
+    },
+    /// BlockDataChecksum() of the gauge differs from the checksum of the block data read, on every attempt
+    BlockChecksumMismatch {
+        sub_class: u8,
+        block: u8,
+        expected: u8,
+        actual: u8,
+    },
+    /// DataFlashClass() and DataFlashBlock() read back another block than the selected one, on every attempt
+    BlockNotSelected {
+        sub_class: u8,
+        block: u8,
+        selected_sub_class: u8,
+        selected_block: u8,
+    },
+    /// A data flash block read back after the restore differs from the one of the image
+    BlockNotStored {
+        sub_class: u8,
+        block: u8,
+    },
+    /// The image was taken from another device type or firmware version than the one of the gauge
+    IncompatibleImage {
+        image: DeviceInfo,
+        device: DeviceInfo,
+    },
+    /// The pack has the SCALED bit set but its scale can not be read from the gauge (sealed or without the "write"
+    /// feature), give it with with_scale() or set_scale() of the driver
+    UnknownScale,
+    /// The value has the wrong kind for the data flash parameter or is out of its range
+    InvalidValue {
+        parameter: &'static str,
+    },
+}
+
+impl<E> Bq34Z100Error<E> {
+    /// Transaction of a bus error
+    pub fn operation(&self) -> Option<BusOperation> {
+        return match self {
+            Bq34Z100Error::I2C { operation, .. } => Some(*operation),
+            _ => None,
+        };
+    }
+}
+
+impl<E: embedded_hal::i2c::Error> Bq34Z100Error<E> {
+    /// Kind of a bus error, eg. to tell a NACK of a busy gauge from an arbitration loss
+    pub fn i2c_kind(&self) -> Option<ErrorKind> {
+        return match self {
+            Bq34Z100Error::I2C { error, .. } => Some(error.kind()),
+            _ => None,
+        };
+    }
+}
+
+/// NotStored error if the value read back differs from the written one
+#[cfg(feature = "write")]
+pub(crate) fn verify_stored<E>(
+    parameter: &DataFlashParameter,
+    expected: impl Into<i32>,
+    actual: impl Into<i32>,
+) -> Result<(), Bq34Z100Error<E>> {
+    let (expected, actual) = (expected.into(), actual.into());
+    if expected != actual {
+        let DataFlashParameter { name: parameter, sub_class, offset, .. } = *parameter;
+        return Err(Bq34Z100Error::NotStored { parameter, sub_class, offset, expected, actual });
+    }
+    return Ok(());
+}
+
+/// NotStored error if the bytes read back differ from the written ones
+#[cfg(feature = "write")]
+pub(crate) fn verify_stored_bytes<E>(
+    parameter: &DataFlashParameter,
+    expected: &[u8],
+    actual: &[u8],
+) -> Result<(), Bq34Z100Error<E>> {
+    if expected != actual {
+        let (expected, actual) = (parameter.reported(expected), parameter.reported(actual));
+        let DataFlashParameter { name: parameter, sub_class, offset, .. } = *parameter;
+        return Err(Bq34Z100Error::NotStored { parameter, sub_class, offset, expected, actual });
+    }
+    return Ok(());
+}
+
+impl<E: Debug> Display for Bq34Z100Error<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        return match self {
+            Bq34Z100Error::NotStored { parameter, sub_class, offset, expected, actual } => write!(
+                f,
+                "{parameter} (subclass {sub_class} offset {offset}) not stored, expected {expected} but read {actual}"
+            ),
+            Bq34Z100Error::I2C { error, operation } => write!(f, "I2C error in {operation}: {error:?}"),
+            Bq34Z100Error::XemicsConversionError => f.write_str("value out of range of the Xemics float format"),
+            #[cfg(feature = "flashstream")]
+            Bq34Z100Error::FlashStreamError { error } => write!(f, "flash stream error: {error}"),
+            Bq34Z100Error::ChecksumError { register, expected, actual } => {
+                write!(f, "compare of register 0x{register:02x} failed, expected 0x{expected:02x} but read 0x{actual:02x}")
+            }
+            Bq34Z100Error::SecurityAccessDenied { requested, actual } => {
+                write!(f, "access denied, requested {requested:?} but the gauge is {actual:?}")
+            }
+            Bq34Z100Error::InvalidPackConfiguration { error } => write!(f, "invalid pack configuration: {error:?}"),
+            Bq34Z100Error::Unsupported { capability, variant } => {
+                write!(f, "{capability:?} is not supported by the {variant} firmware")
+            }
+            Bq34Z100Error::BlockChecksumMismatch { sub_class, block, expected, actual } => write!(
+                f,
+                "data flash block {block} of subclass {sub_class} has checksum 0x{expected:02x} but read 0x{actual:02x}"
+            ),
+            Bq34Z100Error::BlockNotSelected { sub_class, block, selected_sub_class, selected_block } => write!(
+                f,
+                "data flash block {block} of subclass {sub_class} not selected, the gauge has block {selected_block} of subclass {selected_sub_class}"
+            ),
+            Bq34Z100Error::BlockNotStored { sub_class, block } => {
+                write!(f, "data flash block {block} of subclass {sub_class} not stored")
+            }
+            Bq34Z100Error::IncompatibleImage { image, device } => write!(
+                f,
+                "image of device type 0x{:04x} firmware 0x{:04x} does not fit device type 0x{:04x} firmware 0x{:04x}",
+                image.device_type, image.fw_version, device.device_type, device.fw_version
+            ),
+            Bq34Z100Error::UnknownScale => f.write_str("scale of the SCALED pack unknown, see with_scale()"),
+            Bq34Z100Error::InvalidValue { parameter } => write!(f, "invalid value for {parameter}"),
+        };
+    }
+}
+
+impl<E: Debug> core::error::Error for Bq34Z100Error<E> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        return match self {
+            #[cfg(feature = "flashstream")]
+            Bq34Z100Error::FlashStreamError { error: FlashStreamParseError::InvalidNumber(error) } => Some(error),
+            _ => None,
+        };
+    }
+}
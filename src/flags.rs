@@ -0,0 +1,107 @@
+//! Status bits of Flags() and FlagsB().
+//!
+//! Bit positions follow the order the register is read in: the byte the reference manual calls the high byte is
+//! bits 0 to 7 here. Undocumented bits are kept, so a diff also reports them.
+use bitflags::bitflags;
+
+bitflags! {
+    /// Flags() standard command
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Flags: u16 {
+        /// CHG, (fast) charging allowed
+        const CHG = 1 << 0;
+        /// FC, full charge detected
+        const FC = 1 << 1;
+        /// XCHG, charging not allowed
+        const XCHG = 1 << 2;
+        /// CHG_INH, charge inhibit, temperature outside the charge range
+        const CHG_INH = 1 << 3;
+        /// BATLOW, battery low voltage
+        const BATLOW = 1 << 4;
+        /// BATHI, battery high voltage
+        const BATHI = 1 << 5;
+        /// OTD, over temperature in discharge
+        const OTD = 1 << 6;
+        /// OTC, over temperature in charge
+        const OTC = 1 << 7;
+        /// DSG, discharging
+        const DSG = 1 << 8;
+        /// SOCF, StateOfCharge() final threshold reached
+        const SOCF = 1 << 9;
+        /// SOC1, StateOfCharge() threshold 1 reached
+        const SOC1 = 1 << 10;
+        /// CF, condition flag, a learning cycle is needed
+        const CF = 1 << 12;
+        /// TDD, tab disconnect detected
+        const TDD = 1 << 13;
+        /// ISD, internal short detected
+        const ISD = 1 << 14;
+        /// OCVTAKEN, open circuit voltage measurement taken in relax
+        const OCVTAKEN = 1 << 15;
+
+        const _ = !0;
+    }
+}
+
+bitflags! {
+    /// FlagsB() standard command
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct FlagsB: u16 {
+        /// DTRC, the gauge detected that RemainingCapacity() should be recalculated
+        const DTRC = 1 << 1;
+        /// DODEOC, DOD at end of charge is being updated
+        const DODEOC = 1 << 2;
+        /// FIRSTDOD, the first DOD after a reset was taken
+        const FIRSTDOD = 1 << 5;
+        /// LIFE, lifetime data is being collected
+        const LIFE = 1 << 6;
+        /// SOH, StateOfHealth() calculation is active
+        const SOH = 1 << 7;
+
+        const _ = !0;
+    }
+}
+
+impl From<u16> for Flags {
+    fn from(value: u16) -> Self {
+        return Flags::from_bits_retain(value);
+    }
+}
+
+impl From<u16> for FlagsB {
+    fn from(value: u16) -> Self {
+        return FlagsB::from_bits_retain(value);
+    }
+}
+
+/// Bits that changed between two reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagChanges<F> {
+    pub set: F,
+    pub cleared: F,
+}
+
+impl<F: bitflags::Flags + Copy> FlagChanges<F> {
+    pub fn is_empty(&self) -> bool {
+        return self.set.is_empty() && self.cleared.is_empty();
+    }
+
+    /// All bits that toggled in either direction
+    pub fn changed(&self) -> F {
+        return self.set.union(self.cleared);
+    }
+}
+
+impl Flags {
+    /// Bits that are set and cleared in self compared to the previous read
+    pub fn diff(self, previous: Flags) -> FlagChanges<Flags> {
+        return FlagChanges { set: self.difference(previous), cleared: previous.difference(self) };
+    }
+}
+
+impl FlagsB {
+    /// Bits that are set and cleared in self compared to the previous read
+    pub fn diff(self, previous: FlagsB) -> FlagChanges<FlagsB> {
+        return FlagChanges { set: self.difference(previous), cleared: previous.difference(self) };
+    }
+}
@@ -0,0 +1,94 @@
+//! Parsing of TI flash stream lines (as exported by bqStudio), shared by the blocking and the async driver.
+use core::fmt::{Display, Formatter};
+use core::num::ParseIntError;
+use core::str::SplitAsciiWhitespace;
+
+use crate::Bq34Z100Error;
+
+/// Why a line of a flash stream file was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlashStreamParseError {
+    /// A byte or the wait time is no valid hex number
+    InvalidNumber(ParseIntError),
+    /// The line ended before the address or the register
+    MissingField,
+    /// `%` placeholders of dynamic addresses are not supported
+    DynamicAddress,
+}
+
+impl Display for FlashStreamParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        return match self {
+            FlashStreamParseError::InvalidNumber(error) => write!(f, "invalid number: {error}"),
+            FlashStreamParseError::MissingField => f.write_str("line ended"),
+            FlashStreamParseError::DynamicAddress => f.write_str("dynamic addresses are not supported"),
+        };
+    }
+}
+
+pub(crate) enum FlashStreamLine<'a> {
+    /// Comments and empty lines
+    Skip,
+    /// `W: <address> <register> <bytes..>`, write the bytes starting at register
+    Write {
+        address: u8,
+        register: u8,
+        values: HexBytes<'a>,
+    },
+    /// `X: <ms>`, wait for the given time
+    Wait { ms: u32 },
+    /// `C: <address> <register> <bytes..>`, read back and compare starting at register
+    Compare {
+        address: u8,
+        register: u8,
+        values: HexBytes<'a>,
+    },
+}
+
+pub(crate) struct HexBytes<'a>(SplitAsciiWhitespace<'a>);
+
+impl<'a> Iterator for HexBytes<'a> {
+    type Item = Result<u8, ParseIntError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|value| u8::from_str_radix(value, 16))
+    }
+}
+
+pub(crate) fn map_parse_err<E>(e: ParseIntError) -> Bq34Z100Error<E> {
+    return Bq34Z100Error::FlashStreamError { error: FlashStreamParseError::InvalidNumber(e) };
+}
+
+fn missing_part<E>() -> Bq34Z100Error<E> {
+    return Bq34Z100Error::FlashStreamError { error: FlashStreamParseError::MissingField };
+}
+
+pub(crate) fn parse_line<E>(line: &str) -> Result<FlashStreamLine<'_>, Bq34Z100Error<E>> {
+    if line.starts_with(';') {
+        return Ok(FlashStreamLine::Skip);
+    }
+    if line.contains('%') {
+        return Err(Bq34Z100Error::FlashStreamError { error: FlashStreamParseError::DynamicAddress });
+    }
+    let mut parts = line.split_ascii_whitespace();
+    let start = match parts.next() {
+        Some(start) => start,
+        None => return Ok(FlashStreamLine::Skip),
+    };
+    if start.eq("W:") || start.eq("C:") {
+        let address_string = parts.next().ok_or_else(missing_part)?;
+        let address = u8::from_str_radix(address_string, 16).map_err(map_parse_err)?;
+        let register_string = parts.next().ok_or_else(missing_part)?;
+        let register = u8::from_str_radix(register_string, 16).map_err(map_parse_err)?;
+        let values = HexBytes(parts);
+        if start.eq("W:") {
+            return Ok(FlashStreamLine::Write { address, register, values });
+        }
+        return Ok(FlashStreamLine::Compare { address, register, values });
+    } else if start.eq("X:") {
+        let wait_for_string = parts.next().ok_or_else(missing_part)?;
+        let ms = u32::from_str_radix(wait_for_string, 16).map_err(map_parse_err)?;
+        return Ok(FlashStreamLine::Wait { ms });
+    }
+    return Ok(FlashStreamLine::Skip);
+}
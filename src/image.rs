@@ -0,0 +1,397 @@
+//! Image of the data flash for backup and restore.
+//!
+//! A [`DataFlashImage`] holds every block of the subclasses in [`DataFlash::SUBCLASSES`](crate::DataFlash), whether
+//! or not the bytes belong to a named parameter, the security keys of the Codes subclass aside: a replacement board keeps its own keys, so the driver can still unseal it after the
+//! restore. `dump_data_flash()` of the drivers reads the image, `restore_data_flash()` writes it back, but only to a
+//! gauge with the device type and firmware version the image was taken from. The subclasses the gauge updates itself
+//! ([`DataFlashSubclass::updated_by_gauge`](crate::DataFlashSubclass)) are only verified by
+//! `restore_data_flash_strict()`.
+//!
+//! The binary format is stable: the magic "BQ34DF" and the format version, the device type, firmware, hardware and
+//! data flash version as big endian words, the number of blocks and for every block its subclass, block number and 32
+//! data bytes, closed by a checksum byte like the one of a data flash block. With the "std" feature the image can also
+//! be written and read as JSON, the block data as hex strings.
+#[cfg(feature = "std")]
+use core::fmt::Write;
+#[cfg(feature = "std")]
+use std::string::String;
+
+use crate::registers::*;
+use crate::{DataFlash, DataFlashParameter, DataFlashValue, DeviceInfo};
+
+const MAGIC: &[u8; 6] = b"BQ34DF";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + 8 + 1;
+const BLOCK_ENTRY_SIZE: usize = 2 + FLASH_BLOCK_SIZE;
+
+/// Every block of the subclasses as (subclass, block) in data flash order, and their number
+const fn collect_blocks() -> ([(u8, u8); 256], usize) {
+    let mut blocks = [(0, 0); 256];
+    let mut count = 0;
+    let mut i = 0;
+    while i < DataFlash::SUBCLASSES.len() {
+        let subclass = &DataFlash::SUBCLASSES[i];
+        let mut block = 0;
+        while subclass.id != SUBCLASS_CODES && block < subclass.blocks() {
+            blocks[count] = (subclass.id, block);
+            count += 1;
+            block += 1;
+        }
+        i += 1;
+    }
+    return (blocks, count);
+}
+
+const BLOCK_COUNT: usize = collect_blocks().1;
+const BLOCKS: [(u8, u8); BLOCK_COUNT] = {
+    let (all, _) = collect_blocks();
+    let mut blocks = [(0, 0); BLOCK_COUNT];
+    let mut i = 0;
+    while i < BLOCK_COUNT {
+        blocks[i] = all[i];
+        i += 1;
+    }
+    blocks
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageError {
+    /// The data does not start with the magic of the binary format
+    InvalidFormat,
+    UnsupportedVersion(u8),
+    Truncated,
+    ChecksumMismatch,
+    /// Syntax error or unexpected member at the byte position
+    InvalidJson {
+        position: usize,
+    },
+    /// The block does not belong to the image, eg. one of another data flash layout
+    UnknownBlock {
+        sub_class: u8,
+        block: u8,
+    },
+    MissingBlock {
+        sub_class: u8,
+        block: u8,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataFlashImage {
+    device: DeviceInfo,
+    pub(crate) blocks: [[u8; FLASH_BLOCK_SIZE]; BLOCK_COUNT],
+}
+
+impl DataFlashImage {
+    /// Blocks of the image as (subclass, block), in the order they are dumped, restored and serialized
+    pub const BLOCKS: &'static [(u8, u8)] = &BLOCKS;
+    /// Size of the binary format
+    pub const BINARY_SIZE: usize = HEADER_SIZE + BLOCK_COUNT * BLOCK_ENTRY_SIZE + 1;
+
+    pub(crate) fn new(device: DeviceInfo) -> Self {
+        return DataFlashImage { device, blocks: [[0; FLASH_BLOCK_SIZE]; BLOCK_COUNT] };
+    }
+
+    /// Identity of the gauge the image was taken from
+    pub fn device(&self) -> DeviceInfo {
+        return self.device;
+    }
+
+    /// Whether the image may be restored onto the gauge, device type and firmware version have to match
+    pub fn compatible_with(&self, device: &DeviceInfo) -> bool {
+        return self.device.device_type == device.device_type && self.device.fw_version == device.fw_version;
+    }
+
+    pub fn block(&self, sub_class: u8, block: u8) -> Option<&[u8; FLASH_BLOCK_SIZE]> {
+        let index = BLOCKS.iter().position(|&known| known == (sub_class, block))?;
+        return Some(&self.blocks[index]);
+    }
+
+    /// Value of a parameter in the image, None for the security keys
+    pub fn value(&self, parameter: &DataFlashParameter) -> Option<DataFlashValue> {
+        let mut bytes = [0u8; FLASH_BLOCK_SIZE];
+        for (offset, part) in parameter.block_parts() {
+            let block = self.block(parameter.sub_class, offset / FLASH_BLOCK_SIZE as u8)?;
+            let start = offset as usize % FLASH_BLOCK_SIZE;
+            let len = part.len();
+            bytes[part].copy_from_slice(&block[start..start + len]);
+        }
+        return Some(parameter.decode(&bytes));
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::BINARY_SIZE] {
+        let mut bytes = [0u8; Self::BINARY_SIZE];
+        bytes[..MAGIC.len()].copy_from_slice(MAGIC);
+        bytes[MAGIC.len()] = FORMAT_VERSION;
+        let device = self.device;
+        let identity = [device.device_type, device.fw_version, device.hw_version, device.df_version];
+        for (i, word) in identity.iter().enumerate() {
+            bytes[MAGIC.len() + 1 + i * 2..][..2].copy_from_slice(&word.to_be_bytes());
+        }
+        bytes[HEADER_SIZE - 1] = BLOCK_COUNT as u8;
+        for (i, (&(sub_class, block), data)) in BLOCKS.iter().zip(self.blocks.iter()).enumerate() {
+            let entry = &mut bytes[HEADER_SIZE + i * BLOCK_ENTRY_SIZE..][..BLOCK_ENTRY_SIZE];
+            entry[0] = sub_class;
+            entry[1] = block;
+            entry[2..].copy_from_slice(data);
+        }
+        bytes[Self::BINARY_SIZE - 1] = checksum(&bytes[..Self::BINARY_SIZE - 1]);
+        return bytes;
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        if bytes.len() < MAGIC.len() + 1 {
+            return Err(ImageError::Truncated);
+        }
+        if &bytes[..MAGIC.len()] != MAGIC {
+            return Err(ImageError::InvalidFormat);
+        }
+        if bytes[MAGIC.len()] != FORMAT_VERSION {
+            return Err(ImageError::UnsupportedVersion(bytes[MAGIC.len()]));
+        }
+        if bytes.len() < HEADER_SIZE {
+            return Err(ImageError::Truncated);
+        }
+        let count = bytes[HEADER_SIZE - 1] as usize;
+        let size = HEADER_SIZE + count * BLOCK_ENTRY_SIZE + 1;
+        if bytes.len() < size {
+            return Err(ImageError::Truncated);
+        }
+        if checksum(&bytes[..size - 1]) != bytes[size - 1] {
+            return Err(ImageError::ChecksumMismatch);
+        }
+
+        let word = |i: usize| u16::from_be_bytes([bytes[MAGIC.len() + 1 + i * 2], bytes[MAGIC.len() + 2 + i * 2]]);
+        let mut image = DataFlashImage::new(DeviceInfo::new(word(0), word(1), word(2), word(3)));
+        let mut seen = [false; BLOCK_COUNT];
+        for entry in bytes[HEADER_SIZE..size - 1].chunks_exact(BLOCK_ENTRY_SIZE) {
+            let mut data = [0u8; FLASH_BLOCK_SIZE];
+            data.copy_from_slice(&entry[2..]);
+            image.set_block(&mut seen, entry[0], entry[1], data)?;
+        }
+        return image.complete(&seen);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        let device = self.device;
+        // writing to a String does not fail
+        let _ = write!(
+            json,
+            "{{\n  \"format\": {FORMAT_VERSION},\n  \"device_type\": {},\n  \"fw_version\": {},\n  \"hw_version\": {},\n  \"df_version\": {},\n  \"blocks\": [\n",
+            device.device_type, device.fw_version, device.hw_version, device.df_version
+        );
+        for (i, (&(sub_class, block), data)) in BLOCKS.iter().zip(self.blocks.iter()).enumerate() {
+            let _ = write!(json, "    {{ \"sub_class\": {sub_class}, \"block\": {block}, \"data\": \"");
+            for byte in data {
+                let _ = write!(json, "{byte:02x}");
+            }
+            json.push_str(if i + 1 < BLOCK_COUNT { "\" },\n" } else { "\" }\n" });
+        }
+        json.push_str("  ]\n}\n");
+        return json;
+    }
+
+    /// Reads an image written by to_json(), the members may come in any order
+    #[cfg(feature = "std")]
+    pub fn from_json(json: &str) -> Result<Self, ImageError> {
+        let mut parser = JsonParser { text: json.as_bytes(), position: 0 };
+        let mut format = None;
+        let mut identity = [None; 4];
+        let mut image = DataFlashImage::new(DeviceInfo::new(0, 0, 0, 0));
+        let mut seen = [false; BLOCK_COUNT];
+        parser.members(|parser, key| {
+            match key {
+                "format" => format = Some(parser.number(u8::MAX as u64)? as u8),
+                "device_type" => identity[0] = Some(parser.number(u16::MAX as u64)? as u16),
+                "fw_version" => identity[1] = Some(parser.number(u16::MAX as u64)? as u16),
+                "hw_version" => identity[2] = Some(parser.number(u16::MAX as u64)? as u16),
+                "df_version" => identity[3] = Some(parser.number(u16::MAX as u64)? as u16),
+                "blocks" => parser.elements(|parser| image.parse_block(parser, &mut seen))?,
+                _ => return Err(parser.error()),
+            }
+            return Ok(());
+        })?;
+        if parser.peek().is_some() {
+            return Err(parser.error());
+        }
+
+        match format {
+            Some(FORMAT_VERSION) => {}
+            Some(version) => return Err(ImageError::UnsupportedVersion(version)),
+            None => return Err(ImageError::InvalidFormat),
+        }
+        let [Some(device_type), Some(fw_version), Some(hw_version), Some(df_version)] = identity else {
+            return Err(ImageError::InvalidFormat);
+        };
+        image.device = DeviceInfo::new(device_type, fw_version, hw_version, df_version);
+        return image.complete(&seen);
+    }
+
+    #[cfg(feature = "std")]
+    fn parse_block(&mut self, parser: &mut JsonParser<'_>, seen: &mut [bool; BLOCK_COUNT]) -> Result<(), ImageError> {
+        let (mut sub_class, mut block, mut data) = (None, None, None);
+        let start = parser.position;
+        parser.members(|parser, key| {
+            match key {
+                "sub_class" => sub_class = Some(parser.number(u8::MAX as u64)? as u8),
+                "block" => block = Some(parser.number(u8::MAX as u64)? as u8),
+                "data" => data = Some(parser.hex_block()?),
+                _ => return Err(parser.error()),
+            }
+            return Ok(());
+        })?;
+        let (Some(sub_class), Some(block), Some(data)) = (sub_class, block, data) else {
+            return Err(ImageError::InvalidJson { position: start });
+        };
+        return self.set_block(seen, sub_class, block, data);
+    }
+
+    fn set_block(
+        &mut self,
+        seen: &mut [bool; BLOCK_COUNT],
+        sub_class: u8,
+        block: u8,
+        data: [u8; FLASH_BLOCK_SIZE],
+    ) -> Result<(), ImageError> {
+        let index = BLOCKS
+            .iter()
+            .position(|&known| known == (sub_class, block))
+            .ok_or(ImageError::UnknownBlock { sub_class, block })?;
+        self.blocks[index] = data;
+        seen[index] = true;
+        return Ok(());
+    }
+
+    fn complete(self, seen: &[bool; BLOCK_COUNT]) -> Result<Self, ImageError> {
+        if let Some(index) = seen.iter().position(|seen| !seen) {
+            let (sub_class, block) = BLOCKS[index];
+            return Err(ImageError::MissingBlock { sub_class, block });
+        }
+        return Ok(self);
+    }
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    return 255 - bytes.iter().fold(0_u8, |sum, &byte| sum.wrapping_add(byte));
+}
+
+/// Just enough JSON for the images: objects, arrays, strings without escapes and unsigned numbers
+#[cfg(feature = "std")]
+struct JsonParser<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+#[cfg(feature = "std")]
+impl<'a> JsonParser<'a> {
+    fn error(&self) -> ImageError {
+        return ImageError::InvalidJson { position: self.position };
+    }
+
+    /// Next byte after white space
+    fn peek(&mut self) -> Option<u8> {
+        while self.text.get(self.position).is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.position += 1;
+        }
+        return self.text.get(self.position).copied();
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ImageError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error());
+        }
+        self.position += 1;
+        return Ok(());
+    }
+
+    fn string(&mut self) -> Result<&'a str, ImageError> {
+        self.expect(b'"')?;
+        let start = self.position;
+        while let Some(&byte) = self.text.get(self.position) {
+            match byte {
+                b'"' => {
+                    self.position += 1;
+                    return core::str::from_utf8(&self.text[start..self.position - 1]).map_err(|_| self.error());
+                }
+                b'\\' => return Err(self.error()),
+                _ => self.position += 1,
+            }
+        }
+        return Err(self.error());
+    }
+
+    fn number(&mut self, max: u64) -> Result<u64, ImageError> {
+        self.peek();
+        let start = self.position;
+        let mut value: u64 = 0;
+        while let Some(digit) = self.text.get(self.position).filter(|byte| byte.is_ascii_digit()) {
+            value = value.saturating_mul(10).saturating_add((digit - b'0') as u64);
+            self.position += 1;
+        }
+        if self.position == start || value > max {
+            return Err(ImageError::InvalidJson { position: start });
+        }
+        return Ok(value);
+    }
+
+    fn hex_block(&mut self) -> Result<[u8; FLASH_BLOCK_SIZE], ImageError> {
+        let start = self.position;
+        let text = self.string()?;
+        let mut data = [0u8; FLASH_BLOCK_SIZE];
+        if text.len() != FLASH_BLOCK_SIZE * 2 {
+            return Err(ImageError::InvalidJson { position: start });
+        }
+        for (byte, digits) in data.iter_mut().zip(text.as_bytes().chunks_exact(2)) {
+            let digits = core::str::from_utf8(digits).map_err(|_| ImageError::InvalidJson { position: start })?;
+            *byte = u8::from_str_radix(digits, 16).map_err(|_| ImageError::InvalidJson { position: start })?;
+        }
+        return Ok(data);
+    }
+
+    /// Calls member for the key of every member of an object, it has to consume the value
+    fn members(
+        &mut self,
+        mut member: impl FnMut(&mut Self, &'a str) -> Result<(), ImageError>,
+    ) -> Result<(), ImageError> {
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            let key = self.string()?;
+            self.expect(b':')?;
+            member(self, key)?;
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// Calls element for every element of an array, it has to consume it
+    fn elements(&mut self, mut element: impl FnMut(&mut Self) -> Result<(), ImageError>) -> Result<(), ImageError> {
+        self.expect(b'[')?;
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            element(self)?;
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+}
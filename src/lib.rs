@@ -1,11 +1,3 @@
-#![no_main]
-#[cfg(feature = "std")]
-extern crate std;
-
-use embedded_hal::{delay::DelayNs, i2c::I2c};
-
-const BQ34Z100_G1_ADDRESS: u8 = 0x55;
-
 //
 //  bq34z100g1.cpp
 //  SMC
@@ -14,1237 +6,1517 @@ const BQ34Z100_G1_ADDRESS: u8 = 0x55;
 //  directly ported from
 //  https://github.com/xkam1x/BQ34Z100G1/blob/master/bq34z100g1.cpp by Kamran Ahmad on 08/05/2019.
 //  Xemics conversion from https://github.com/Ralim/BQ34Z100/blob/master/bq34z100.cpp
+#![no_std]
+#![allow(clippy::needless_return)]
+#[cfg(feature = "std")]
+extern crate std;
+
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+#[cfg(feature = "async")]
+pub mod asynch;
+pub mod cache;
+mod control;
+mod data_flash;
+mod device;
+mod error;
+#[cfg(feature = "flashstream")]
+mod flashstream;
+mod flags;
 #[cfg(feature = "write")]
-fn xemics_to_double(x: u32) -> f32 {
-    let mut b_is_positive = false;
-    let f_exponent: f32;
-    let mut f_result: f32;
-    let v_msbyte: u8 = (x >> 24) as u8;
-    let mut v_mid_hi_byte: u8 = (x >> 16) as u8;
-    let v_mid_lo_byte: u8 = (x >> 8) as u8;
-    let v_lsbyte: u8 = x as u8;
-    // Get the sign, its in the 0x00 80 00 00 bit
-    if (v_mid_hi_byte & 128) == 0 {
-        b_is_positive = true;
+pub mod image;
+#[cfg(feature = "record")]
+pub mod recording;
+pub mod pack_configuration;
+mod registers;
+mod retry;
+mod security;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+pub mod snapshot;
+mod status;
+mod timing;
+#[cfg(feature = "write")]
+pub mod transaction;
+pub mod typestate;
+pub mod units;
+pub mod xemics;
+
+#[cfg(feature = "write")]
+use pack_configuration::PACK_CONFIGURATION_MASK;
+#[cfg(feature = "write")]
+use error::{verify_stored, verify_stored_bytes};
+#[cfg(feature = "write")]
+use image::DataFlashImage;
+#[cfg(feature = "write")]
+use transaction::{DataFlashTransaction, TransactionReport};
+use registers::*;
+
+pub use control::ControlCommand;
+pub use data_flash::{
+    DataFlash, DataFlashParameter, DataFlashSubclass, DataFlashText, DataFlashType, DataFlashValue,
+};
+pub use device::{Capabilities, DeviceInfo, Variant};
+pub use error::{Bq34Z100Error, BusOperation};
+#[cfg(feature = "flashstream")]
+pub use flashstream::FlashStreamParseError;
+pub use flags::{FlagChanges, Flags, FlagsB};
+pub use pack_configuration::{
+    PackConfiguration, PackConfigurationB, PackConfigurationBuilder, PackConfigurationC, PackConfigurationError,
+};
+pub use registers::BQ34Z100_G1_ADDRESS;
+pub use retry::{RetryOperations, RetryPolicy, RetryStatistics};
+pub use security::{SecurityKey, SecurityMode};
+pub use snapshot::{GaugeSnapshot, SnapshotGroups};
+pub use status::GaugeStatus;
+pub use timing::BusTiming;
+pub use typestate::Bq34z100g1TypestateDriver;
+pub use units::{
+    DeciKelvin, Measurements, MilliampHours, Milliamps, Millivolts, MilliwattHours, Milliwatts, Minutes, Scale,
+};
+
+#[cfg(feature = "async")]
+pub use asynch::{Bq34z100g1Async, Bq34z100g1AsyncDriver, GaugeStatusAsync, MeasurementsAsync};
+#[cfg(all(feature = "async", feature = "flashstream"))]
+pub use asynch::FlashStreamProgrammerAsync;
+#[cfg(all(feature = "async", feature = "write"))]
+pub use asynch::{GaugeCalibratorAsync, GaugeConfiguratorAsync};
+
+/// Mean and variance of the samples taken during calibration.
+#[cfg(feature = "write")]
+fn mean_and_variance(samples: &[f32]) -> (f32, f32) {
+    let mut mean: f32 = 0.0;
+    for sample in samples {
+        mean += sample;
     }
+    mean /= samples.len() as f32;
 
-    // Get the exponent, it's 2^(MSbyte - 0x80)
-    f_exponent = 2.0_f32.powf(((v_msbyte as i16) - 128) as f32);
-    // Or in 0x80 to the MidHiByte
-    v_mid_hi_byte = (v_mid_hi_byte | 128) as u8;
-    // get value out of midhi byte
-    f_result = (v_mid_hi_byte as f32) * 65536.0;
-    // add in midlow byte
-    f_result = f_result + (v_mid_lo_byte as f32 * 256.0) as f32;
-    // add in LS byte
-    f_result = f_result + v_lsbyte as f32;
-    // multiply by 2^-24 to get the actual fraction
-    f_result = f_result * 2.0_f32.powf(-24.0);
-    // multiply fraction by the ‘exponent’ part
-    f_result = f_result * f_exponent;
-    // Make negative if necessary
-    if b_is_positive {
-        return f_result;
-    } else {
-        return -f_result;
+    let mut variance: f32 = 0.0;
+    for sample in samples {
+        variance += (sample - mean) * (sample - mean);
     }
+    variance /= samples.len() as f32;
+    return (mean, variance);
 }
 
+/// Computes the new CC Gain and CC Delta (both xemics encoded) from the currently stored CC Gain and the
+/// measured mean current while applied_current was flowing. Returns None if the stored gain is zero or a result is
+/// out of the xemics range.
 #[cfg(feature = "write")]
-fn float_to_xemics(mut x: f32) -> u32 {
-    let mut b_negative = false;
-
-    // Vermeidung von Logarithmus von Null
-    if x == 0.0 {
-        x = 0.00001;
+fn sense_resistor_gains(cc_gain: u32, current_mean: f32, applied_current: i16) -> Option<(u32, u32)> {
+    let float_cc_gain = xemics::decode(cc_gain);
+    if !float_cc_gain.is_normal() {
+        return None;
     }
 
-    // Überprüfung auf negative Zahl
-    if x < 0.0 {
-        b_negative = true;
-        x = -x;
-    }
+    let gain_resistence: f32 = 4.768 / float_cc_gain;
 
-    // Korrekten Exponenten finden
-    let i_exp = (x.log2().floor() + 1.0) as i32;
+    let temp: f32 = (current_mean * gain_resistence) / applied_current as f32;
+
+    let new_cc_gain: u32 = xemics::encode(4.768 / temp)?;
+    let new_cc_delta: u32 = xemics::encode(5677445.6 / temp)?;
+    return Some((new_cc_gain, new_cc_delta));
+}
 
-    // MS-Byte ist der Exponent + 0x80
-    let i_byte1 = (i_exp + 128) as u32;
+/// Implements the driver traits and the bus access for a driver struct, blocking with [] [] and async with
+/// [async] [.await], so both drivers run the very same protocol sequences
+macro_rules! gauge_driver {
+    (
+        [$($async:tt)?] [$($await:tt)*]
+        $Driver:ident: $Gauge:ident, $FlashStream:ident, $Configurator:ident, $Calibrator:ident; $Measurements:ident
+    ) => {
+        impl<I2C, DELAY, E: embedded_hal::i2c::Error> $Gauge<E> for $Driver<I2C, DELAY>
+        where
+            I2C: I2c<Error = E>,
+            DELAY: DelayNs,
+        {
+            $($async)? fn read_2_register_as_u16(&mut self, address: u8) -> Result<u16, Bq34Z100Error<E>> {
+                let data: [u8; 1] = [address];
+                let mut buffer: [u8; 2] = [0; 2];
+                self.bus_write_read(BusOperation::Read { command: address }, self.address, &data, &mut buffer)$($await)*?;
+                Ok(u16::from_le_bytes([buffer[0], buffer[1]]))
+            }
 
-    // Eingabe durch diesen Exponenten teilen, um Mantisse zu erhalten
-    let f_mantissa = x / 2f32.powi(i_exp);
+            $($async)? fn read_1_register_as_u8(&mut self, address: u8) -> Result<u8, Bq34Z100Error<E>> {
+                let data: [u8; 1] = [address];
+                let mut buffer: [u8; 1] = [0; 1];
+                self.bus_write_read(BusOperation::Read { command: address }, self.address, &data, &mut buffer)$($await)*?;
+                Ok(buffer[0])
+            }
 
-    // Skalierung
-    let scaled_mantissa = f_mantissa * 2f32.powi(24);
+            $($async)? fn read_control(&mut self, address_lsb: u8, address_msb: u8) -> Result<u16, Bq34Z100Error<E>> {
+                let data: [u8; 3] = [CONTROL, address_lsb, address_msb];
+                self.control_write(&data)$($await)*?;
+                return self.read_2_register_as_u16(CONTROL)$($await)*;
+            }
 
-    // Aufteilung der Mantisse in 3 Bytes
-    let i_byte2 = ((scaled_mantissa / 65536.0) as u32) & 0xFF;
-    let i_byte3 = ((scaled_mantissa / 256.0) as u32) & 0xFF;
-    let i_byte4 = (scaled_mantissa as u32) & 0xFF;
+            $($async)? fn read_registers(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Bq34Z100Error<E>> {
+                let data: [u8; 1] = [address];
+                self.bus_write_read(BusOperation::Read { command: address }, self.address, &data, buffer)$($await)*?;
+                return Ok(());
+            }
 
-    // Subtraktion des Vorzeichenbits, falls die Zahl positiv ist
-    let i_byte2 = if !b_negative { i_byte2 & 0x7F } else { i_byte2 };
+            #[cfg(feature = "write")]
+            $($async)? fn read_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>> {
+                let block = offset / 32;
+                let mut attempt = 1;
+                loop {
+                    self.write_reg(BLOCK_DATA_CONTROL, 0x00)$($await)*?; // Block control
+                    self.write_reg(DATA_FLASH_CLASS, sub_class)$($await)*?; // Flash class
+                    self.write_reg(DATA_FLASH_BLOCK, block)$($await)*?; // Flash block
+                    let mut selected = [0u8; 2];
+                    self.read_registers(DATA_FLASH_CLASS, &mut selected)$($await)*?;
+
+                    let data: [u8; 1] = [BLOCK_DATA];
+                    let mut data_block = [0u8; 32];
+                    let operation = BusOperation::DataFlashRead { sub_class, offset: block * 32 };
+                    self.bus_write(operation, self.address, &data)$($await)*?;
+                    self.bus_read(operation, self.address, &mut data_block)$($await)*?;
+                    let expected = self.read_1_register_as_u8(BLOCK_DATA_CHECKSUM)$($await)*?;
+                    let actual = crate::registers::flash_block_checksum(&data_block);
+
+                    let error = if selected != [sub_class, block] {
+                        let [selected_sub_class, selected_block] = selected;
+                        Bq34Z100Error::BlockNotSelected { sub_class, block, selected_sub_class, selected_block }
+                    } else if expected != actual {
+                        Bq34Z100Error::BlockChecksumMismatch { sub_class, block, expected, actual }
+                    } else {
+                        self.flash_block_data = data_block;
+                        return Ok(());
+                    };
+                    if attempt == FLASH_BLOCK_READ_ATTEMPTS {
+                        return Err(error);
+                    }
+                    attempt += 1;
+                }
+            }
 
-    // Zusammenbau des Ergebnisses
-    return (i_byte1 << 24) | (i_byte2 << 16) | (i_byte3 << 8) | i_byte4;
-}
+            $($async)? fn write_reg(&mut self, address: u8, value: u8) -> Result<(), Bq34Z100Error<E>> {
+                let data: [u8; 2] = [address, value];
+                self.bus_write(BusOperation::register_write(address, self.selected_block), self.address, &data)$($await)*?;
+                self.selected_block = crate::error::select_block(self.selected_block, address, value);
+                return Ok(());
+            }
 
-#[derive(Debug)]
-pub enum Bq34Z100Error<E> {
-    NotStored {
-        error: &'static str,
-    },
-    I2C {
-        error: E,
-    },
-    XemicsConversionError,
-    #[cfg(feature = "flashstream")]
-    FlashStreamError {
-        error: std::io::Error,
-    },
-    ChecksumError {
-        register: u8,
-        expected: u8,
-        actual: u8
-    },
-}
+            #[cfg(feature = "write")]
+            $($async)? fn write_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>> {
+                let block = offset / 32;
+                self.write_reg(BLOCK_DATA_CONTROL, 0x00)$($await)*?; // Block control
+                self.write_reg(DATA_FLASH_CLASS, sub_class)$($await)*?; // Flash class
+                self.write_reg(DATA_FLASH_BLOCK, block)$($await)*?; // Flash block
+
+                let mut data = [BLOCK_DATA; 33];
+                data[1..].copy_from_slice(&self.flash_block_data);
+                let operation = BusOperation::DataFlashWrite { sub_class, offset: block * 32 };
+                self.bus_write(operation, self.address, &data)$($await)*?;
+                // the gauge only takes the block with a matching checksum
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
+                return Ok(());
+            }
 
-impl<E> From<E> for Bq34Z100Error<E> {
-    fn from(value: E) -> Self {
-        Bq34Z100Error::I2C { error: value }
-    }
-}
+            #[cfg(feature = "write")]
+            $($async)? fn flash_block_checksum(&mut self) -> Result<u8, Bq34Z100Error<E>> {
+                return Ok(crate::registers::flash_block_checksum(&self.flash_block_data));
+            }
 
-impl<I2C, DELAY, E: std::fmt::Debug> Bq34z100g1<E> for Bq34z100g1Driver<I2C, DELAY>
-where
-    I2C: I2c<Error = E>,
-    DELAY: DelayNs,
-{
-    #[cfg(feature = "flashstream")]
-    fn write_flash_stream_i2c(&mut self, line: &str, dryrun: bool) -> Result<(), Bq34Z100Error<E>> {
-        use std::num::ParseIntError;
-
-        let map_parse_err = |e: ParseIntError| {
-            let mapped: Bq34Z100Error<E> = Bq34Z100Error::FlashStreamError {
-                error: std::io::Error::new(std::io::ErrorKind::UnexpectedEof, e),
-            };
-            return mapped;
-        };
-        let missing_part = || Bq34Z100Error::FlashStreamError {
-            error: std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Line ended"),
-        };
-        if line.starts_with(";") {
-            return Ok(());
-        }
-        if line.contains("%") {
-            let error: Bq34Z100Error<E> = Bq34Z100Error::FlashStreamError {
-                error: std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Dynamic addreses are not supported"),
-            };
-            return Err(error);
-        }
-        let mut parts = line.split_ascii_whitespace();
-        let start = parts.next().unwrap();
-        if start.eq("W:") {
-            let address_string = parts.next().ok_or(missing_part())?;
-            let address = u8::from_str_radix(address_string, 16).map_err(map_parse_err)?;
-            let register_string = parts.next().ok_or(missing_part())?;
-            let mut register = u8::from_str_radix(register_string, 16).map_err(map_parse_err)?;
-            for value in parts {
-                let value = u8::from_str_radix(value, 16).map_err(map_parse_err)?;
-                let data: [u8; 2] = [register, value];
-                if !dryrun {
-                    self.i2c.write(address >> 1, &data).unwrap();
+            $($async)? fn unsealed(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                if self.unsealed_known {
+                    return Ok(());
                 }
-                register += 1;
-            }
-        } else if start.eq("X:") {
-            let wait_for_string = parts.next().ok_or(missing_part())?;
-            let wait_for = u32::from_str_radix(wait_for_string, 16).map_err(map_parse_err)?;
-            if !dryrun {
-                self.delay.delay_ms(wait_for);
-            }
-        } else if start.eq("C:") {
-            let address_string = parts.next().ok_or(missing_part())?;
-            let address = u8::from_str_radix(address_string, 16).map_err(map_parse_err)?;
-            let register_string = parts.next().ok_or(missing_part())?;
-            let mut register = u8::from_str_radix(register_string, 16).map_err(map_parse_err)?;
-            for value in parts {
-                let compare_byte = u8::from_str_radix(value, 16).map_err(map_parse_err)?;
-                let data: [u8; 1] = [register];
-                let mut buffer: [u8; 1] = [0; 1];
-                if !dryrun {
-                    self.i2c.write_read(address >> 1, &data, &mut buffer)?;
+                return self.unseal(self.unseal_key)$($await)*;
+            }
+
+            $($async)? fn unseal(&mut self, key: SecurityKey) -> Result<(), Bq34Z100Error<E>> {
+                for data in key.commands() {
+                    self.control_write(&data)$($await)*?;
                 }
+                let mode = self.security_mode()$($await)*?;
+                if mode == SecurityMode::Sealed {
+                    return Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Unsealed, actual: mode });
+                }
+                // remembered for the unsealed() calls of the update and calibration paths
+                self.unseal_key = key;
+                return Ok(());
+            }
 
-                let read = buffer[0];
-                if read != compare_byte {
-                    let error: Bq34Z100Error<E> = Bq34Z100Error::ChecksumError { register: register, expected: compare_byte, actual: read };
-                    return Err(error);
+            $($async)? fn full_access(&mut self, key: SecurityKey) -> Result<(), Bq34Z100Error<E>> {
+                for data in key.commands() {
+                    self.control_write(&data)$($await)*?;
+                }
+                let mode = self.security_mode()$($await)*?;
+                if mode != SecurityMode::FullAccess {
+                    return Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::FullAccess, actual: mode });
                 }
-                register += 1;
+                return Ok(());
             }
-        }
-        return Ok(());
-    }
 
-    fn read_2_register_as_u16(&mut self, address: u8) -> Result<u16, Bq34Z100Error<E>> {
-        let data: [u8; 1] = [address];
-        let mut buffer: [u8; 2] = [0; 2];
-        self.i2c
-            .write_read(BQ34Z100_G1_ADDRESS, &data, &mut buffer)?;
-        Ok(u16::from_le_bytes([buffer[0], buffer[1]]))
-    }
+            $($async)? fn seal(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.sealed()$($await)*?;
+                let mode = self.security_mode()$($await)*?;
+                if mode != SecurityMode::Sealed {
+                    return Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Sealed, actual: mode });
+                }
+                return Ok(());
+            }
 
-    fn read_1_register_as_u8(&mut self, address: u8) -> Result<u8, Bq34Z100Error<E>> {
-        let data: [u8; 1] = [address];
-        let mut buffer: [u8; 1] = [0; 1];
-        self.i2c
-            .write_read(BQ34Z100_G1_ADDRESS, &data, &mut buffer)?;
-        Ok(buffer[0])
-    }
+            $($async)? fn security_mode(&mut self) -> Result<SecurityMode, Bq34Z100Error<E>> {
+                return Ok(self.control_status_decoded()$($await)*?.security_mode());
+            }
 
-    fn read_control(&mut self, address_lsb: u8, address_msb: u8) -> Result<u16, Bq34Z100Error<E>> {
-        let data: [u8; 3] = [0x00_u8, address_lsb, address_msb];
-        self.i2c.write(BQ34Z100_G1_ADDRESS, &data)?;
-        return self.read_2_register_as_u16(0x00);
-    }
+            $($async)? fn identify(&mut self) -> Result<DeviceInfo, Bq34Z100Error<E>> {
+                let device = DeviceInfo::new(
+                    self.device_type()$($await)*?,
+                    self.fw_version()$($await)*?,
+                    self.hw_version()$($await)*?,
+                    self.df_version()$($await)*?,
+                );
+                self.device = Some(device);
+                return Ok(device);
+            }
 
-    fn internal_temperature(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x2a);
-    }
+            $($async)? fn ready(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.it_enable()$($await)*?;
+                return Ok(());
+            }
 
-    #[cfg(feature = "write")]
-    fn read_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>> {
-        self.write_reg(0x61, 0x00)?; // Block control
-        self.write_reg(0x3e, sub_class)?; // Flash class
-        self.write_reg(0x3f, offset / 32)?; // Flash block
-
-        let data: [u8; 1] = [0x40];
-        self.i2c.write(BQ34Z100_G1_ADDRESS, &data)?;
-        self.i2c
-            .read(BQ34Z100_G1_ADDRESS, &mut self.flash_block_data)?;
-        return Ok(());
-    }
+            $($async)? fn send_control(&mut self, command: ControlCommand) -> Result<Option<u16>, Bq34Z100Error<E>> {
+                // a sealed gauge silently ignores privileged subcommands, so the mode is checked first
+                if command.required_mode() != SecurityMode::Sealed {
+                    let mode = ControlStatus::from(self.read_control(CONTROL_STATUS, 0x00)$($await)*?).security_mode();
+                    if !command.allowed_in(mode) {
+                        return Err(Bq34Z100Error::SecurityAccessDenied { requested: command.required_mode(), actual: mode });
+                    }
+                }
+                let [lsb, msb] = command.subcommand().to_le_bytes();
+                self.control_write(&[CONTROL, lsb, msb])$($await)*?;
+                if command.processing_time_us() > 0 {
+                    self.delay.delay_us(command.processing_time_us())$($await)*;
+                }
+                if !command.returns_data() {
+                    return Ok(None);
+                }
+                return Ok(Some(self.read_2_register_as_u16(CONTROL)$($await)*?));
+            }
 
-    fn write_reg(&mut self, address: u8, value: u8) -> Result<(), Bq34Z100Error<E>> {
-        let data: [u8; 2] = [address, value];
-        self.i2c.write(BQ34Z100_G1_ADDRESS, &data)?;
-        return Ok(());
-    }
+            $($async)? fn board_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::BoardOffset)$($await)*?;
+                return Ok(());
+            }
 
-    #[cfg(feature = "write")]
-    fn write_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>> {
-        self.write_reg(0x61, 0x00)?; // Block control
-        self.write_reg(0x3e, sub_class)?; // Flash class
-        self.write_reg(0x3f, offset / 32)?; // Flash block
-
-        self.i2c
-            .write(BQ34Z100_G1_ADDRESS, &self.flash_block_data)?;
-        return Ok(());
-    }
+            $($async)? fn cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::CcOffset)$($await)*?;
+                return Ok(());
+            }
 
-    #[cfg(feature = "write")]
-    fn flash_block_checksum(&mut self) -> Result<u8, Bq34Z100Error<E>> {
-        let mut temp: u8 = 0;
-        for i in self.flash_block_data.iter() {
-            temp = u8::wrapping_add(temp, *i);
-        }
-        return Ok(u8::wrapping_sub(255, temp));
-    }
+            $($async)? fn cc_offset_save(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::CcOffsetSave)$($await)*?;
+                return Ok(());
+            }
 
-    fn unsealed(&mut self) -> Result<(), Bq34Z100Error<E>> {
-        let data: [u8; 3] = [0x00, 0x14, 0x04];
-        self.i2c.write(BQ34Z100_G1_ADDRESS, &data)?;
+            $($async)? fn set_fullsleep(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::SetFullsleep)$($await)*?;
+                return Ok(());
+            }
 
-        let data2: [u8; 3] = [0x00, 0x72, 0x36];
-        self.i2c.write(BQ34Z100_G1_ADDRESS, &data2)?;
-        return Ok(());
-    }
+            $($async)? fn set_hibernate(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::SetHibernate)$($await)*?;
+                return Ok(());
+            }
 
-    #[cfg(feature = "write")]
-    fn enter_calibration(&mut self) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        loop {
-            self.cal_enable()?;
-            self.enter_cal()?;
-            self.delay.delay_ms(1000);
-            if self.control_status()? & 0x1000 > 0 {
-                break;
-            }
-        } // CALEN
-        return Ok(());
-    }
+            $($async)? fn clear_hibernate(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::ClearHibernate)$($await)*?;
+                return Ok(());
+            }
 
-    #[cfg(feature = "write")]
-    fn exit_calibration(&mut self) -> Result<(), Bq34Z100Error<E>> {
-        loop {
-            self.exit_cal()?;
-            self.delay.delay_ms(1000);
-            if self.control_status()? & 0x1000 == 0 {
-                break;
-            }
-        } // CALEN
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
-        return Ok(());
-    }
+            $($async)? fn sealed(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::Sealed)$($await)*?;
+                return Ok(());
+            }
 
-    /**
-     * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
-     * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
-     */
-    #[cfg(feature = "write")]
-    fn update_design_capacity(&mut self, capacity: u16) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(48, 0)?;
+            $($async)? fn it_enable(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::ItEnable)$($await)*?;
+                return Ok(());
+            }
 
-        self.flash_block_data[6] = 0; // Cycle Count
-        self.flash_block_data[7] = 0;
+            #[cfg(feature = "write")]
+            $($async)? fn cal_enable(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::CalEnable)$($await)*?;
+                return Ok(());
+            }
 
-        self.flash_block_data[8] = (capacity >> 8) as u8; // CC Threshold
-        self.flash_block_data[9] = (capacity & 0xff) as u8;
+            #[cfg(feature = "write")]
+            $($async)? fn reset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed_known = false;
+                self.send_control(ControlCommand::Reset)$($await)*?;
+                return Ok(());
+            }
 
-        self.flash_block_data[11] = (capacity >> 8) as u8; // Design Capacity
-        self.flash_block_data[12] = (capacity & 0xff) as u8;
+            #[cfg(feature = "write")]
+            $($async)? fn exit_cal(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::ExitCal)$($await)*?;
+                return Ok(());
+            }
 
-        for i in 6..=9 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+            #[cfg(feature = "write")]
+            $($async)? fn enter_cal(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.send_control(ControlCommand::EnterCal)$($await)*?;
+                return Ok(());
+            }
 
-        for i in 11..=12 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
+            #[cfg(feature = "write")]
+            $($async)? fn offset_cal(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::OffsetCal)$($await)*?.unwrap_or_default() as i16);
+            }
         }
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
+        #[cfg(feature = "flashstream")]
+        impl<I2C, DELAY, E: embedded_hal::i2c::Error> $FlashStream<E> for $Driver<I2C, DELAY>
+        where
+            I2C: I2c<Error = E>,
+            DELAY: DelayNs,
+        {
+            $($async)? fn write_flash_stream_i2c(&mut self, line: &str, dryrun: bool) -> Result<(), Bq34Z100Error<E>> {
+                use crate::flashstream::{map_parse_err, parse_line, FlashStreamLine};
+
+                match parse_line(line)? {
+                    FlashStreamLine::Skip => {}
+                    FlashStreamLine::Write { address, register, values } => {
+                        for (i, value) in values.enumerate() {
+                            let value = value.map_err(map_parse_err)?;
+                            let data: [u8; 2] = [register.wrapping_add(i as u8), value];
+                            if !dryrun {
+                                let operation = BusOperation::FlashStream { address, register: data[0] };
+                                self.bus_write(operation, address >> 1, &data)$($await)*?;
+                            }
+                        }
+                    }
+                    FlashStreamLine::Wait { ms } => {
+                        if !dryrun {
+                            self.delay.delay_ms(ms)$($await)*;
+                        }
+                    }
+                    FlashStreamLine::Compare { address, register, values } => {
+                        for (i, value) in values.enumerate() {
+                            let compare_byte = value.map_err(map_parse_err)?;
+                            let register = register.wrapping_add(i as u8);
+                            let data: [u8; 1] = [register];
+                            let mut buffer: [u8; 1] = [0; 1];
+                            if !dryrun {
+                                let operation = BusOperation::FlashStream { address, register };
+                                self.bus_write_read(operation, address >> 1, &data, &mut buffer)$($await)*?;
+                            }
+
+                            let read = buffer[0];
+                            if read != compare_byte {
+                                let error: Bq34Z100Error<E> = Bq34Z100Error::ChecksumError { register, expected: compare_byte, actual: read };
+                                return Err(error);
+                            }
+                        }
+                    }
+                }
+                return Ok(());
+            }
+        }
 
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
+        #[cfg(feature = "write")]
+        impl<I2C, DELAY, E: embedded_hal::i2c::Error> $Configurator<E> for $Driver<I2C, DELAY>
+        where
+            I2C: I2c<Error = E>,
+            DELAY: DelayNs,
+        {
+            /**
+             * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
+             * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
+             */
+            $($async)? fn update_design_capacity(&mut self, capacity: u16) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_DATA, 0)$($await)*?;
 
-        self.unsealed()?;
+                self.flash_block_data[6] = 0; // Cycle Count
+                self.flash_block_data[7] = 0;
 
-        self.read_flash_block(48, 0)?;
-        let mut updated_cc_threshold: i16 = (self.flash_block_data[8] as i16) << 8_i16;
-        updated_cc_threshold |= self.flash_block_data[9] as i16;
+                self.flash_block_data[8] = (capacity >> 8) as u8; // CC Threshold
+                self.flash_block_data[9] = (capacity & 0xff) as u8;
 
-        let mut updated_capacity: i16 = (self.flash_block_data[11] as i16) << 8;
-        updated_capacity |= self.flash_block_data[12] as i16;
+                self.flash_block_data[11] = (capacity >> 8) as u8; // Design Capacity
+                self.flash_block_data[12] = (capacity & 0xff) as u8;
 
-        if self.flash_block_data[6] != 0 || self.flash_block_data[7] != 0 {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Block 6 or 7 wrong",
-            });
-        }
-        if capacity as i32 != updated_cc_threshold as i32 {
-            return Err(Bq34Z100Error::NotStored {
-                error: "cc threshold wrong",
-            });
-        }
-        if capacity as i32 != updated_capacity as i32 {
-            return Err(Bq34Z100Error::NotStored {
-                error: "capacity wrong",
-            });
-        }
-        return Ok(());
-    }
+                for i in 6..=9 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-    #[cfg(feature = "write")]
-    fn update_q_max(&mut self, capacity: i16) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(82, 0)?;
-        self.flash_block_data[0] = (capacity >> 8) as u8; // Q Max
-        self.flash_block_data[1] = (capacity & 0xff) as u8;
+                for i in 11..=12 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-        self.flash_block_data[2] = 0; // Cycle Count
-        self.flash_block_data[3] = 0;
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        for i in 0_u8..3_u8 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
+                self.unsealed()$($await)*?;
 
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
+                self.read_flash_block(SUBCLASS_DATA, 0)$($await)*?;
+                let mut updated_cycle_count: u16 = (self.flash_block_data[6] as u16) << 8;
+                updated_cycle_count |= self.flash_block_data[7] as u16;
 
-        self.unsealed()?;
-        self.read_flash_block(82, 0)?;
-        let mut updated_q_max: i16 = (self.flash_block_data[0] as i16) << 8;
-        updated_q_max |= self.flash_block_data[1] as i16;
+                let mut updated_cc_threshold: u16 = (self.flash_block_data[8] as u16) << 8;
+                updated_cc_threshold |= self.flash_block_data[9] as u16;
 
-        if capacity != updated_q_max {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Qmax not updated",
-            });
-        }
-        return Ok(());
-    }
+                let mut updated_capacity: u16 = (self.flash_block_data[11] as u16) << 8;
+                updated_capacity |= self.flash_block_data[12] as u16;
 
-    /**
-     * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
-     * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
-     */
-    #[cfg(feature = "write")]
-    fn update_design_energy(
-        &mut self,
-        energy: i16,
-        energy_scale: u8,
-    ) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(48, 0)?;
-        self.flash_block_data[13] = (energy >> 8) as u8; // Design Energy
-        self.flash_block_data[14] = (energy & 0xff) as u8;
-        self.flash_block_data[30] = energy_scale;
-
-        for i in 13..=14 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
-        self.write_reg(0x40 + 30, self.flash_block_data[30])?;
+                verify_stored(&DataFlash::CYCLE_COUNT, 0, updated_cycle_count)?;
+                verify_stored(&DataFlash::CC_THRESHOLD, capacity, updated_cc_threshold)?;
+                return verify_stored(&DataFlash::DESIGN_CAPACITY, capacity, updated_capacity);
+            }
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
+            $($async)? fn update_q_max(&mut self, capacity: i16) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_STATE, 0)$($await)*?;
+                self.flash_block_data[0] = (capacity >> 8) as u8; // Q Max
+                self.flash_block_data[1] = (capacity & 0xff) as u8;
 
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
+                self.flash_block_data[2] = 0; // Cycle Count
+                self.flash_block_data[3] = 0;
 
-        self.unsealed()?;
-        self.read_flash_block(48, 0)?;
-        let mut updated_energy: i16 = (self.flash_block_data[13] as i16) << 8;
-        updated_energy |= self.flash_block_data[14] as i16;
+                for i in 0_u8..3_u8 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-        if energy != updated_energy {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Energy not updated",
-            });
-        }
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        let updated_energy_scale: u8 = self.flash_block_data[30];
-        if updated_energy_scale != energy_scale {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Energy scale not updated",
-            });
-        }
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-        return Ok(());
-    }
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_STATE, 0)$($await)*?;
+                let mut updated_q_max: i16 = (self.flash_block_data[0] as i16) << 8;
+                updated_q_max |= self.flash_block_data[1] as i16;
 
-    #[cfg(feature = "write")]
-    fn update_cell_charge_voltage_range(
-        &mut self,
-        t1_t2: u16,
-        t2_t3: u16,
-        t3_t4: u16,
-    ) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(48, 0)?;
-
-        self.flash_block_data[17] = (t1_t2 >> 8) as u8; // Cell Charge Voltage T1-T2
-        self.flash_block_data[18] = (t1_t2 & 0xff) as u8;
-
-        self.flash_block_data[19] = (t2_t3 >> 8) as u8; // Cell Charge Voltage T2-T3
-        self.flash_block_data[20] = (t2_t3 & 0xff) as u8;
-
-        self.flash_block_data[21] = (t3_t4 >> 8) as u8; // Cell Charge Voltage T3-T4
-        self.flash_block_data[22] = (t3_t4 & 0xff) as u8;
-
-        for i in 17..=22 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+                return verify_stored(&DataFlash::QMAX_CELL_0, capacity, updated_q_max);
+            }
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
+            /**
+             * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
+             * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
+             */
+            $($async)? fn update_design_energy(
+                &mut self,
+                energy: i16,
+                energy_scale: u8,
+            ) -> Result<(), Bq34Z100Error<E>> {
+                self.scale = None;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_DATA, 0)$($await)*?;
+                self.flash_block_data[13] = (energy >> 8) as u8; // Design Energy
+                self.flash_block_data[14] = (energy & 0xff) as u8;
+                self.flash_block_data[30] = energy_scale;
+
+                for i in 13..=14 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
+                self.write_reg(BLOCK_DATA + 30, self.flash_block_data[30])$($await)*?;
 
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        self.unsealed()?;
-        self.read_flash_block(48, 0)?;
-        let mut updated_t1_t2: u16 = (self.flash_block_data[17] as u16) << 8;
-        updated_t1_t2 |= self.flash_block_data[18] as u16;
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-        let mut updated_t2_t3: u16 = (self.flash_block_data[19] as u16) << 8;
-        updated_t2_t3 |= self.flash_block_data[20] as u16;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_DATA, 0)$($await)*?;
+                let mut updated_energy: i16 = (self.flash_block_data[13] as i16) << 8;
+                updated_energy |= self.flash_block_data[14] as i16;
 
-        let mut updated_t3_t4: u16 = (self.flash_block_data[21] as u16) << 8;
-        updated_t3_t4 |= self.flash_block_data[22] as u16;
+                verify_stored(&DataFlash::DESIGN_ENERGY, energy, updated_energy)?;
 
-        if t1_t2 as u16 != updated_t1_t2
-            || t2_t3 as u16 != updated_t2_t3
-            || t3_t4 as u16 != updated_t3_t4
-        {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Charge parameters not updated",
-            });
-        }
-        return Ok(());
-    }
+                let updated_energy_scale: u8 = self.flash_block_data[30];
+                return verify_stored(&DataFlash::DESIGN_ENERGY_SCALE, energy_scale, updated_energy_scale);
+            }
 
-    #[cfg(feature = "write")]
-    //Warning, only for stock firmware, newer firmeware changed register
-    fn set_led_mode(&mut self, led_config: u8) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(64, 0)?;
-        self.flash_block_data[4] = led_config;
-        self.write_reg(0x40 + 4, self.flash_block_data[4])?;
-
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
-
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
-
-        self.unsealed()?;
-        self.read_flash_block(64, 0)?;
-
-        if self.flash_block_data[4] != led_config {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Failed to set led config!",
-            });
-        }
-        return Ok(());
-    }
+            $($async)? fn update_cell_charge_voltage_range(
+                &mut self,
+                t1_t2: u16,
+                t2_t3: u16,
+                t3_t4: u16,
+            ) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_DATA, 0)$($await)*?;
 
-    /**
-     * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
-     * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
-     */
-    #[cfg(feature = "write")]
-    fn update_number_of_series_cells(&mut self, cells: u8) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(64, 0)?;
+                self.flash_block_data[17] = (t1_t2 >> 8) as u8; // Cell Charge Voltage T1-T2
+                self.flash_block_data[18] = (t1_t2 & 0xff) as u8;
 
-        self.flash_block_data[7] = cells; // Number of Series Cell
-        self.write_reg(0x40 + 7, self.flash_block_data[7])?;
+                self.flash_block_data[19] = (t2_t3 >> 8) as u8; // Cell Charge Voltage T2-T3
+                self.flash_block_data[20] = (t2_t3 & 0xff) as u8;
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
+                self.flash_block_data[21] = (t3_t4 >> 8) as u8; // Cell Charge Voltage T3-T4
+                self.flash_block_data[22] = (t3_t4 & 0xff) as u8;
 
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
+                for i in 17..=22 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-        self.unsealed()?;
-        self.read_flash_block(64, 0)?;
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        if cells != self.flash_block_data[7] {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Series cells not updated",
-            });
-        }
-        return Ok(());
-    }
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-    /**
-     * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
-     * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
-     */
-    #[cfg(feature = "write")]
-    fn update_pack_configuration(&mut self, config: u16) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(64, 0)?;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_DATA, 0)$($await)*?;
+                let mut updated_t1_t2: u16 = (self.flash_block_data[17] as u16) << 8;
+                updated_t1_t2 |= self.flash_block_data[18] as u16;
 
-        self.flash_block_data[0] = (config >> 8) as u8; // Pack Configuration
-        self.flash_block_data[1] = (config & 0xff) as u8;
+                let mut updated_t2_t3: u16 = (self.flash_block_data[19] as u16) << 8;
+                updated_t2_t3 |= self.flash_block_data[20] as u16;
 
-        for i in 0..=1 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+                let mut updated_t3_t4: u16 = (self.flash_block_data[21] as u16) << 8;
+                updated_t3_t4 |= self.flash_block_data[22] as u16;
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
-
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(1000);
-
-        self.unsealed()?;
-        self.read_flash_block(64, 0)?;
-        let mut updated_config = (self.flash_block_data[0] as u16) << 8;
-        updated_config |= self.flash_block_data[1] as u16;
-        if config != updated_config {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Config flags not updated",
-            });
-        }
-        return Ok(());
-    }
+                verify_stored(&DataFlash::CELL_CHARGE_VOLTAGE_T1_T2, t1_t2, updated_t1_t2)?;
+                verify_stored(&DataFlash::CELL_CHARGE_VOLTAGE_T2_T3, t2_t3, updated_t2_t3)?;
+                return verify_stored(&DataFlash::CELL_CHARGE_VOLTAGE_T3_T4, t3_t4, updated_t3_t4);
+            }
 
-    //Not recommended to use this
-    #[cfg(feature = "write")]
-    fn update_charge_termination_parameters(
-        &mut self,
-        taper_current: i16,
-        min_taper_capacity: i16,
-        cell_taper_voltage: i16,
-        taper_window: u8,
-        tca_set: i8,
-        tca_clear: i8,
-        fc_set: i8,
-        fc_clear: i8,
-    ) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.read_flash_block(36, 0)?;
+            // only on firmware with LED_Comm Configuration at offset 4, fails with Unsupported otherwise
+            $($async)? fn set_led_mode(&mut self, led_config: u8) -> Result<(), Bq34Z100Error<E>> {
+                self.require(Capabilities::LED_CONFIGURATION)$($await)*?;
+                return self.update_registers_byte(&DataFlash::LED_COMM_CONFIGURATION, led_config)$($await)*;
+            }
 
-        self.flash_block_data[0] = (taper_current >> 8) as u8; // Taper Current
-        self.flash_block_data[1] = (taper_current & 0xff) as u8;
+            $($async)? fn read_parameter(&mut self, parameter: &DataFlashParameter) -> Result<DataFlashValue, Bq34Z100Error<E>> {
+                let mut bytes = [0u8; FLASH_BLOCK_SIZE];
+                self.unsealed()$($await)*?;
+                self.read_parameter_bytes(parameter, &mut bytes)$($await)*?;
+                return Ok(parameter.decode(&bytes));
+            }
 
-        self.flash_block_data[2] = (min_taper_capacity >> 8) as u8; // Min Taper Capacity
-        self.flash_block_data[3] = (min_taper_capacity & 0xff) as u8;
+            $($async)? fn write_parameter(&mut self, parameter: &DataFlashParameter, value: DataFlashValue) -> Result<(), Bq34Z100Error<E>> {
+                let mut bytes = [0u8; FLASH_BLOCK_SIZE];
+                parameter.encode(value, &mut bytes).ok_or(Bq34Z100Error::InvalidValue { parameter: parameter.name })?;
+                if *parameter == DataFlash::DESIGN_ENERGY_SCALE || *parameter == DataFlash::PACK_CONFIGURATION {
+                    self.scale = None; // SCALED or the energy scale may change
+                }
+                self.unsealed()$($await)*?;
+                for (offset, part) in parameter.block_parts() {
+                    self.read_flash_block(parameter.sub_class, offset)$($await)*?;
+                    let start = offset % FLASH_BLOCK_SIZE as u8;
+                    for (i, &byte) in bytes[part].iter().enumerate() {
+                        self.flash_block_data[start as usize + i] = byte;
+                        self.write_reg(BLOCK_DATA + start + i as u8, byte)$($await)*?;
+                    }
+                    let checksum = self.flash_block_checksum()$($await)*?;
+                    self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
+                }
 
-        self.flash_block_data[4] = (cell_taper_voltage >> 8) as u8; // Cell Taper Voltage
-        self.flash_block_data[5] = (cell_taper_voltage & 0xff) as u8;
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-        self.flash_block_data[6] = taper_window; // Current Taper Window
+                self.unsealed()$($await)*?;
+                let mut stored = [0u8; FLASH_BLOCK_SIZE];
+                self.read_parameter_bytes(parameter, &mut stored)$($await)*?;
+                let size = parameter.size() as usize;
+                return verify_stored_bytes(parameter, &bytes[..size], &stored[..size]);
+            }
 
-        self.flash_block_data[7] = (tca_set as u8) & 0xff; // TCA Set %
+            $($async)? fn commit_transaction<const N: usize>(
+                &mut self,
+                transaction: &DataFlashTransaction<N>,
+            ) -> Result<TransactionReport<N>, Bq34Z100Error<E>> {
+                let mut report = TransactionReport::new();
+                if transaction.is_empty() {
+                    return Ok(report);
+                }
+                if transaction.contains(&DataFlash::DESIGN_ENERGY_SCALE)
+                    || transaction.contains(&DataFlash::PACK_CONFIGURATION)
+                {
+                    self.scale = None; // SCALED or the energy scale may change
+                }
+                self.unsealed()$($await)*?;
+                for (sub_class, offset) in transaction.blocks() {
+                    self.read_flash_block(sub_class, offset)$($await)*?;
+                    for (position, byte) in transaction.block_bytes(sub_class, offset) {
+                        self.flash_block_data[position as usize] = byte;
+                        self.write_reg(BLOCK_DATA + position, byte)$($await)*?;
+                    }
+                    let checksum = self.flash_block_checksum()$($await)*?;
+                    self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
+                }
 
-        self.flash_block_data[8] = (tca_clear as u8) & 0xff; // TCA Clear %
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-        self.flash_block_data[9] = (fc_set as u8) & 0xff; // FC Set %
+                self.unsealed()$($await)*?;
+                for edit in transaction.edits() {
+                    let mut stored = [0u8; FLASH_BLOCK_SIZE];
+                    self.read_parameter_bytes(&edit.parameter, &mut stored)$($await)*?;
+                    report.record(edit, &stored);
+                }
+                return Ok(report);
+            }
 
-        self.flash_block_data[10] = (fc_clear as u8) & 0xff; // FC Clear %
+            $($async)? fn dump_data_flash(&mut self) -> Result<DataFlashImage, Bq34Z100Error<E>> {
+                let mut image = DataFlashImage::new(self.identify()$($await)*?);
+                self.unsealed()$($await)*?;
+                for (index, &(sub_class, block)) in DataFlashImage::BLOCKS.iter().enumerate() {
+                    self.read_flash_block(sub_class, block * FLASH_BLOCK_SIZE as u8)$($await)*?;
+                    image.blocks[index] = self.flash_block_data;
+                }
+                return Ok(image);
+            }
 
-        for i in 0..=10 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+            $($async)? fn restore_data_flash(&mut self, image: &DataFlashImage) -> Result<(), Bq34Z100Error<E>> {
+                return self.restore_image(image, false)$($await)*;
+            }
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
+            $($async)? fn restore_data_flash_strict(&mut self, image: &DataFlashImage) -> Result<(), Bq34Z100Error<E>> {
+                return self.restore_image(image, true)$($await)*;
+            }
 
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
+            /**
+             * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
+             * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
+             */
+            $($async)? fn update_number_of_series_cells(&mut self, cells: u8) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
 
-        self.unsealed()?;
-        self.read_flash_block(36, 0)?;
-        let mut updated_taper_current: i16;
-        let mut updated_min_taper_capacity: i16;
-        let mut updated_cell_taper_voltage: i16;
-        let updated_taper_window: u8;
-        let updated_tca_set: i8;
-        let updated_tca_clear: i8;
-        let updated_fc_set: i8;
-        let updated_fc_clear: i8;
+                self.flash_block_data[7] = cells; // Number of Series Cell
+                self.write_reg(BLOCK_DATA + 7, self.flash_block_data[7])$($await)*?;
 
-        updated_taper_current = (self.flash_block_data[0] as i16) << 8;
-        updated_taper_current |= self.flash_block_data[1] as i16;
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        updated_min_taper_capacity = (self.flash_block_data[2] as i16) << 8;
-        updated_min_taper_capacity |= self.flash_block_data[3] as i16;
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-        updated_cell_taper_voltage = (self.flash_block_data[4] as i16) << 8;
-        updated_cell_taper_voltage |= self.flash_block_data[5] as i16;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
 
-        updated_taper_window = self.flash_block_data[6];
+                return verify_stored(&DataFlash::NUMBER_OF_SERIES_CELLS, cells, self.flash_block_data[7]);
+            }
 
-        updated_tca_set = (self.flash_block_data[7] & 0xff) as i8;
+            /**
+             * If you are using a lipo li-ion battery this should be the only one you use. Since you cannot change the chemid with this driver,
+             * and need to use the BatteryManager desktop application anyway, I strongly recommend to set all other config there as well.
+             */
+            $($async)? fn update_pack_configuration(&mut self, config: PackConfiguration) -> Result<(), Bq34Z100Error<E>> {
+                config.validate().map_err(|error| Bq34Z100Error::InvalidPackConfiguration { error })?;
+                self.scale = None; // SCALED may change
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
+                config
+                    .validate_for_cells(self.flash_block_data[7]) // Number of Series Cell
+                    .map_err(|error| Bq34Z100Error::InvalidPackConfiguration { error })?;
+
+                // reserved bits stay as they are
+                let current = u16::from_be_bytes([self.flash_block_data[0], self.flash_block_data[1]]);
+                let config = (current & !PACK_CONFIGURATION_MASK) | config.bits();
+                self.flash_block_data[0] = (config >> 8) as u8; // Pack Configuration
+                self.flash_block_data[1] = (config & 0xff) as u8;
+
+                for i in 0..=1 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-        updated_tca_clear = (self.flash_block_data[8] & 0xff) as i8;
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        updated_fc_set = (self.flash_block_data[9] & 0xff) as i8;
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
+                self.delay.delay_ms(1000)$($await)*;
 
-        updated_fc_clear = (self.flash_block_data[10] & 0xff) as i8;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
+                let mut updated_config = (self.flash_block_data[0] as u16) << 8;
+                updated_config |= self.flash_block_data[1] as u16;
+                return verify_stored(&DataFlash::PACK_CONFIGURATION, config, updated_config);
+            }
 
-        if taper_current != updated_taper_current {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update taper current",
-            });
-        }
-        if min_taper_capacity != updated_min_taper_capacity {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update min_taper_capacity",
-            });
-        }
-        if cell_taper_voltage != updated_cell_taper_voltage {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update cell_taper_voltage",
-            });
-        }
-        if taper_window != updated_taper_window {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update taper_window",
-            });
-        }
-        if tca_set != updated_tca_set {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update tca_set",
-            });
-        }
-        if tca_clear != updated_tca_clear {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update tca_clear",
-            });
-        }
-        if fc_set != updated_fc_set {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update fc_set",
-            });
-        }
-        if fc_clear != updated_fc_clear {
-            return Err(Bq34Z100Error::NotStored {
-                error: "Could not update fc_clear",
-            });
-        }
-        return Ok(());
-    }
+            $($async)? fn pack_configuration_b(&mut self) -> Result<PackConfigurationB, Bq34Z100Error<E>> {
+                self.require(Capabilities::PACK_CONFIGURATION_BC)$($await)*?;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
+                return Ok(PackConfigurationB::from_bits_retain(self.flash_block_data[2]));
+            }
 
-    #[cfg(feature = "write")]
-    fn calibrate_cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
-        self.enter_calibration()?;
+            $($async)? fn update_pack_configuration_b(&mut self, config: PackConfigurationB) -> Result<(), Bq34Z100Error<E>> {
+                self.require(Capabilities::PACK_CONFIGURATION_BC)$($await)*?;
+                return self.update_registers_byte(&DataFlash::PACK_CONFIGURATION_B, config.bits())$($await)*;
+            }
 
-        loop {
-            self.cc_offset()?;
-            self.delay.delay_ms(1000);
-            if self.control_status()? & 0x0800 > 0 {
-                break;
+            $($async)? fn pack_configuration_c(&mut self) -> Result<PackConfigurationC, Bq34Z100Error<E>> {
+                self.require(Capabilities::PACK_CONFIGURATION_BC)$($await)*?;
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
+                return Ok(PackConfigurationC::from_bits_retain(self.flash_block_data[3]));
             }
-        } // CCA
 
-        loop {
-            self.delay.delay_ms(1000);
-            if self.control_status()? & 0x0800 == 0 {
-                break;
+            $($async)? fn update_pack_configuration_c(&mut self, config: PackConfigurationC) -> Result<(), Bq34Z100Error<E>> {
+                self.require(Capabilities::PACK_CONFIGURATION_BC)$($await)*?;
+                return self.update_registers_byte(&DataFlash::PACK_CONFIGURATION_C, config.bits())$($await)*;
             }
-        } // CCA
 
-        self.cc_offset_save()?;
-        self.exit_calibration()?;
-        return Ok(());
-    }
+            //Not recommended to use this
+            $($async)? fn update_charge_termination_parameters(
+                &mut self,
+                taper_current: i16,
+                min_taper_capacity: i16,
+                cell_taper_voltage: i16,
+                taper_window: u8,
+                tca_set: i8,
+                tca_clear: i8,
+                fc_set: i8,
+                fc_clear: i8,
+            ) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_CHARGE_TERMINATION, 0)$($await)*?;
 
-    #[cfg(feature = "write")]
-    fn calibrate_board_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
-        self.enter_calibration()?;
-        loop {
-            self.board_offset()?;
-            self.delay.delay_ms(1000);
-            if self.control_status()? & 0x0c00 > 0 {
-                break;
-            }
-        } // CCA + BCA
-
-        loop {
-            self.delay.delay_ms(1000);
-            if self.control_status()? & 0x0c00 == 0 {
-                break;
-            }
-        } // CCA + BCA
-
-        self.cc_offset_save()?;
-        self.exit_calibration()?;
-        return Ok(());
-    }
+                self.flash_block_data[0] = (taper_current >> 8) as u8; // Taper Current
+                self.flash_block_data[1] = (taper_current & 0xff) as u8;
 
-    #[cfg(feature = "write")]
-    fn calibrate_voltage_divider(&mut self, applied_voltage: f32) -> Result<(), Bq34Z100Error<E>> {
-        let mut volt_array: [f32; 50] = [0.0; 50];
-        for i in 0..50 {
-            volt_array[i] = self.voltage()? as f32;
-            self.delay.delay_ms(150);
-        }
-        let mut volt_mean: f32 = 0.0;
-        for i in 0..50 {
-            volt_mean += volt_array[i];
-        }
-        volt_mean /= 50.0;
+                self.flash_block_data[2] = (min_taper_capacity >> 8) as u8; // Min Taper Capacity
+                self.flash_block_data[3] = (min_taper_capacity & 0xff) as u8;
 
-        let mut volt_sd: f32 = 0.0;
-        for i in 0..50 {
-            volt_sd += (volt_array[i] - volt_mean).powf(2.0);
-        }
-        volt_sd /= 50.0;
-        volt_sd = volt_sd.sqrt();
+                self.flash_block_data[4] = (cell_taper_voltage >> 8) as u8; // Cell Taper Voltage
+                self.flash_block_data[5] = (cell_taper_voltage & 0xff) as u8;
 
-        if volt_sd > 100.0 {
-            return Ok(());
-        }
+                self.flash_block_data[6] = taper_window; // Current Taper Window
 
-        self.unsealed()?;
+                self.flash_block_data[7] = tca_set as u8; // TCA Set %
 
-        self.read_flash_block(104, 0)?;
+                self.flash_block_data[8] = tca_clear as u8; // TCA Clear %
 
-        let mut current_voltage_divider: u16 = (self.flash_block_data[14] as u16) << 8;
-        current_voltage_divider |= self.flash_block_data[15] as u16;
+                self.flash_block_data[9] = fc_set as u8; // FC Set %
 
-        let new_voltage_divider: u16 =
-            ((applied_voltage as f32 / volt_mean as f32) * current_voltage_divider as f32) as u16;
+                self.flash_block_data[10] = fc_clear as u8; // FC Clear %
 
-        self.flash_block_data[14] = (new_voltage_divider >> 8) as u8;
-        self.flash_block_data[15] = (new_voltage_divider & 0xff) as u8;
+                for i in 0..=10 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-        for i in 14..=15 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
-        self.delay.delay_ms(150);
-        return Ok(());
-    }
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-    #[cfg(feature = "write")]
-    fn calibrate_sense_resistor(&mut self, applied_current: i16) -> Result<(), Bq34Z100Error<E>> {
-        let mut current_array: [f32; 50] = [0.0; 50];
-        for i in 0..50 {
-            current_array[i] = self.current()? as f32;
-            self.delay.delay_ms(150);
-        }
-        let mut current_mean: f32 = 0.0;
-        for i in 0..50 {
-            current_mean += current_array[i];
-        }
-        current_mean /= 50.0;
-
-        let mut current_sd: f32 = 0.0;
-        for i in 0..50 {
-            current_sd += (current_array[i] - current_mean).powf(2.0);
-        }
-        current_sd /= 50.0;
-        current_sd = current_sd.sqrt();
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_CHARGE_TERMINATION, 0)$($await)*?;
 
-        if current_sd > 100.0 {
-            //actually this might not be ok?
-            return Ok(());
-        }
+                let mut updated_taper_current: i16 = (self.flash_block_data[0] as i16) << 8;
+                updated_taper_current |= self.flash_block_data[1] as i16;
 
-        self.unsealed()?;
-        self.read_flash_block(104, 0)?;
+                let mut updated_min_taper_capacity: i16 = (self.flash_block_data[2] as i16) << 8;
+                updated_min_taper_capacity |= self.flash_block_data[3] as i16;
 
-        let mut cc_gain: u32 = (self.flash_block_data[0] as u32) << 24;
-        cc_gain |= (self.flash_block_data[1] as u32) << 16;
-        cc_gain |= (self.flash_block_data[2] as u32) << 8;
-        cc_gain |= self.flash_block_data[3] as u32;
+                let mut updated_cell_taper_voltage: i16 = (self.flash_block_data[4] as i16) << 8;
+                updated_cell_taper_voltage |= self.flash_block_data[5] as i16;
 
-        let float_cc_gain = xemics_to_double(cc_gain);
-        let xemics_cc_gain = float_to_xemics(float_cc_gain);
-        let float_cc_gain2 = xemics_to_double(xemics_cc_gain);
-        if (float_cc_gain - float_cc_gain2).abs() > 0.01 {
-            return Err(Bq34Z100Error::XemicsConversionError);
-        }
+                let updated_taper_window: u8 = self.flash_block_data[6];
 
-        let gain_resistence: f32 = 4.768 / float_cc_gain;
+                let updated_tca_set: i8 = self.flash_block_data[7] as i8;
 
-        let temp: f32 = (current_mean * gain_resistence) / applied_current as f32;
+                let updated_tca_clear: i8 = self.flash_block_data[8] as i8;
 
-        let mut new_cc_gain: u32 = float_to_xemics(4.768 / temp);
-        self.flash_block_data[0] = (new_cc_gain >> 24) as u8;
-        self.flash_block_data[1] = (new_cc_gain >> 16) as u8;
-        self.flash_block_data[2] = (new_cc_gain >> 8) as u8;
-        self.flash_block_data[3] = (new_cc_gain & 0xff) as u8;
+                let updated_fc_set: i8 = self.flash_block_data[9] as i8;
 
-        new_cc_gain = float_to_xemics(5677445.6 / temp);
-        self.flash_block_data[4] = (new_cc_gain >> 24) as u8;
-        self.flash_block_data[5] = (new_cc_gain >> 16) as u8;
-        self.flash_block_data[6] = (new_cc_gain >> 8) as u8;
-        self.flash_block_data[7] = (new_cc_gain & 0xff) as u8;
+                let updated_fc_clear: i8 = self.flash_block_data[10] as i8;
 
-        for i in 0..=3 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
+                verify_stored(&DataFlash::TAPER_CURRENT, taper_current, updated_taper_current)?;
+                verify_stored(&DataFlash::MIN_TAPER_CAPACITY, min_taper_capacity, updated_min_taper_capacity)?;
+                verify_stored(&DataFlash::CELL_TAPER_VOLTAGE, cell_taper_voltage, updated_cell_taper_voltage)?;
+                verify_stored(&DataFlash::CURRENT_TAPER_WINDOW, taper_window, updated_taper_window)?;
+                verify_stored(&DataFlash::TCA_SET, tca_set, updated_tca_set)?;
+                verify_stored(&DataFlash::TCA_CLEAR, tca_clear, updated_tca_clear)?;
+                verify_stored(&DataFlash::FC_SET, fc_set, updated_fc_set)?;
+                return verify_stored(&DataFlash::FC_CLEAR, fc_clear, updated_fc_clear);
+            }
         }
 
-        for i in 4..=7 {
-            self.write_reg(0x40 + i, self.flash_block_data[i as usize])?;
-        }
+        #[cfg(feature = "write")]
+        impl<I2C, DELAY, E: embedded_hal::i2c::Error> $Calibrator<E> for $Driver<I2C, DELAY>
+        where
+            I2C: I2c<Error = E>,
+            DELAY: DelayNs,
+        {
+            $($async)? fn enter_calibration(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                loop {
+                    self.cal_enable()$($await)*?;
+                    self.enter_cal()$($await)*?;
+                    self.delay.delay_ms(1000)$($await)*;
+                    if self.control_status_decoded()$($await)*?.calibration_enabled {
+                        break;
+                    }
+                } // CALEN
+                return Ok(());
+            }
 
-        let checksum = self.flash_block_checksum()?;
-        self.write_reg(0x60, checksum)?;
-        self.delay.delay_ms(150);
-        self.reset()?;
-        self.delay.delay_ms(150);
-        return Ok(());
-    }
+            $($async)? fn exit_calibration(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                loop {
+                    self.exit_cal()$($await)*?;
+                    self.delay.delay_ms(1000)$($await)*;
+                    if !self.control_status_decoded()$($await)*?.calibration_enabled {
+                        break;
+                    }
+                } // CALEN
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
+                return Ok(());
+            }
 
-    fn ready(&mut self) -> Result<(), Bq34Z100Error<E>> {
-        self.unsealed()?;
-        self.it_enable()?;
-        return Ok(());
-    }
+            $($async)? fn calibrate_cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.enter_calibration()$($await)*?;
+
+                loop {
+                    self.cc_offset()$($await)*?;
+                    self.delay.delay_ms(1000)$($await)*;
+                    if self.control_status_decoded()$($await)*?.cc_offset_calibration {
+                        break;
+                    }
+                } // CCA
+
+                loop {
+                    self.delay.delay_ms(1000)$($await)*;
+                    if !self.control_status_decoded()$($await)*?.cc_offset_calibration {
+                        break;
+                    }
+                } // CCA
+
+                self.cc_offset_save()$($await)*?;
+                self.exit_calibration()$($await)*?;
+                return Ok(());
+            }
 
-    fn control_status(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x00, 0x00);
-    }
+            $($async)? fn calibrate_board_offset(&mut self) -> Result<(), Bq34Z100Error<E>> {
+                self.enter_calibration()$($await)*?;
+                loop {
+                    self.board_offset()$($await)*?;
+                    self.delay.delay_ms(1000)$($await)*;
+                    if self.control_status_decoded()$($await)*?.offset_calibration_running() {
+                        break;
+                    }
+                } // CCA + BCA
+
+                loop {
+                    self.delay.delay_ms(1000)$($await)*;
+                    if !self.control_status_decoded()$($await)*?.offset_calibration_running() {
+                        break;
+                    }
+                } // CCA + BCA
+
+                self.cc_offset_save()$($await)*?;
+                self.exit_calibration()$($await)*?;
+                return Ok(());
+            }
 
-    fn device_type(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x01, 0x00);
-    }
+            $($async)? fn measure_cc_offset(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+                self.enter_calibration()$($await)*?;
+                let offset = self.offset_cal()$($await)*?;
+                self.exit_calibration()$($await)*?;
+                return Ok(offset);
+            }
 
-    fn fw_version(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x02, 0x00);
-    }
+            $($async)? fn calibrate_voltage_divider(&mut self, applied_voltage: f32) -> Result<(), Bq34Z100Error<E>> {
+                let mut volt_array: [f32; 50] = [0.0; 50];
+                for volt in volt_array.iter_mut() {
+                    *volt = self.voltage()$($await)*? as f32;
+                    self.delay.delay_ms(150)$($await)*;
+                }
+                let (volt_mean, volt_variance) = mean_and_variance(&volt_array);
 
-    fn hw_version(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x03, 0x00);
-    }
+                if volt_variance > 100.0 * 100.0 {
+                    return Ok(());
+                }
 
-    fn reset_data(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x05, 0x00);
-    }
+                self.unsealed()$($await)*?;
 
-    fn prev_macwrite(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x07, 0x00);
-    }
+                self.read_flash_block(SUBCLASS_CALIBRATION_DATA, 0)$($await)*?;
 
-    fn chem_id(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x08, 0x00);
-    }
+                let mut current_voltage_divider: u16 = (self.flash_block_data[14] as u16) << 8;
+                current_voltage_divider |= self.flash_block_data[15] as u16;
 
-    fn board_offset(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x09, 0x00);
-    }
+                let new_voltage_divider: u16 =
+                    ((applied_voltage / volt_mean) * current_voltage_divider as f32) as u16;
 
-    fn cc_offset(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x0a, 0x00);
-    }
+                self.flash_block_data[14] = (new_voltage_divider >> 8) as u8;
+                self.flash_block_data[15] = (new_voltage_divider & 0xff) as u8;
 
-    fn cc_offset_save(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x0b, 0x00);
-    }
+                for i in 14..=15 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-    fn df_version(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x0c, 0x00);
-    }
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
+                self.delay.delay_ms(150)$($await)*;
+                return Ok(());
+            }
 
-    fn set_fullsleep(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x10, 0x00);
-    }
+            $($async)? fn calibrate_sense_resistor(&mut self, applied_current: i16) -> Result<(), Bq34Z100Error<E>> {
+                let mut current_array: [f32; 50] = [0.0; 50];
+                for current in current_array.iter_mut() {
+                    *current = self.current()$($await)*? as f32;
+                    self.delay.delay_ms(150)$($await)*;
+                }
+                let (current_mean, current_variance) = mean_and_variance(&current_array);
 
-    fn static_chem_chksum(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x17, 0x00);
-    }
+                if current_variance > 100.0 * 100.0 {
+                    //actually this might not be ok?
+                    return Ok(());
+                }
 
-    fn sealed(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        //why do we want to ever seal a hobby diy project tho? anyway pull requests welcome ;)
-        todo!()
-    }
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_CALIBRATION_DATA, 0)$($await)*?;
 
-    fn it_enable(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x21, 0x00);
-    }
+                let mut cc_gain: u32 = (self.flash_block_data[0] as u32) << 24;
+                cc_gain |= (self.flash_block_data[1] as u32) << 16;
+                cc_gain |= (self.flash_block_data[2] as u32) << 8;
+                cc_gain |= self.flash_block_data[3] as u32;
 
-    #[cfg(feature = "write")]
-    fn cal_enable(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x2d, 0x00);
-    }
-    #[cfg(feature = "write")]
-    fn reset(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x41, 0x00);
-    }
-    #[cfg(feature = "write")]
-    fn exit_cal(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x80, 0x00);
-    }
-    #[cfg(feature = "write")]
-    fn enter_cal(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x81, 0x00);
-    }
-    #[cfg(feature = "write")]
-    fn offset_cal(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_control(0x82, 0x00);
-    }
+                let (cc_gain, cc_delta) = sense_resistor_gains(cc_gain, current_mean, applied_current)
+                    .ok_or(Bq34Z100Error::XemicsConversionError)?;
+                self.flash_block_data[0..4].copy_from_slice(&cc_gain.to_be_bytes());
+                self.flash_block_data[4..8].copy_from_slice(&cc_delta.to_be_bytes());
 
-    fn state_of_charge(&mut self) -> Result<u8, Bq34Z100Error<E>> {
-        return self.read_1_register_as_u8(0x02);
-    }
+                for i in 0..=3 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-    fn state_of_charge_max_error(&mut self) -> Result<u8, Bq34Z100Error<E>> {
-        return self.read_1_register_as_u8(0x03);
-    }
+                for i in 4..=7 {
+                    self.write_reg(BLOCK_DATA + i, self.flash_block_data[i as usize])$($await)*?;
+                }
 
-    fn remaining_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x04);
-    }
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
+                return Ok(());
+            }
+        }
 
-    fn full_charge_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x06);
-    }
+        #[cfg(feature = "write")]
+        impl<I2C, DELAY, E: embedded_hal::i2c::Error> $Driver<I2C, DELAY>
+        where
+            I2C: I2c<Error = E>,
+            DELAY: DelayNs,
+        {
+            /// Fails with Unsupported if the firmware lacks the capability, the gauge is identified on first use
+            $($async)? fn require(&mut self, capability: Capabilities) -> Result<(), Bq34Z100Error<E>> {
+                let device = match self.device {
+                    Some(device) => device,
+                    None => self.identify()$($await)*?,
+                };
+                if !device.supports(capability) {
+                    return Err(Bq34Z100Error::Unsupported { capability, variant: device.variant });
+                }
+                return Ok(());
+            }
 
-    fn voltage(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x08);
-    }
+            /// Writes the changed blocks of the image, resets once and verifies the blocks, those the gauge updates
+            /// itself only if verify_all is set
+            $($async)? fn restore_image(&mut self, image: &DataFlashImage, verify_all: bool)
+                -> Result<(), Bq34Z100Error<E>> {
+                let device = self.identify()$($await)*?;
+                if !image.compatible_with(&device) {
+                    return Err(Bq34Z100Error::IncompatibleImage { image: image.device(), device });
+                }
+                self.scale = None;
+                self.unsealed()$($await)*?;
+                let mut changed = false;
+                for (index, &(sub_class, block)) in DataFlashImage::BLOCKS.iter().enumerate() {
+                    self.read_flash_block(sub_class, block * FLASH_BLOCK_SIZE as u8)$($await)*?;
+                    let (current, data) = (self.flash_block_data, image.blocks[index]);
+                    if current == data {
+                        continue;
+                    }
+                    for (position, (&old, &new)) in current.iter().zip(data.iter()).enumerate() {
+                        if old != new {
+                            self.write_reg(BLOCK_DATA + position as u8, new)$($await)*?;
+                        }
+                    }
+                    self.flash_block_data = data;
+                    let checksum = self.flash_block_checksum()$($await)*?;
+                    self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
+                    changed = true;
+                }
+                if !changed {
+                    return Ok(());
+                }
 
-    fn average_current(&mut self) -> Result<i16, Bq34Z100Error<E>> {
-        return Ok(self.read_2_register_as_u16(0x0a)? as i16);
-    }
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
+
+                self.unsealed()$($await)*?;
+                for (index, &(sub_class, block)) in DataFlashImage::BLOCKS.iter().enumerate() {
+                    if !verify_all && DataFlash::subclass(sub_class).is_some_and(|subclass| subclass.updated_by_gauge) {
+                        continue;
+                    }
+                    self.read_flash_block(sub_class, block * FLASH_BLOCK_SIZE as u8)$($await)*?;
+                    if self.flash_block_data != image.blocks[index] {
+                        return Err(Bq34Z100Error::BlockNotStored { sub_class, block });
+                    }
+                }
+                return Ok(());
+            }
 
-    fn temperature(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x0c);
-    }
+            /// Reads the bytes of a parameter block by block, the gauge has to be unsealed
+            $($async)? fn read_parameter_bytes(&mut self, parameter: &DataFlashParameter, bytes: &mut [u8]) -> Result<(), Bq34Z100Error<E>> {
+                for (offset, part) in parameter.block_parts() {
+                    self.read_flash_block(parameter.sub_class, offset)$($await)*?;
+                    let start = offset as usize % FLASH_BLOCK_SIZE;
+                    let len = part.len();
+                    bytes[part].copy_from_slice(&self.flash_block_data[start..start + len]);
+                }
+                return Ok(());
+            }
 
-    fn flags(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x0e);
-    }
+            /// Writes a byte parameter of the first Registers block, resets the gauge and reads the byte back
+            $($async)? fn update_registers_byte(&mut self, parameter: &DataFlashParameter, value: u8) -> Result<(), Bq34Z100Error<E>> {
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
+                let offset = parameter.offset;
+                self.flash_block_data[offset as usize] = value;
+                self.write_reg(BLOCK_DATA + offset, value)$($await)*?;
 
-    fn flags_b(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x12);
-    }
+                let checksum = self.flash_block_checksum()$($await)*?;
+                self.write_reg(BLOCK_DATA_CHECKSUM, checksum)$($await)*?;
 
-    fn current(&mut self) -> Result<i16, Bq34Z100Error<E>> {
-        return Ok(self.read_2_register_as_u16(0x10)? as i16);
-    }
+                self.delay.delay_ms(150)$($await)*;
+                self.reset()$($await)*?;
 
-    fn average_time_to_empty(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x18);
-    }
+                self.unsealed()$($await)*?;
+                self.read_flash_block(SUBCLASS_REGISTERS, 0)$($await)*?;
 
-    fn average_time_to_full(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x1a);
-    }
+                return verify_stored(parameter, value, self.flash_block_data[offset as usize]);
+            }
+        }
 
-    fn passed_charge(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x1c);
-    }
 
-    fn do_d0_time(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x1e);
-    }
+        impl<I2C, Delay> $Driver<I2C, Delay> {
+            /// Gives the scale of the pack up front, eg. `new(i2c, delay, None).with_scale(Scale { .. })`. This is the
+            /// way to read scaled capacity, energy and power of a sealed SCALED pack, whose scale can not be detected
+            /// without unsealing it, and of packs with a coulomb counter calibrated to a larger unit than 1 mA.
+            pub fn with_scale(mut self, scale: Scale) -> Self {
+                self.scale = Some(scale);
+                return self;
+            }
 
-    fn available_energy(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x24);
-    }
+            /// The 7 bit I2C address the driver talks to
+            pub fn address(&self) -> u8 {
+                return self.address;
+            }
 
-    fn average_power(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x26);
-    }
+            /// Block loaded by the last read_flash_block
+            #[cfg(feature = "write")]
+            pub fn flash_block_data(&self) -> &[u8; 32] {
+                return &self.flash_block_data;
+            }
 
-    fn serial_number(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x28);
-    }
+            /// Block that is written by the next write_flash_block
+            #[cfg(feature = "write")]
+            pub fn flash_block_data_mut(&mut self) -> &mut [u8; 32] {
+                return &mut self.flash_block_data;
+            }
 
-    fn cycle_count(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x2c);
-    }
+            /// Destroys the driver and hands back the bus and the delay
+            pub fn release(self) -> (I2C, Delay) {
+                return (self.i2c, self.delay);
+            }
+        }
 
-    fn state_of_health(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x2e);
-    }
+        impl<I2C, DELAY, E: embedded_hal::i2c::Error> $Driver<I2C, DELAY>
+        where
+            I2C: I2c<Error = E>,
+            DELAY: DelayNs,
+        {
+            /// Sets the waiting times between transactions, see [`BusTiming`]
+            pub fn set_bus_timing(&mut self, timing: BusTiming) {
+                self.timing = timing;
+            }
 
-    fn charge_voltage(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x30);
-    }
+            pub fn bus_timing(&self) -> BusTiming {
+                return self.timing;
+            }
 
-    fn charge_current(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x32);
-    }
+            /// Sets which transactions are retried and how often, see [`RetryPolicy`]
+            pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+                self.retry_policy = policy;
+            }
 
-    fn pack_configuration(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x3a);
-    }
+            pub fn retry_policy(&self) -> RetryPolicy {
+                return self.retry_policy;
+            }
 
-    fn design_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x3c);
-    }
+            pub fn retry_statistics(&self) -> RetryStatistics {
+                return self.retry_statistics;
+            }
 
-    fn grid_number(&mut self) -> Result<u8, Bq34Z100Error<E>> {
-        return self.read_1_register_as_u8(0x62);
-    }
+            pub fn reset_retry_statistics(&mut self) {
+                self.retry_statistics = RetryStatistics::default();
+            }
 
-    fn learned_status(&mut self) -> Result<u8, Bq34Z100Error<E>> {
-        return self.read_1_register_as_u8(0x63);
-    }
+            // every transaction goes through these, so the gauge always gets its bus free time and the retry policy applies
+            $($async)? fn bus_write(&mut self, operation: BusOperation, address: u8, data: &[u8]) -> Result<(), Bq34Z100Error<E>> {
+                let mut attempt = 0;
+                loop {
+                    let result = self.i2c.write(address, data)$($await)*;
+                    self.delay.delay_us(self.timing.bus_free_us)$($await)*;
+                    if !self.retry(operation, &result, &mut attempt)$($await)* {
+                        return result.map_err(|error| Bq34Z100Error::I2C { error, operation });
+                    }
+                }
+            }
 
-    fn dod_at_eoc(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x64);
-    }
+            #[cfg(feature = "write")]
+            $($async)? fn bus_read(&mut self, operation: BusOperation, address: u8, buffer: &mut [u8]) -> Result<(), Bq34Z100Error<E>> {
+                let mut attempt = 0;
+                loop {
+                    let result = self.i2c.read(address, buffer)$($await)*;
+                    self.delay.delay_us(self.timing.bus_free_us)$($await)*;
+                    if !self.retry(operation, &result, &mut attempt)$($await)* {
+                        return result.map_err(|error| Bq34Z100Error::I2C { error, operation });
+                    }
+                }
+            }
 
-    fn q_start(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x66);
-    }
+            $($async)? fn bus_write_read(
+                &mut self,
+                operation: BusOperation,
+                address: u8,
+                data: &[u8],
+                buffer: &mut [u8],
+            ) -> Result<(), Bq34Z100Error<E>> {
+                let mut attempt = 0;
+                loop {
+                    let result = self.i2c.write_read(address, data, buffer)$($await)*;
+                    self.delay.delay_us(self.timing.bus_free_us)$($await)*;
+                    if !self.retry(operation, &result, &mut attempt)$($await)* {
+                        return result.map_err(|error| Bq34Z100Error::I2C { error, operation });
+                    }
+                }
+            }
 
-    fn true_fcc(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x6a);
-    }
+            /// Write of [CONTROL, lsb, msb] to Control(), followed by the time the gauge needs to process it
+            $($async)? fn control_write(&mut self, data: &[u8; 3]) -> Result<(), Bq34Z100Error<E>> {
+                let operation = BusOperation::Control { subcommand: u16::from_le_bytes([data[1], data[2]]) };
+                let mut attempt = 0;
+                loop {
+                    let result = self.i2c.write(self.address, data)$($await)*;
+                    self.delay.delay_us(self.timing.bus_free_us.max(self.timing.control_us))$($await)*;
+                    if !self.retry(operation, &result, &mut attempt)$($await)* {
+                        return result.map_err(|error| Bq34Z100Error::I2C { error, operation });
+                    }
+                }
+            }
 
-    fn state_time(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x6c);
-    }
+            /// Books the attempt and waits the backoff if the transaction should be repeated
+            $($async)? fn retry(&mut self, operation: BusOperation, result: &Result<(), E>, attempt: &mut u8) -> bool {
+                let policy = self.retry_policy;
+                let outcome = result.as_ref().map(|_| ()).map_err(|error| error.kind());
+                let operations = operation.retry_operations();
+                return match crate::retry::next_attempt(&policy, &mut self.retry_statistics, operations, outcome, attempt) {
+                    Some(backoff_us) => {
+                        self.delay.delay_us(backoff_us)$($await)*;
+                        true
+                    }
+                    None => false,
+                };
+            }
 
-    fn q_max_passed_q(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x6e);
-    }
+            /// Scale of the readings, detected on first use and cached until the pack configuration or energy
+            /// scale is updated. Without the SCALED bit the pack is taken as unscaled. With it, energy and power are
+            /// scaled by Design Energy Scale, which is read from the data flash and so only while the gauge is
+            /// unsealed, a sealed gauge fails with SecurityAccessDenied instead of being unsealed behind the caller's
+            /// back. Current and capacity come in the unit the coulomb counter was calibrated to, which the gauge does
+            /// not store: the detected scale has a current factor of 1, give the scale with with_scale() (or
+            /// set_scale()) for packs calibrated to a larger unit and for sealed gauges.
+            pub $($async)? fn scale(&mut self) -> Result<Scale, Bq34Z100Error<E>> {
+                if let Some(scale) = self.scale {
+                    return Ok(scale);
+                }
+                let scale = self.detect_scale()$($await)*?;
+                self.scale = Some(scale);
+                return Ok(scale);
+            }
 
-    fn dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x70);
-    }
+            /// Configures the scale of the pack, kept until forget_scale() or an update of the pack configuration or
+            /// energy scale
+            pub fn set_scale(&mut self, scale: Scale) {
+                self.scale = Some(scale);
+            }
 
-    fn q_max_dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x72);
-    }
+            /// Drops the cached scale, the next scale() reads it from the gauge again
+            pub fn forget_scale(&mut self) {
+                self.scale = None;
+            }
 
-    fn q_max_time(&mut self) -> Result<u16, Bq34Z100Error<E>> {
-        return self.read_2_register_as_u16(0x74);
-    }
+            /// The readings as typed units with the sign and the scale of the pack applied. If the scale of a SCALED
+            /// pack can not be detected only the scaled readings fail (with UnknownScale), voltage, temperature and
+            /// times are still read.
+            pub $($async)? fn measurements(&mut self) -> Result<$Measurements<'_, Self, E>, Bq34Z100Error<E>> {
+                let scale = self.readings_scale()$($await)*?;
+                return Ok($Measurements::with_detected_scale(self, scale));
+            }
 
-    fn get_flags_decoded(&mut self) -> Result<Flags, Bq34Z100Error<E>> {
-        let flags = self.flags()?.to_le_bytes();
-
-        return Ok(Flags {
-            fast_charge_allowed: flags[0] >> 0 & 1 > 0,
-            full_chage: flags[0] >> 1 & 1 > 0,
-            charging_not_allowed: flags[0] >> 2 & 1 > 0,
-            charge_inhibit: flags[0] >> 3 & 1 > 0,
-            bat_low: flags[0] >> 4 & 1 > 0,
-            bat_high: flags[0] >> 5 & 1 > 0,
-            over_temp_discharge: flags[0] >> 6 & 1 > 0,
-            over_temp_charge: flags[0] >> 7 & 1 > 0,
-
-            discharge: flags[1] >> 0 & 1 > 0,
-            state_of_charge_f: flags[1] >> 1 & 1 > 0,
-            state_of_charge_1: flags[1] >> 2 & 1 > 0,
-            cf: flags[1] >> 4 & 1 > 0,
-            ocv_taken: flags[1] >> 7 & 1 > 0,
-        });
-    }
+            /// scale() for the typed readings, None if the scale of the SCALED pack can not be detected
+            pub(crate) $($async)? fn readings_scale(&mut self) -> Result<Option<Scale>, Bq34Z100Error<E>> {
+                return match self.scale()$($await)* {
+                    Ok(scale) => Ok(Some(scale)),
+                    Err(Bq34Z100Error::SecurityAccessDenied { .. } | Bq34Z100Error::UnknownScale) => Ok(None),
+                    Err(error) => Err(error),
+                };
+            }
+
+            $($async)? fn detect_scale(&mut self) -> Result<Scale, Bq34Z100Error<E>> {
+                if !self.pack_configuration_decoded()$($await)*?.scaled {
+                    return Ok(Scale::UNSCALED);
+                }
+                let actual = self.security_mode()$($await)*?;
+                if actual == SecurityMode::Sealed {
+                    return Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Unsealed, actual });
+                }
+                #[cfg(feature = "write")]
+                if let DataFlashValue::Integer(factor) = self.read_parameter(&DataFlash::DESIGN_ENERGY_SCALE)$($await)*? {
+                    return Ok(Scale { current: 1, energy: (factor as u16).max(1) });
+                }
+                return Err(Bq34Z100Error::UnknownScale);
+            }
+        }
+    };
 }
+#[cfg(feature = "async")]
+pub(crate) use gauge_driver;
+
+gauge_driver!(
+    [] []
+    Bq34z100g1Driver: Bq34z100g1, FlashStreamProgrammer, GaugeConfigurator, GaugeCalibrator; Measurements
+);
 
 pub struct Bq34z100g1Driver<I2C, Delay> {
-    pub i2c: I2C,
-    pub delay: Delay,
-    pub flash_block_data: [u8; 32],
-}
-pub trait Bq34z100g1<E> {
-    #[cfg(feature = "flashstream")]
-    fn write_flash_stream_i2c(&mut self, line: &str, dryrun: bool) -> Result<(), Bq34Z100Error<E>>;
-    fn read_2_register_as_u16(&mut self, address: u8) -> Result<u16, Bq34Z100Error<E>>;
-    fn read_1_register_as_u8(&mut self, address: u8) -> Result<u8, Bq34Z100Error<E>>;
-    fn read_control(&mut self, address_lsb: u8, address_msb: u8) -> Result<u16, Bq34Z100Error<E>>;
-    fn write_reg(&mut self, address: u8, value: u8) -> Result<(), Bq34Z100Error<E>>;
+    i2c: I2C,
+    delay: Delay,
+    address: u8,
+    unseal_key: SecurityKey,
+    timing: BusTiming,
+    retry_policy: RetryPolicy,
+    retry_statistics: RetryStatistics,
+    scale: Option<Scale>,
+    device: Option<DeviceInfo>,
+    selected_block: Option<(u8, u8)>, // data flash (subclass, block), only used for the context of errors
+    unsealed_known: bool,             // set by the typestate driver, unsealed() skips the key until the next reset
     #[cfg(feature = "write")]
-    fn read_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn write_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn flash_block_checksum(&mut self) -> Result<u8, Bq34Z100Error<E>>;
+    flash_block_data: [u8; 32],
+}
 
-    fn unsealed(&mut self) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn enter_calibration(&mut self) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn exit_calibration(&mut self) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_design_capacity(&mut self, capacity: u16) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_q_max(&mut self, capacity: i16) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_design_energy(&mut self, energy: i16, scale: u8) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_cell_charge_voltage_range(
-        &mut self,
-        t1_t2: u16,
-        t2_t3: u16,
-        t3_t4: u16,
-    ) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_number_of_series_cells(&mut self, cells: u8) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_pack_configuration(&mut self, config: u16) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn update_charge_termination_parameters(
-        &mut self,
-        taper_current: i16,
-        min_taper_capacity: i16,
-        cell_taper_voltage: i16,
-        taper_window: u8,
-        tca_set: i8,
-        tca_clear: i8,
-        fc_set: i8,
-        fc_clear: i8,
-    ) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn calibrate_cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn calibrate_board_offset(&mut self) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn calibrate_voltage_divider(&mut self, applied_voltage: f32) -> Result<(), Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn calibrate_sense_resistor(&mut self, applied_current: i16) -> Result<(), Bq34Z100Error<E>>;
-    fn ready(&mut self) -> Result<(), Bq34Z100Error<E>>;
-
-    fn control_status(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn device_type(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn fw_version(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn hw_version(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn reset_data(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn prev_macwrite(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn chem_id(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn board_offset(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn cc_offset(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn cc_offset_save(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn df_version(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn set_fullsleep(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn static_chem_chksum(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn sealed(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn it_enable(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn cal_enable(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn reset(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn exit_cal(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn enter_cal(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn offset_cal(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-
-    fn state_of_charge(&mut self) -> Result<u8, Bq34Z100Error<E>>; // 0 to 100%
-    fn state_of_charge_max_error(&mut self) -> Result<u8, Bq34Z100Error<E>>; // 1 to 100%
-    fn remaining_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn full_charge_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn voltage(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mV
-    fn average_current(&mut self) -> Result<i16, Bq34Z100Error<E>>; // mA
-    fn temperature(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Unit of x10 K
-    fn flags(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn flags_b(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn current(&mut self) -> Result<i16, Bq34Z100Error<E>>; // mA
-
-    fn average_time_to_empty(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Minutes
-    fn average_time_to_full(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Minutes
-    fn passed_charge(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn do_d0_time(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Minutes
-    fn available_energy(&mut self) -> Result<u16, Bq34Z100Error<E>>; // 10 mWh
-    fn average_power(&mut self) -> Result<u16, Bq34Z100Error<E>>; // 10 mW
-    fn serial_number(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn internal_temperature(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Unit of x10 K
-    fn cycle_count(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Counts
-    fn state_of_health(&mut self) -> Result<u16, Bq34Z100Error<E>>; // 0 to 100%
-    fn charge_voltage(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mV
-    fn charge_current(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mA
-    fn pack_configuration(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn design_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn grid_number(&mut self) -> Result<u8, Bq34Z100Error<E>>;
-    fn learned_status(&mut self) -> Result<u8, Bq34Z100Error<E>>;
-    fn dod_at_eoc(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn q_start(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn true_fcc(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn state_time(&mut self) -> Result<u16, Bq34Z100Error<E>>; // s
-    fn q_max_passed_q(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
-    fn dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn q_max_dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    fn q_max_time(&mut self) -> Result<u16, Bq34Z100Error<E>>;
-    #[cfg(feature = "write")]
-    fn set_led_mode(&mut self, led_config: u8) -> Result<(), Bq34Z100Error<E>>;
-    fn get_flags_decoded(&mut self) -> Result<Flags, Bq34Z100Error<E>>;
+impl<I2C, Delay> Bq34z100g1Driver<I2C, Delay> {
+    /// Creates a new driver, address defaults to [`BQ34Z100_G1_ADDRESS`] if None is given.
+    /// The bus can also be a shared one, eg. one of the embedded-hal-bus device wrappers.
+    pub fn new(i2c: I2C, delay: Delay, address: Option<u8>) -> Self {
+        return Bq34z100g1Driver {
+            i2c,
+            delay,
+            address: address.unwrap_or(BQ34Z100_G1_ADDRESS),
+            unseal_key: SecurityKey::DEFAULT_UNSEAL,
+            timing: BusTiming::DATASHEET,
+            retry_policy: RetryPolicy::NONE,
+            retry_statistics: RetryStatistics::default(),
+            scale: None,
+            device: None,
+            selected_block: None,
+            unsealed_known: false,
+            #[cfg(feature = "write")]
+            flash_block_data: [0; 32],
+        };
+    }
 }
 
-#[derive(Debug)]
-pub struct Flags {
-    pub fast_charge_allowed: bool,
-    pub full_chage: bool,
-    pub charging_not_allowed: bool,
-    pub charge_inhibit: bool,
-    pub bat_low: bool,
-    pub bat_high: bool,
-    pub over_temp_discharge: bool,
-    pub over_temp_charge: bool,
-    pub discharge: bool,
-    pub state_of_charge_f: bool,
-    pub state_of_charge_1: bool,
-    pub cf: bool,
-    pub ocv_taken: bool,
+/// Declares the driver traits, blocking with [] and async with [async], so both drivers offer the same operations
+macro_rules! gauge_traits {
+    ([$($async:tt)?] $Gauge:ident, $FlashStream:ident, $Configurator:ident, $Calibrator:ident) => {
+        /// Register, Control() and security access of the gauge, the other traits build on it
+        #[allow(async_fn_in_trait)]
+        pub trait $Gauge<E> {
+            $($async)? fn read_2_register_as_u16(&mut self, address: u8) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn read_1_register_as_u8(&mut self, address: u8) -> Result<u8, Bq34Z100Error<E>>;
+            $($async)? fn read_control(&mut self, address_lsb: u8, address_msb: u8) -> Result<u16, Bq34Z100Error<E>>;
+            /** Incremental burst read of consecutive standard commands starting at address */
+            $($async)? fn read_registers(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn write_reg(&mut self, address: u8, value: u8) -> Result<(), Bq34Z100Error<E>>;
+            #[cfg(feature = "write")]
+            $($async)? fn read_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>>;
+            /** Writes flash_block_data as the block of the subclass holding offset, followed by its checksum */
+            #[cfg(feature = "write")]
+            $($async)? fn write_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<E>>;
+            #[cfg(feature = "write")]
+            $($async)? fn flash_block_checksum(&mut self) -> Result<u8, Bq34Z100Error<E>>;
+            $($async)? fn unsealed(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            /** Unseals with the key and checks CONTROL_STATUS, the key is then also used by unsealed() */
+            $($async)? fn unseal(&mut self, key: SecurityKey) -> Result<(), Bq34Z100Error<E>>;
+            /** Enters full access, the gauge has to be unsealed already */
+            $($async)? fn full_access(&mut self, key: SecurityKey) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn seal(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn security_mode(&mut self) -> Result<SecurityMode, Bq34Z100Error<E>>;
+            /** Reads DEVICE_TYPE, FW_VERSION, HW_VERSION and DF_VERSION, the result is kept to check firmware dependent operations */
+            $($async)? fn identify(&mut self) -> Result<DeviceInfo, Bq34Z100Error<E>>;
+            $($async)? fn ready(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            /** Executes a subcommand after checking its security mode, returns the result word of the subcommands having one */
+            $($async)? fn send_control(&mut self, command: ControlCommand) -> Result<Option<u16>, Bq34Z100Error<E>>;
+            $($async)? fn board_offset(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn cc_offset_save(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn set_fullsleep(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn set_hibernate(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn clear_hibernate(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn sealed(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn it_enable(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            #[cfg(feature = "write")]
+            $($async)? fn cal_enable(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            #[cfg(feature = "write")]
+            $($async)? fn reset(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            #[cfg(feature = "write")]
+            $($async)? fn exit_cal(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            #[cfg(feature = "write")]
+            $($async)? fn enter_cal(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            /** Internal coulomb counter offset, only valid in calibration mode */
+            #[cfg(feature = "write")]
+            $($async)? fn offset_cal(&mut self) -> Result<i16, Bq34Z100Error<E>>;
+        }
+
+        /// Programming of TI flash stream files (.bq.fs, .df.fs) line by line
+        #[cfg(feature = "flashstream")]
+        #[allow(async_fn_in_trait)]
+        pub trait $FlashStream<E> {
+            $($async)? fn write_flash_stream_i2c(&mut self, line: &str, dryrun: bool) -> Result<(), Bq34Z100Error<E>>;
+        }
+
+        /// Data flash updates, every one is verified by reading it back after a reset
+        #[cfg(feature = "write")]
+        #[allow(async_fn_in_trait)]
+        pub trait $Configurator<E> {
+            $($async)? fn update_design_capacity(&mut self, capacity: u16) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn update_q_max(&mut self, capacity: i16) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn update_design_energy(&mut self, energy: i16, scale: u8) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn update_cell_charge_voltage_range(
+                &mut self,
+                t1_t2: u16,
+                t2_t3: u16,
+                t3_t4: u16,
+            ) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn update_number_of_series_cells(&mut self, cells: u8) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn update_pack_configuration(&mut self, config: PackConfiguration) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn pack_configuration_b(&mut self) -> Result<PackConfigurationB, Bq34Z100Error<E>>;
+            $($async)? fn update_pack_configuration_b(&mut self, config: PackConfigurationB) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn pack_configuration_c(&mut self) -> Result<PackConfigurationC, Bq34Z100Error<E>>;
+            $($async)? fn update_pack_configuration_c(&mut self, config: PackConfigurationC) -> Result<(), Bq34Z100Error<E>>;
+            #[allow(clippy::too_many_arguments)]
+            $($async)? fn update_charge_termination_parameters(
+                &mut self,
+                taper_current: i16,
+                min_taper_capacity: i16,
+                cell_taper_voltage: i16,
+                taper_window: u8,
+                tca_set: i8,
+                tca_clear: i8,
+                fc_set: i8,
+                fc_clear: i8,
+            ) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn set_led_mode(&mut self, led_config: u8) -> Result<(), Bq34Z100Error<E>>;
+            /** Reads any data flash parameter, also one spanning two blocks */
+            $($async)? fn read_parameter(&mut self, parameter: &DataFlashParameter) -> Result<DataFlashValue, Bq34Z100Error<E>>;
+            /** Writes any data flash parameter after checking its kind and range, block by block with their checksums */
+            $($async)? fn write_parameter(&mut self, parameter: &DataFlashParameter, value: DataFlashValue) -> Result<(), Bq34Z100Error<E>>;
+            /** Writes all edits block by block, resets once and reads every parameter back */
+            $($async)? fn commit_transaction<const N: usize>(
+                &mut self,
+                transaction: &DataFlashTransaction<N>,
+            ) -> Result<TransactionReport<N>, Bq34Z100Error<E>>;
+            /** Reads every block of the known subclasses except the security keys, with the identity of the gauge */
+            $($async)? fn dump_data_flash(&mut self) -> Result<DataFlashImage, Bq34Z100Error<E>>;
+            /**
+             * Writes the changed blocks of an image of the same firmware, resets once and verifies the blocks. The
+             * blocks the gauge updates itself while running (learned state, lifetime data, Ra tables) are written but
+             * not verified, they may already differ from the image after the reset.
+             */
+            $($async)? fn restore_data_flash(&mut self, image: &DataFlashImage) -> Result<(), Bq34Z100Error<E>>;
+            /** restore_data_flash() verifying every block, for gauges that can not update their data flash meanwhile */
+            $($async)? fn restore_data_flash_strict(&mut self, image: &DataFlashImage) -> Result<(), Bq34Z100Error<E>>;
+        }
+
+        /// Calibration of the coulomb counter offsets, the voltage divider and the sense resistor
+        #[cfg(feature = "write")]
+        #[allow(async_fn_in_trait)]
+        pub trait $Calibrator<E> {
+            $($async)? fn enter_calibration(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn exit_calibration(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn calibrate_cc_offset(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn calibrate_board_offset(&mut self) -> Result<(), Bq34Z100Error<E>>;
+            /** Reads the internal coulomb counter offset with OFFSET_CAL, entering and leaving calibration mode */
+            $($async)? fn measure_cc_offset(&mut self) -> Result<i16, Bq34Z100Error<E>>;
+            $($async)? fn calibrate_voltage_divider(&mut self, applied_voltage: f32) -> Result<(), Bq34Z100Error<E>>;
+            $($async)? fn calibrate_sense_resistor(&mut self, applied_current: i16) -> Result<(), Bq34Z100Error<E>>;
+        }
+    };
+}
+#[cfg(feature = "async")]
+pub(crate) use gauge_traits;
+
+gauge_traits!([] Bq34z100g1, FlashStreamProgrammer, GaugeConfigurator, GaugeCalibrator);
+
+/// Decoded CONTROL_STATUS subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ControlStatus {
+    pub full_access_sealed: bool,         // FAS
+    pub sealed: bool,                     // SS
+    pub calibration_enabled: bool,        // CALEN
+    pub cc_offset_calibration: bool,      // CCA
+    pub board_offset_calibration: bool,   // BCA
+    pub checksum_valid: bool,             // CSV
+    pub full_sleep: bool,                 // FULLSLEEP
+    pub sleep: bool,                      // SLEEP
+    pub constant_power_load: bool,        // LDMD, constant current load otherwise
+    pub resistance_update_disabled: bool, // RUP_DIS
+    pub voltage_ok: bool,                 // VOK
+    pub impedance_track_enabled: bool,    // QEN
+}
+
+impl ControlStatus {
+    pub fn security_mode(&self) -> SecurityMode {
+        if self.sealed {
+            return SecurityMode::Sealed;
+        }
+        if self.full_access_sealed {
+            return SecurityMode::Unsealed;
+        }
+        return SecurityMode::FullAccess;
+    }
+
+    /// CC offset or board offset calibration still in progress
+    pub fn offset_calibration_running(&self) -> bool {
+        return self.cc_offset_calibration || self.board_offset_calibration;
+    }
+}
+
+impl From<u16> for ControlStatus {
+    fn from(value: u16) -> Self {
+        return ControlStatus {
+            full_access_sealed: value & CONTROL_STATUS_FAS > 0,
+            sealed: value & CONTROL_STATUS_SS > 0,
+            calibration_enabled: value & CONTROL_STATUS_CALEN > 0,
+            cc_offset_calibration: value & CONTROL_STATUS_CCA > 0,
+            board_offset_calibration: value & CONTROL_STATUS_BCA > 0,
+            checksum_valid: value & CONTROL_STATUS_CSV > 0,
+            full_sleep: value & CONTROL_STATUS_FULLSLEEP > 0,
+            sleep: value & CONTROL_STATUS_SLEEP > 0,
+            constant_power_load: value & CONTROL_STATUS_LDMD > 0,
+            resistance_update_disabled: value & CONTROL_STATUS_RUP_DIS > 0,
+            voltage_ok: value & CONTROL_STATUS_VOK > 0,
+            impedance_track_enabled: value & CONTROL_STATUS_QEN > 0,
+        };
+    }
 }
@@ -0,0 +1,246 @@
+//! Pack Configuration, Pack Configuration B and Pack Configuration C of the Registers subclass (64).
+//!
+//! Pack Configuration is also readable through the PackConfiguration() standard command. Bits not listed here are
+//! reserved and left as they are in data flash when updating.
+use bitflags::bitflags;
+
+const RESCAP: u16 = 1 << 15;
+const CALEN: u16 = 1 << 14;
+const INTPOL: u16 = 1 << 13;
+const INTSEL: u16 = 1 << 12;
+const IWAKE: u16 = 1 << 11;
+const RSNS_SHIFT: u16 = 9;
+const RSNS_MASK: u16 = 0b11 << RSNS_SHIFT;
+const SE: u16 = 1 << 8;
+const SCALED: u16 = 1 << 7;
+const SLEEP: u16 = 1 << 6;
+const RMFCC: u16 = 1 << 5;
+const NIDT: u16 = 1 << 4;
+const VOLTSEL: u16 = 1 << 3;
+const GNDSEL: u16 = 1 << 2;
+const TEMPS: u16 = 1 << 0;
+
+/// Every bit of Pack Configuration that has a meaning, the rest is reserved
+#[cfg(feature = "write")]
+pub(crate) const PACK_CONFIGURATION_MASK: u16 = !(1 << 1);
+
+/// Combinations of Pack Configuration bits the gauge cannot work with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackConfigurationError {
+    /// RSNS only has two bits
+    WakeSenseOutOfRange { rsns: u8 },
+    /// IWAKE selects the wake threshold, but RSNS = 0 disables waking on current
+    WakeThresholdWithoutSense,
+    /// More than one series cell exceeds the input range of the internal divider, VOLTSEL has to be set
+    ExternalDividerRequired { cells: u8 },
+}
+
+/// Pack Configuration data flash word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PackConfiguration {
+    pub rescap: bool,                   // RESCAP, no-load rate of compensation applied to the reserve capacity
+    pub calibration_enabled: bool,      // CALEN
+    pub interrupt_active_high: bool,    // INTPOL
+    pub interrupt_output: bool,         // INTSEL, interrupt on the HDQ pin instead of P5
+    pub wake_threshold_high: bool,      // IWAKE
+    pub wake_sense: u8,                 // RSNS1:RSNS0, 0 disables waking from SLEEP on current
+    pub shutdown_enabled: bool,         // SE
+    pub scaled: bool,                   // SCALED, capacity and energy are reported scaled
+    pub sleep_enabled: bool,            // SLEEP
+    pub remaining_capacity_fcc: bool,   // RMFCC, RemainingCapacity() set to FullChargeCapacity() on primary charge termination
+    pub nimh_temperature_detect: bool,  // NiDT
+    pub external_voltage_divider: bool, // VOLTSEL
+    pub ground_select: bool,            // GNDSEL, ADC ground on Vss instead of SRP
+    pub external_temperature: bool,     // TEMPS, external thermistor instead of the internal sensor
+}
+
+impl PackConfiguration {
+    pub fn builder() -> PackConfigurationBuilder {
+        return PackConfigurationBuilder { config: PackConfiguration::default() };
+    }
+
+    /// Builder starting from this configuration, eg. to change a single bit of the value read from the gauge
+    pub fn to_builder(self) -> PackConfigurationBuilder {
+        return PackConfigurationBuilder { config: self };
+    }
+
+    pub fn validate(&self) -> Result<(), PackConfigurationError> {
+        if self.wake_sense > 3 {
+            return Err(PackConfigurationError::WakeSenseOutOfRange { rsns: self.wake_sense });
+        }
+        if self.wake_threshold_high && self.wake_sense == 0 {
+            return Err(PackConfigurationError::WakeThresholdWithoutSense);
+        }
+        return Ok(());
+    }
+
+    /// validate(), plus the checks that depend on the number of series cells
+    pub fn validate_for_cells(&self, cells: u8) -> Result<(), PackConfigurationError> {
+        self.validate()?;
+        if cells > 1 && !self.external_voltage_divider {
+            return Err(PackConfigurationError::ExternalDividerRequired { cells });
+        }
+        return Ok(());
+    }
+
+    pub fn bits(&self) -> u16 {
+        let flag = |set: bool, bit: u16| if set { bit } else { 0 };
+        return flag(self.rescap, RESCAP)
+            | flag(self.calibration_enabled, CALEN)
+            | flag(self.interrupt_active_high, INTPOL)
+            | flag(self.interrupt_output, INTSEL)
+            | flag(self.wake_threshold_high, IWAKE)
+            | ((self.wake_sense as u16) << RSNS_SHIFT) & RSNS_MASK
+            | flag(self.shutdown_enabled, SE)
+            | flag(self.scaled, SCALED)
+            | flag(self.sleep_enabled, SLEEP)
+            | flag(self.remaining_capacity_fcc, RMFCC)
+            | flag(self.nimh_temperature_detect, NIDT)
+            | flag(self.external_voltage_divider, VOLTSEL)
+            | flag(self.ground_select, GNDSEL)
+            | flag(self.external_temperature, TEMPS);
+    }
+}
+
+impl From<u16> for PackConfiguration {
+    fn from(value: u16) -> Self {
+        return PackConfiguration {
+            rescap: value & RESCAP > 0,
+            calibration_enabled: value & CALEN > 0,
+            interrupt_active_high: value & INTPOL > 0,
+            interrupt_output: value & INTSEL > 0,
+            wake_threshold_high: value & IWAKE > 0,
+            wake_sense: ((value & RSNS_MASK) >> RSNS_SHIFT) as u8,
+            shutdown_enabled: value & SE > 0,
+            scaled: value & SCALED > 0,
+            sleep_enabled: value & SLEEP > 0,
+            remaining_capacity_fcc: value & RMFCC > 0,
+            nimh_temperature_detect: value & NIDT > 0,
+            external_voltage_divider: value & VOLTSEL > 0,
+            ground_select: value & GNDSEL > 0,
+            external_temperature: value & TEMPS > 0,
+        };
+    }
+}
+
+impl From<PackConfiguration> for u16 {
+    fn from(value: PackConfiguration) -> Self {
+        return value.bits();
+    }
+}
+
+/// Builds a [`PackConfiguration`], build() rejects illegal combinations
+#[derive(Debug, Clone, Copy)]
+pub struct PackConfigurationBuilder {
+    config: PackConfiguration,
+}
+
+impl PackConfigurationBuilder {
+    pub fn rescap(mut self, enabled: bool) -> Self {
+        self.config.rescap = enabled;
+        return self;
+    }
+
+    pub fn calibration_enabled(mut self, enabled: bool) -> Self {
+        self.config.calibration_enabled = enabled;
+        return self;
+    }
+
+    pub fn interrupt_active_high(mut self, active_high: bool) -> Self {
+        self.config.interrupt_active_high = active_high;
+        return self;
+    }
+
+    pub fn interrupt_output(mut self, enabled: bool) -> Self {
+        self.config.interrupt_output = enabled;
+        return self;
+    }
+
+    /// RSNS and IWAKE, wake_sense 0 disables waking on current
+    pub fn wake(mut self, wake_sense: u8, threshold_high: bool) -> Self {
+        self.config.wake_sense = wake_sense;
+        self.config.wake_threshold_high = threshold_high;
+        return self;
+    }
+
+    pub fn shutdown_enabled(mut self, enabled: bool) -> Self {
+        self.config.shutdown_enabled = enabled;
+        return self;
+    }
+
+    pub fn scaled(mut self, scaled: bool) -> Self {
+        self.config.scaled = scaled;
+        return self;
+    }
+
+    pub fn sleep_enabled(mut self, enabled: bool) -> Self {
+        self.config.sleep_enabled = enabled;
+        return self;
+    }
+
+    pub fn remaining_capacity_fcc(mut self, enabled: bool) -> Self {
+        self.config.remaining_capacity_fcc = enabled;
+        return self;
+    }
+
+    pub fn nimh_temperature_detect(mut self, enabled: bool) -> Self {
+        self.config.nimh_temperature_detect = enabled;
+        return self;
+    }
+
+    pub fn external_voltage_divider(mut self, external: bool) -> Self {
+        self.config.external_voltage_divider = external;
+        return self;
+    }
+
+    pub fn ground_select(mut self, vss: bool) -> Self {
+        self.config.ground_select = vss;
+        return self;
+    }
+
+    pub fn external_temperature(mut self, external: bool) -> Self {
+        self.config.external_temperature = external;
+        return self;
+    }
+
+    pub fn build(self) -> Result<PackConfiguration, PackConfigurationError> {
+        self.config.validate()?;
+        return Ok(self.config);
+    }
+}
+
+bitflags! {
+    /// Pack Configuration B data flash byte
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PackConfigurationB: u8 {
+        /// ChgDoDEoC, DOD at end of charge is taken at charge termination
+        const CHG_DOD_EOC = 1 << 7;
+        /// VconsEN, voltage consistency check
+        const VCONS_EN = 1 << 5;
+        /// LFPRelax, LiFePO4 relax detection
+        const LFP_RELAX = 1 << 2;
+        /// DoDWT, DOD weighting for the Qmax update
+        const DOD_WT = 1 << 1;
+        /// FConvEn, fast convergence of the Qmax learning
+        const FCONV_EN = 1 << 0;
+
+        const _ = !0;
+    }
+}
+
+bitflags! {
+    /// Pack Configuration C data flash byte
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct PackConfigurationC: u8 {
+        /// RelaxSmoothOK, smoothing of RemainingCapacity() in relax
+        const RELAX_SMOOTH_OK = 1 << 5;
+        /// RelaxJumpOK, RemainingCapacity() may jump in relax
+        const RELAX_JUMP_OK = 1 << 4;
+        /// SmoothEn, smoothing engine
+        const SMOOTH_EN = 1 << 3;
+        /// SleepWkChg, compensation for the charge passed in SLEEP
+        const SLEEP_WK_CHG = 1 << 2;
+
+        const _ = !0;
+    }
+}
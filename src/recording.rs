@@ -0,0 +1,432 @@
+//! Recording and replaying of the I2C traffic between driver and gauge, for golden regression tests.
+//!
+//! A [`Recorder`] hands out a [`RecordingI2c`] and a [`RecordingDelay`] that wrap the real bus and delay. Every
+//! `write`, `read`, `write_read` and `transaction` is logged with its result, timestamped by the time that passed
+//! through the delay provider. [`Recorder::save`] stores the session in a compact binary format.
+//!
+//! [`Replay`] loads such a session and implements [`I2c`] itself: it serves the recorded responses (and errors) and
+//! panics as soon as the driver issues a different call than recorded. [`Replay::done`] checks that the whole
+//! session was consumed.
+//!
+//! ```ignore
+//! let recorder = Recorder::new();
+//! let mut gauge = Bq34z100g1Driver::new(recorder.i2c(i2c), recorder.delay(delay), None);
+//! gauge.update_design_capacity(3000)?;
+//! recorder.save(&mut std::fs::File::create("update_design_capacity.bqrec")?)?;
+//!
+//! // later, offline
+//! let replay = Replay::load(&mut std::fs::File::open("update_design_capacity.bqrec")?)?;
+//! let mut gauge = Bq34z100g1Driver::new(replay, NoopDelay, None);
+//! gauge.update_design_capacity(3000)?;
+//! gauge.release().0.done();
+//! ```
+use std::cell::RefCell;
+use std::io::{Read, Write};
+use std::rc::Rc;
+use std::vec;
+use std::vec::Vec;
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::{Error, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+const MAGIC: &[u8; 7] = b"BQ34REC";
+const FORMAT_VERSION: u8 = 1;
+
+/// Which method of the I2c trait was called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum I2cCall {
+    Read,
+    Write,
+    WriteRead,
+    Transaction,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedOperation {
+    /// Bytes sent to the gauge
+    Write(Vec<u8>),
+    /// Bytes received from the gauge
+    Read(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEvent {
+    /// Time passed in the delay provider since the recording started
+    pub time_ns: u64,
+    pub call: I2cCall,
+    pub address: u8,
+    pub operations: Vec<RecordedOperation>,
+    /// Error the bus returned for this call
+    pub error: Option<ErrorKind>,
+}
+
+#[derive(Default)]
+struct Session {
+    time_ns: u64,
+    events: Vec<RecordedEvent>,
+}
+
+/// Collects the traffic of a [`RecordingI2c`] and the time of a [`RecordingDelay`]
+#[derive(Clone, Default)]
+pub struct Recorder {
+    session: Rc<RefCell<Session>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        return Recorder::default();
+    }
+
+    /// Wraps the bus, all calls are logged to this recorder
+    pub fn i2c<I2C>(&self, i2c: I2C) -> RecordingI2c<I2C> {
+        return RecordingI2c {
+            i2c,
+            session: self.session.clone(),
+        };
+    }
+
+    /// Wraps the delay, the time spent in it is used to timestamp the calls
+    pub fn delay<D>(&self, delay: D) -> RecordingDelay<D> {
+        return RecordingDelay {
+            delay,
+            session: self.session.clone(),
+        };
+    }
+
+    pub fn events(&self) -> Vec<RecordedEvent> {
+        return self.session.borrow().events.clone();
+    }
+
+    /// Writes the recorded session in the binary format understood by [`Replay::load`]
+    pub fn save<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        let mut last_time_ns = 0;
+        for event in self.session.borrow().events.iter() {
+            writer.write_all(&[encode_call(event.call), event.address, encode_error(event.error)])?;
+            write_varint(writer, event.time_ns - last_time_ns)?;
+            last_time_ns = event.time_ns;
+            write_varint(writer, event.operations.len() as u64)?;
+            for operation in event.operations.iter() {
+                let (tag, bytes) = match operation {
+                    RecordedOperation::Write(bytes) => (0, bytes),
+                    RecordedOperation::Read(bytes) => (1, bytes),
+                };
+                writer.write_all(&[tag])?;
+                write_varint(writer, bytes.len() as u64)?;
+                writer.write_all(bytes)?;
+            }
+        }
+        return Ok(());
+    }
+}
+
+pub struct RecordingI2c<I2C> {
+    i2c: I2C,
+    session: Rc<RefCell<Session>>,
+}
+
+impl<I2C> RecordingI2c<I2C> {
+    /// Hands back the wrapped bus
+    pub fn release(self) -> I2C {
+        return self.i2c;
+    }
+
+    fn log<E: Error>(&self, call: I2cCall, address: u8, operations: Vec<RecordedOperation>, result: &Result<(), E>) {
+        let mut session = self.session.borrow_mut();
+        let time_ns = session.time_ns;
+        session.events.push(RecordedEvent {
+            time_ns,
+            call,
+            address,
+            operations,
+            error: result.as_ref().err().map(|e| e.kind()),
+        });
+    }
+}
+
+impl<I2C: ErrorType> ErrorType for RecordingI2c<I2C> {
+    type Error = I2C::Error;
+}
+
+impl<I2C: I2c> I2c for RecordingI2c<I2C> {
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.i2c.read(address, read);
+        self.log(I2cCall::Read, address, vec![RecordedOperation::Read(read.to_vec())], &result);
+        return result;
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        let result = self.i2c.write(address, write);
+        self.log(I2cCall::Write, address, vec![RecordedOperation::Write(write.to_vec())], &result);
+        return result;
+    }
+
+    fn write_read(&mut self, address: u8, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        let result = self.i2c.write_read(address, write, read);
+        let operations = vec![RecordedOperation::Write(write.to_vec()), RecordedOperation::Read(read.to_vec())];
+        self.log(I2cCall::WriteRead, address, operations, &result);
+        return result;
+    }
+
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        let result = self.i2c.transaction(address, operations);
+        let recorded = operations
+            .iter()
+            .map(|operation| match operation {
+                Operation::Write(bytes) => RecordedOperation::Write(bytes.to_vec()),
+                Operation::Read(bytes) => RecordedOperation::Read(bytes.to_vec()),
+            })
+            .collect();
+        self.log(I2cCall::Transaction, address, recorded, &result);
+        return result;
+    }
+}
+
+pub struct RecordingDelay<D> {
+    delay: D,
+    session: Rc<RefCell<Session>>,
+}
+
+impl<D> RecordingDelay<D> {
+    /// Hands back the wrapped delay
+    pub fn release(self) -> D {
+        return self.delay;
+    }
+
+    fn advance(&self, ns: u64) {
+        let mut session = self.session.borrow_mut();
+        session.time_ns = session.time_ns.saturating_add(ns);
+    }
+}
+
+impl<D: DelayNs> DelayNs for RecordingDelay<D> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.delay.delay_ns(ns);
+        self.advance(ns as u64);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.delay.delay_us(us);
+        self.advance(us as u64 * 1_000);
+    }
+
+    fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms);
+        self.advance(ms as u64 * 1_000_000);
+    }
+}
+
+/// Error a [`Replay`] returns where the recorded bus returned one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayError {
+    pub kind: ErrorKind,
+}
+
+impl Error for ReplayError {
+    fn kind(&self) -> ErrorKind {
+        return self.kind;
+    }
+}
+
+/// Plays a recorded session back, panics if the driver deviates from it
+pub struct Replay {
+    events: Vec<RecordedEvent>,
+    position: usize,
+}
+
+impl Replay {
+    pub fn new(events: Vec<RecordedEvent>) -> Self {
+        return Replay { events, position: 0 };
+    }
+
+    /// Reads a session written by [`Recorder::save`]
+    pub fn load<R: Read>(reader: &mut R) -> std::io::Result<Self> {
+        let mut header = [0; 8];
+        reader.read_exact(&mut header)?;
+        if &header[0..7] != MAGIC || header[7] != FORMAT_VERSION {
+            return Err(invalid_data("not a bq34z100 recording"));
+        }
+        let mut events = Vec::new();
+        let mut time_ns: u64 = 0;
+        loop {
+            let mut fixed = [0; 3];
+            match reader.read_exact(&mut fixed) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+            let call = decode_call(fixed[0])?;
+            let error = decode_error(fixed[2])?;
+            time_ns += read_varint(reader)?;
+            let count = read_varint(reader)?;
+            let mut operations = Vec::new();
+            for _ in 0..count {
+                let mut tag = [0; 1];
+                reader.read_exact(&mut tag)?;
+                let mut bytes = vec![0; read_varint(reader)? as usize];
+                reader.read_exact(&mut bytes)?;
+                match tag[0] {
+                    0 => operations.push(RecordedOperation::Write(bytes)),
+                    1 => operations.push(RecordedOperation::Read(bytes)),
+                    _ => return Err(invalid_data("unknown operation")),
+                }
+            }
+            events.push(RecordedEvent {
+                time_ns,
+                call,
+                address: fixed[1],
+                operations,
+                error,
+            });
+        }
+        return Ok(Replay::new(events));
+    }
+
+    pub fn events(&self) -> &[RecordedEvent] {
+        return &self.events;
+    }
+
+    /// Panics if not all recorded calls were replayed
+    pub fn done(&self) {
+        assert_eq!(
+            self.position,
+            self.events.len(),
+            "replay ended after {} of {} recorded calls",
+            self.position,
+            self.events.len()
+        );
+    }
+
+    fn replay(&mut self, call: I2cCall, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ReplayError> {
+        let index = self.position;
+        let expected = self
+            .events
+            .get(index)
+            .unwrap_or_else(|| panic!("call {}: {:?} to {:#04x} after the end of the recording", index, call, address));
+        assert_eq!((expected.call, expected.address), (call, address), "call {}: unexpected call or address", index);
+        assert_eq!(expected.operations.len(), operations.len(), "call {}: different number of operations", index);
+        for (recorded, operation) in expected.operations.iter().zip(operations.iter_mut()) {
+            match (recorded, operation) {
+                (RecordedOperation::Write(recorded), Operation::Write(bytes)) => {
+                    assert_eq!(recorded.as_slice(), *bytes, "call {}: different bytes written", index);
+                }
+                (RecordedOperation::Read(recorded), Operation::Read(buffer)) => {
+                    assert_eq!(recorded.len(), buffer.len(), "call {}: different read length", index);
+                    buffer.copy_from_slice(recorded);
+                }
+                (recorded, _) => panic!("call {}: expected {:?}", index, recorded),
+            }
+        }
+        self.position += 1;
+        return match expected.error {
+            Some(kind) => Err(ReplayError { kind }),
+            None => Ok(()),
+        };
+    }
+}
+
+impl ErrorType for Replay {
+    type Error = ReplayError;
+}
+
+impl I2c for Replay {
+    fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        return self.replay(I2cCall::Read, address, &mut [Operation::Read(read)]);
+    }
+
+    fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        return self.replay(I2cCall::Write, address, &mut [Operation::Write(write)]);
+    }
+
+    fn write_read(&mut self, address: u8, write: &[u8], read: &mut [u8]) -> Result<(), Self::Error> {
+        return self.replay(
+            I2cCall::WriteRead,
+            address,
+            &mut [Operation::Write(write), Operation::Read(read)],
+        );
+    }
+
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        return self.replay(I2cCall::Transaction, address, operations);
+    }
+}
+
+fn invalid_data(error: &'static str) -> std::io::Error {
+    return std::io::Error::new(std::io::ErrorKind::InvalidData, error);
+}
+
+fn encode_call(call: I2cCall) -> u8 {
+    match call {
+        I2cCall::Read => 0,
+        I2cCall::Write => 1,
+        I2cCall::WriteRead => 2,
+        I2cCall::Transaction => 3,
+    }
+}
+
+fn decode_call(call: u8) -> std::io::Result<I2cCall> {
+    match call {
+        0 => Ok(I2cCall::Read),
+        1 => Ok(I2cCall::Write),
+        2 => Ok(I2cCall::WriteRead),
+        3 => Ok(I2cCall::Transaction),
+        _ => Err(invalid_data("unknown call")),
+    }
+}
+
+fn encode_error(error: Option<ErrorKind>) -> u8 {
+    match error {
+        None => 0,
+        Some(ErrorKind::Bus) => 1,
+        Some(ErrorKind::ArbitrationLoss) => 2,
+        Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)) => 3,
+        Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data)) => 4,
+        Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown)) => 5,
+        Some(ErrorKind::Overrun) => 6,
+        Some(_) => 7,
+    }
+}
+
+fn decode_error(error: u8) -> std::io::Result<Option<ErrorKind>> {
+    match error {
+        0 => Ok(None),
+        1 => Ok(Some(ErrorKind::Bus)),
+        2 => Ok(Some(ErrorKind::ArbitrationLoss)),
+        3 => Ok(Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))),
+        4 => Ok(Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data))),
+        5 => Ok(Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown))),
+        6 => Ok(Some(ErrorKind::Overrun)),
+        7 => Ok(Some(ErrorKind::Other)),
+        _ => Err(invalid_data("unknown error kind")),
+    }
+}
+
+/// LEB128, keeps the common short transfers and time deltas at one or two bytes
+fn write_varint<W: Write>(writer: &mut W, mut value: u64) -> std::io::Result<()> {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0; 1];
+        reader.read_exact(&mut byte)?;
+        if shift >= 64 {
+            return Err(invalid_data("varint too long"));
+        }
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
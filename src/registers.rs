@@ -0,0 +1,129 @@
+//! Register map of the bq34z100-G1, shared by the blocking and the async driver.
+// not every feature combination uses the whole register map
+#![allow(dead_code)]
+
+/// Default 7 bit I2C address of the gauge
+pub const BQ34Z100_G1_ADDRESS: u8 = 0x55;
+
+// Standard commands
+pub(crate) const CONTROL: u8 = 0x00;
+pub(crate) const STATE_OF_CHARGE: u8 = 0x02;
+pub(crate) const MAX_ERROR: u8 = 0x03;
+pub(crate) const REMAINING_CAPACITY: u8 = 0x04;
+pub(crate) const FULL_CHARGE_CAPACITY: u8 = 0x06;
+pub(crate) const VOLTAGE: u8 = 0x08;
+pub(crate) const AVERAGE_CURRENT: u8 = 0x0a;
+pub(crate) const TEMPERATURE: u8 = 0x0c;
+pub(crate) const FLAGS: u8 = 0x0e;
+pub(crate) const CURRENT: u8 = 0x10;
+pub(crate) const FLAGS_B: u8 = 0x12;
+pub(crate) const AVERAGE_TIME_TO_EMPTY: u8 = 0x18;
+pub(crate) const AVERAGE_TIME_TO_FULL: u8 = 0x1a;
+pub(crate) const PASSED_CHARGE: u8 = 0x1c;
+pub(crate) const DOD0_TIME: u8 = 0x1e;
+pub(crate) const AVAILABLE_ENERGY: u8 = 0x24;
+pub(crate) const AVERAGE_POWER: u8 = 0x26;
+pub(crate) const SERIAL_NUMBER: u8 = 0x28;
+pub(crate) const INTERNAL_TEMPERATURE: u8 = 0x2a;
+pub(crate) const CYCLE_COUNT: u8 = 0x2c;
+pub(crate) const STATE_OF_HEALTH: u8 = 0x2e;
+pub(crate) const CHARGE_VOLTAGE: u8 = 0x30;
+pub(crate) const CHARGE_CURRENT: u8 = 0x32;
+pub(crate) const PACK_CONFIGURATION: u8 = 0x3a;
+pub(crate) const DESIGN_CAPACITY: u8 = 0x3c;
+pub(crate) const DATA_FLASH_CLASS: u8 = 0x3e;
+pub(crate) const DATA_FLASH_BLOCK: u8 = 0x3f;
+pub(crate) const BLOCK_DATA: u8 = 0x40;
+pub(crate) const BLOCK_DATA_CHECKSUM: u8 = 0x60;
+pub(crate) const BLOCK_DATA_CONTROL: u8 = 0x61;
+pub(crate) const GRID_NUMBER: u8 = 0x62;
+pub(crate) const LEARNED_STATUS: u8 = 0x63;
+pub(crate) const DOD_AT_EOC: u8 = 0x64;
+pub(crate) const Q_START: u8 = 0x66;
+pub(crate) const TRUE_RC: u8 = 0x68;
+pub(crate) const TRUE_FCC: u8 = 0x6a;
+pub(crate) const STATE_TIME: u8 = 0x6c;
+pub(crate) const Q_MAX_PASSED_Q: u8 = 0x6e;
+pub(crate) const DOD_0: u8 = 0x70;
+pub(crate) const Q_MAX_DOD_0: u8 = 0x72;
+pub(crate) const Q_MAX_TIME: u8 = 0x74;
+
+// Control() subcommands, written LSB first to CONTROL
+pub(crate) const CONTROL_STATUS: u8 = 0x00;
+pub(crate) const DEVICE_TYPE: u8 = 0x01;
+pub(crate) const FW_VERSION: u8 = 0x02;
+pub(crate) const HW_VERSION: u8 = 0x03;
+pub(crate) const RESET_DATA: u8 = 0x05;
+pub(crate) const PREV_MACWRITE: u8 = 0x07;
+pub(crate) const CHEM_ID: u8 = 0x08;
+pub(crate) const BOARD_OFFSET: u8 = 0x09;
+pub(crate) const CC_OFFSET: u8 = 0x0a;
+pub(crate) const CC_OFFSET_SAVE: u8 = 0x0b;
+pub(crate) const DF_VERSION: u8 = 0x0c;
+pub(crate) const SET_FULLSLEEP: u8 = 0x10;
+pub(crate) const SET_HIBERNATE: u8 = 0x11;
+pub(crate) const CLEAR_HIBERNATE: u8 = 0x12;
+pub(crate) const STATIC_CHEM_CHKSUM: u8 = 0x17;
+pub(crate) const STATIC_DF_CHKSUM: u8 = 0x18;
+pub(crate) const INSTRUCTION_FLASH_CHKSUM: u8 = 0x19;
+pub(crate) const SEALED: u8 = 0x20;
+pub(crate) const IT_ENABLE: u8 = 0x21;
+pub(crate) const CAL_ENABLE: u8 = 0x2d;
+pub(crate) const RESET: u8 = 0x41;
+pub(crate) const EXIT_CAL: u8 = 0x80;
+pub(crate) const ENTER_CAL: u8 = 0x81;
+pub(crate) const OFFSET_CAL: u8 = 0x82;
+/// The only subcommand with a MSB, the gauge leaves for the ROM at address 0x0b
+pub(crate) const ENTER_ROM: u16 = 0x0f00;
+
+// CONTROL_STATUS bits
+pub(crate) const CONTROL_STATUS_FAS: u16 = 0x4000;
+pub(crate) const CONTROL_STATUS_SS: u16 = 0x2000;
+pub(crate) const CONTROL_STATUS_CALEN: u16 = 0x1000;
+pub(crate) const CONTROL_STATUS_CCA: u16 = 0x0800;
+pub(crate) const CONTROL_STATUS_BCA: u16 = 0x0400;
+pub(crate) const CONTROL_STATUS_CSV: u16 = 0x0200;
+pub(crate) const CONTROL_STATUS_FULLSLEEP: u16 = 0x0020;
+pub(crate) const CONTROL_STATUS_SLEEP: u16 = 0x0010;
+pub(crate) const CONTROL_STATUS_LDMD: u16 = 0x0008;
+pub(crate) const CONTROL_STATUS_RUP_DIS: u16 = 0x0004;
+pub(crate) const CONTROL_STATUS_VOK: u16 = 0x0002;
+pub(crate) const CONTROL_STATUS_QEN: u16 = 0x0001;
+
+// Data flash subclasses
+pub(crate) const SUBCLASS_SAFETY: u8 = 2;
+pub(crate) const SUBCLASS_CHARGE_INHIBIT_CFG: u8 = 32;
+pub(crate) const SUBCLASS_CHARGE: u8 = 34;
+pub(crate) const SUBCLASS_CHARGE_TERMINATION: u8 = 36;
+pub(crate) const SUBCLASS_DATA: u8 = 48;
+pub(crate) const SUBCLASS_DISCHARGE: u8 = 49;
+pub(crate) const SUBCLASS_MANUFACTURER_DATA: u8 = 56;
+pub(crate) const SUBCLASS_MANUFACTURER_INFO: u8 = 58;
+pub(crate) const SUBCLASS_LIFETIME_DATA: u8 = 59;
+pub(crate) const SUBCLASS_LIFETIME_TEMP_SAMPLES: u8 = 60;
+pub(crate) const SUBCLASS_REGISTERS: u8 = 64;
+pub(crate) const SUBCLASS_LIFETIME_RESOLUTION: u8 = 66;
+pub(crate) const SUBCLASS_LED_DISPLAY: u8 = 67;
+pub(crate) const SUBCLASS_POWER: u8 = 68;
+pub(crate) const SUBCLASS_IT_CFG: u8 = 80;
+pub(crate) const SUBCLASS_CURRENT_THRESHOLDS: u8 = 81;
+pub(crate) const SUBCLASS_STATE: u8 = 82;
+pub(crate) const SUBCLASS_OCVA_TABLE: u8 = 83;
+pub(crate) const SUBCLASS_RA0: u8 = 88;
+pub(crate) const SUBCLASS_RA0X: u8 = 89;
+pub(crate) const SUBCLASS_CALIBRATION_DATA: u8 = 104;
+pub(crate) const SUBCLASS_CALIBRATION_CURRENT: u8 = 107;
+pub(crate) const SUBCLASS_CODES: u8 = 112;
+
+/// Size of one data flash block as seen through BlockData()
+pub(crate) const FLASH_BLOCK_SIZE: usize = 32;
+/// Reads of a data flash block until a checksum or selection mismatch is returned as error
+pub(crate) const FLASH_BLOCK_READ_ATTEMPTS: u8 = 3;
+
+pub(crate) fn flash_block_checksum(block: &[u8; FLASH_BLOCK_SIZE]) -> u8 {
+    let mut temp: u8 = 0;
+    for i in block.iter() {
+        temp = u8::wrapping_add(temp, *i);
+    }
+    return u8::wrapping_sub(255, temp);
+}
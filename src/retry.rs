@@ -0,0 +1,86 @@
+//! Retrying transactions the gauge did not acknowledge.
+//!
+//! The gauge NACKs while it is busy, eg. while writing data flash or right after a reset. A [`RetryPolicy`] repeats
+//! such transactions after a backoff, [`RetryStatistics`] counts what happened so the bus health can be reported.
+use bitflags::bitflags;
+use embedded_hal::i2c::ErrorKind;
+
+bitflags! {
+    /// Kinds of transactions a [`RetryPolicy`] applies to
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct RetryOperations: u8 {
+        /// Standard command and data flash reads
+        const READ = 1 << 0;
+        /// Control() subcommands and key words
+        const CONTROL = 1 << 1;
+        /// Register and block data writes
+        const WRITE = 1 << 2;
+        /// Writes and compares of a flash stream file
+        const FLASH_STREAM = 1 << 3;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RetryPolicy {
+    pub retries: u8,     // additional attempts after the first one
+    pub backoff_us: u32, // wait before the first retry, doubled for every further one
+    pub operations: RetryOperations,
+}
+
+impl RetryPolicy {
+    /// Every error is returned right away
+    pub const NONE: RetryPolicy = RetryPolicy { retries: 0, backoff_us: 0, operations: RetryOperations::empty() };
+
+    /// Three retries starting at 1 ms for all kinds of transactions, covers a gauge busy with a flash write
+    pub const DEFAULT: RetryPolicy = RetryPolicy { retries: 3, backoff_us: 1000, operations: RetryOperations::all() };
+
+    /// Errors of the bus or the gauge being busy, anything else will not go away by trying again
+    fn is_transient(error: ErrorKind) -> bool {
+        return matches!(error, ErrorKind::NoAcknowledge(_) | ErrorKind::ArbitrationLoss | ErrorKind::Bus);
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        return RetryPolicy::NONE;
+    }
+}
+
+/// Counters of all transactions since the driver was created or the statistics were reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct RetryStatistics {
+    pub transactions: u32,
+    pub retries: u32,   // repeated attempts
+    pub recovered: u32, // transactions that succeeded after at least one retry
+    pub failed: u32,    // transactions that returned an error
+}
+
+/// Books the outcome of an attempt, returns the backoff in µs if the transaction should be tried again
+pub(crate) fn next_attempt(
+    policy: &RetryPolicy,
+    statistics: &mut RetryStatistics,
+    operation: RetryOperations,
+    result: Result<(), ErrorKind>,
+    attempt: &mut u8,
+) -> Option<u32> {
+    match result {
+        Ok(()) => {
+            statistics.transactions = statistics.transactions.wrapping_add(1);
+            if *attempt > 0 {
+                statistics.recovered = statistics.recovered.wrapping_add(1);
+            }
+            return None;
+        }
+        Err(error) => {
+            if policy.operations.contains(operation) && *attempt < policy.retries && RetryPolicy::is_transient(error) {
+                let backoff = policy.backoff_us.saturating_mul(1u32 << (*attempt).min(16));
+                *attempt += 1;
+                statistics.retries = statistics.retries.wrapping_add(1);
+                return Some(backoff);
+            }
+            statistics.transactions = statistics.transactions.wrapping_add(1);
+            statistics.failed = statistics.failed.wrapping_add(1);
+            return None;
+        }
+    }
+}
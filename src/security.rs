@@ -0,0 +1,34 @@
+//! Security modes of the gauge and the keys to change between them.
+use crate::registers::*;
+
+/// Security mode as reported by the SS and FAS bits of CONTROL_STATUS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityMode {
+    Sealed,
+    Unsealed,
+    FullAccess,
+}
+
+/// Unseal or full access key, the two words written to Control() in the order they are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityKey {
+    pub first: u16,
+    pub second: u16,
+}
+
+impl SecurityKey {
+    /// Factory default unseal key 0x36720414
+    pub const DEFAULT_UNSEAL: SecurityKey = SecurityKey::new(0x0414, 0x3672);
+    /// Factory default full access key 0xffffffff
+    pub const DEFAULT_FULL_ACCESS: SecurityKey = SecurityKey::new(0xffff, 0xffff);
+
+    pub const fn new(first: u16, second: u16) -> SecurityKey {
+        return SecurityKey { first, second };
+    }
+
+    pub(crate) fn commands(&self) -> [[u8; 3]; 2] {
+        let first = self.first.to_le_bytes();
+        let second = self.second.to_le_bytes();
+        return [[CONTROL, first[0], first[1]], [CONTROL, second[0], second[1]]];
+    }
+}
@@ -0,0 +1,491 @@
+//! Simulated bq34z100-G1 for tests without a physical gauge.
+//!
+//! [`Bq34z100g1Simulator`] implements [`embedded_hal::i2c::I2c`] (and the async version with the async feature)
+//! on register level, so it can be handed to [`Bq34z100g1Driver::new`](crate::Bq34z100g1Driver::new) in place of a real bus.
+//! It models:
+//! * the standard command space 0x00-0x7f, values can be set with [`Bq34z100g1Simulator::set_standard_command`]
+//! * the Control() subcommand protocol, the response is latched and read back from 0x00/0x01
+//! * sealed, unsealed and full access mode with configurable keys, a reset returns to the mode the gauge booted in
+//! * the data flash block interface (0x3e/0x3f/0x40-0x5f/0x60/0x61), a block is only committed if the checksum
+//!   written to 0x60 matches the block content
+//! * reset semantics, DesignCapacity() and PackConfiguration() are reloaded from data flash, the calibration state is cleared
+//!
+//! Timing is not modelled, calibration finishes after its status bits were observed once.
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+use crate::registers::*;
+
+const SUBCLASS_COUNT: usize = 256;
+/// The largest subclass of the bq34z100-G1 is below 4 blocks
+const SUBCLASS_SIZE: usize = 4 * FLASH_BLOCK_SIZE;
+
+/// Security mode of the simulated gauge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatedSecurity {
+    Sealed,
+    Unsealed,
+    FullAccess,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulatorError {
+    /// Transaction to another address than the one of the simulated gauge
+    NoAcknowledge { address: u8 },
+}
+
+impl embedded_hal::i2c::Error for SimulatorError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            SimulatorError::NoAcknowledge { .. } => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum KeyProgress {
+    None,
+    UnsealFirstWord,
+    FullAccessFirstWord,
+}
+
+pub struct Bq34z100g1Simulator {
+    address: u8,
+    standard: [u8; 0x80],
+    pointer: u8,
+
+    control_lsb: u8,
+    control_response: u16,
+    control_status: u16,
+    calibration_enabled: bool,
+    calibration_observed: bool,
+    reset_count: u16,
+
+    boot_security: SimulatedSecurity,
+    security: SimulatedSecurity,
+    unseal_key: [u16; 2],
+    full_access_key: [u16; 2],
+    key_progress: KeyProgress,
+    fw_version: u16,
+    cc_offset: i16,
+    hibernate: bool,
+    nacks_pending: u16,
+    glitches_pending: u16,
+
+    block_data_control: u8,
+    data_flash_class: u8,
+    data_flash_block: u8,
+    block: [u8; FLASH_BLOCK_SIZE],
+    data_flash: [[u8; SUBCLASS_SIZE]; SUBCLASS_COUNT],
+    reset_hook: Option<fn(&mut Bq34z100g1Simulator)>,
+}
+
+impl Default for Bq34z100g1Simulator {
+    fn default() -> Self {
+        return Bq34z100g1Simulator::new();
+    }
+}
+
+impl Bq34z100g1Simulator {
+    /// A sealed gauge at the default address, with the default keys and a plausible single cell configuration
+    pub fn new() -> Self {
+        let mut simulator = Bq34z100g1Simulator {
+            address: BQ34Z100_G1_ADDRESS,
+            standard: [0; 0x80],
+            pointer: 0,
+            control_lsb: 0,
+            control_response: 0,
+            control_status: 0,
+            calibration_enabled: false,
+            calibration_observed: false,
+            reset_count: 0,
+            boot_security: SimulatedSecurity::Sealed,
+            security: SimulatedSecurity::Sealed,
+            unseal_key: [0x0414, 0x3672],
+            full_access_key: [0xffff, 0xffff],
+            key_progress: KeyProgress::None,
+            fw_version: 0x0017,
+            cc_offset: -12,
+            hibernate: false,
+            nacks_pending: 0,
+            glitches_pending: 0,
+            block_data_control: 0,
+            data_flash_class: 0,
+            data_flash_block: 0,
+            block: [0; FLASH_BLOCK_SIZE],
+            data_flash: [[0; SUBCLASS_SIZE]; SUBCLASS_COUNT],
+            reset_hook: None,
+        };
+
+        simulator.set_data_flash(SUBCLASS_DATA, 11, &1000_u16.to_be_bytes()); // Design Capacity
+        simulator.set_data_flash(SUBCLASS_DATA, 13, &3700_u16.to_be_bytes()); // Design Energy
+        simulator.set_data_flash(SUBCLASS_DATA, 17, &4200_u16.to_be_bytes()); // Cell Charge Voltage T1-T2
+        simulator.set_data_flash(SUBCLASS_DATA, 19, &4200_u16.to_be_bytes()); // Cell Charge Voltage T2-T3
+        simulator.set_data_flash(SUBCLASS_DATA, 21, &4100_u16.to_be_bytes()); // Cell Charge Voltage T3-T4
+        simulator.set_data_flash(SUBCLASS_DATA, 30, &[1]); // Energy Scale
+        simulator.set_data_flash(SUBCLASS_REGISTERS, 0, &0x0161_u16.to_be_bytes()); // Pack Configuration
+        simulator.set_data_flash(SUBCLASS_REGISTERS, 7, &[1]); // Number of Series Cell
+        simulator.set_data_flash(SUBCLASS_CALIBRATION_DATA, 0, &[0x7f, 0x74, 0x1f, 0x21]); // CC Gain, 10 mOhm
+        simulator.set_data_flash(SUBCLASS_CALIBRATION_DATA, 4, &[0x94, 0x0a, 0x9c, 0x08]); // CC Delta, 10 mOhm
+        simulator.set_data_flash(SUBCLASS_CALIBRATION_DATA, 14, &5000_u16.to_be_bytes()); // Voltage Divider
+
+        simulator.set_standard_command(STATE_OF_CHARGE, 50);
+        simulator.set_standard_command(MAX_ERROR, 1);
+        simulator.set_standard_command(REMAINING_CAPACITY, 500);
+        simulator.set_standard_command(FULL_CHARGE_CAPACITY, 1000);
+        simulator.set_standard_command(VOLTAGE, 3700);
+        simulator.set_standard_command(TEMPERATURE, 2982);
+        simulator.set_standard_command(AVERAGE_TIME_TO_EMPTY, 0xffff);
+        simulator.set_standard_command(AVERAGE_TIME_TO_FULL, 0xffff);
+        simulator.set_standard_command(AVAILABLE_ENERGY, 185);
+        simulator.set_standard_command(SERIAL_NUMBER, 0x0001);
+        simulator.set_standard_command(INTERNAL_TEMPERATURE, 2982);
+        simulator.set_standard_command(STATE_OF_HEALTH, 100);
+        simulator.set_standard_command(CHARGE_VOLTAGE, 4200);
+        simulator.set_standard_command(CHARGE_CURRENT, 500);
+        simulator.set_standard_command(TRUE_FCC, 1000);
+        simulator.load_from_data_flash();
+        return simulator;
+    }
+
+    /// Responds on another address instead of the default one
+    pub fn with_address(mut self, address: u8) -> Self {
+        self.address = address;
+        return self;
+    }
+
+    /// Boots the gauge in the given security mode, this is also the mode a reset returns to
+    pub fn with_security(mut self, security: SimulatedSecurity) -> Self {
+        self.boot_security = security;
+        self.security = security;
+        return self;
+    }
+
+    /// Replaces the unseal key, given as the two Control() words in the order they have to be sent
+    pub fn with_unseal_key(mut self, key: [u16; 2]) -> Self {
+        self.unseal_key = key;
+        return self;
+    }
+
+    /// Replaces the full access key, given as the two Control() words in the order they have to be sent
+    pub fn with_full_access_key(mut self, key: [u16; 2]) -> Self {
+        self.full_access_key = key;
+        return self;
+    }
+
+    /// Reports another FW_VERSION than the 0x0017 of a bq34z100-G1
+    pub fn with_fw_version(mut self, fw_version: u16) -> Self {
+        self.fw_version = fw_version;
+        return self;
+    }
+
+    /// Reports another coulomb counter offset on OFFSET_CAL than the default -12
+    pub fn with_cc_offset(mut self, cc_offset: i16) -> Self {
+        self.cc_offset = cc_offset;
+        return self;
+    }
+
+    /// Runs the hook after every reset, eg. to change the data flash like a gauge updating its learned state
+    pub fn with_reset_hook(mut self, hook: fn(&mut Bq34z100g1Simulator)) -> Self {
+        self.reset_hook = Some(hook);
+        return self;
+    }
+
+    /// Does not acknowledge the next transactions, like a gauge busy with a data flash write
+    pub fn nack_next(&mut self, count: u16) {
+        self.nacks_pending = count;
+    }
+
+    /// Flips the lowest bit of the first byte of the next block data reads, like noise on the bus
+    pub fn glitch_next_block_reads(&mut self, count: u16) {
+        self.glitches_pending = count;
+    }
+
+    pub fn security(&self) -> SimulatedSecurity {
+        return self.security;
+    }
+
+    /// Whether SET_HIBERNATE was received and not cleared since
+    pub fn hibernate(&self) -> bool {
+        return self.hibernate;
+    }
+
+    /// Number of resets executed since creation
+    pub fn reset_count(&self) -> u16 {
+        return self.reset_count;
+    }
+
+    /// Sets a standard command, one byte commands (StateOfCharge(), MaxError(), ..) only use the low byte.
+    pub fn set_standard_command(&mut self, command: u8, value: u16) {
+        let [low, high] = value.to_le_bytes();
+        self.standard[command as usize & 0x7f] = low;
+        if !matches!(command, STATE_OF_CHARGE | MAX_ERROR | GRID_NUMBER | LEARNED_STATUS) {
+            self.standard[(command as usize + 1) & 0x7f] = high;
+        }
+    }
+
+    pub fn standard_command(&self, command: u8) -> u16 {
+        return u16::from_le_bytes([
+            self.standard[command as usize & 0x7f],
+            self.standard[(command as usize + 1) & 0x7f],
+        ]);
+    }
+
+    /// Data flash content of a subclass, offset is relative to the start of the subclass
+    pub fn data_flash(&self, sub_class: u8, offset: u8, len: usize) -> &[u8] {
+        let offset = offset as usize;
+        return &self.data_flash[sub_class as usize][offset..offset + len];
+    }
+
+    /// Directly patches the data flash, the standard commands mirroring data flash are only updated on reset
+    pub fn set_data_flash(&mut self, sub_class: u8, offset: u8, data: &[u8]) {
+        let offset = offset as usize;
+        self.data_flash[sub_class as usize][offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    fn sealed(&self) -> bool {
+        return self.security == SimulatedSecurity::Sealed;
+    }
+
+    fn load_from_data_flash(&mut self) {
+        let design_capacity = self.data_flash(SUBCLASS_DATA, 11, 2);
+        let design_capacity = u16::from_be_bytes([design_capacity[0], design_capacity[1]]);
+        self.set_standard_command(DESIGN_CAPACITY, design_capacity);
+        let pack_configuration = self.data_flash(SUBCLASS_REGISTERS, 0, 2);
+        let pack_configuration = u16::from_be_bytes([pack_configuration[0], pack_configuration[1]]);
+        self.set_standard_command(PACK_CONFIGURATION, pack_configuration);
+    }
+
+    fn reset(&mut self) {
+        self.reset_count = self.reset_count.wrapping_add(1);
+        self.security = self.boot_security;
+        self.key_progress = KeyProgress::None;
+        self.control_status = 0;
+        self.calibration_enabled = false;
+        self.calibration_observed = false;
+        self.hibernate = false;
+        self.block_data_control = 0;
+        self.data_flash_class = 0;
+        self.data_flash_block = 0;
+        self.block = [0; FLASH_BLOCK_SIZE];
+        self.load_from_data_flash();
+        if let Some(hook) = self.reset_hook {
+            hook(self);
+        }
+    }
+
+    fn load_block(&mut self) {
+        let start = self.data_flash_block as usize * FLASH_BLOCK_SIZE;
+        if start + FLASH_BLOCK_SIZE > SUBCLASS_SIZE {
+            self.block = [0; FLASH_BLOCK_SIZE];
+            return;
+        }
+        self.block
+            .copy_from_slice(&self.data_flash[self.data_flash_class as usize][start..start + FLASH_BLOCK_SIZE]);
+    }
+
+    fn commit_block(&mut self) {
+        let start = self.data_flash_block as usize * FLASH_BLOCK_SIZE;
+        if start + FLASH_BLOCK_SIZE > SUBCLASS_SIZE {
+            return;
+        }
+        self.data_flash[self.data_flash_class as usize][start..start + FLASH_BLOCK_SIZE].copy_from_slice(&self.block);
+    }
+
+    fn data_flash_accessible(&self) -> bool {
+        return !self.sealed() && self.block_data_control == 0;
+    }
+
+    fn status_word(&mut self) -> u16 {
+        let mut status = self.control_status;
+        match self.security {
+            SimulatedSecurity::Sealed => status |= CONTROL_STATUS_FAS | CONTROL_STATUS_SS,
+            SimulatedSecurity::Unsealed => status |= CONTROL_STATUS_FAS,
+            SimulatedSecurity::FullAccess => {}
+        }
+        // a running offset calibration is reported once and then finishes
+        if self.control_status & (CONTROL_STATUS_CCA | CONTROL_STATUS_BCA) > 0 {
+            if self.calibration_observed {
+                self.control_status &= !(CONTROL_STATUS_CCA | CONTROL_STATUS_BCA);
+                self.calibration_observed = false;
+            } else {
+                self.calibration_observed = true;
+            }
+        }
+        return status;
+    }
+
+    fn check_keys(&mut self, word: u16) -> bool {
+        let progress = self.key_progress;
+        self.key_progress = KeyProgress::None;
+        match self.security {
+            SimulatedSecurity::Sealed => {
+                if progress == KeyProgress::UnsealFirstWord && word == self.unseal_key[1] {
+                    self.security = SimulatedSecurity::Unsealed;
+                    return true;
+                }
+                if word == self.unseal_key[0] {
+                    self.key_progress = KeyProgress::UnsealFirstWord;
+                    return true;
+                }
+            }
+            SimulatedSecurity::Unsealed => {
+                if progress == KeyProgress::FullAccessFirstWord && word == self.full_access_key[1] {
+                    self.security = SimulatedSecurity::FullAccess;
+                    return true;
+                }
+                if word == self.full_access_key[0] {
+                    self.key_progress = KeyProgress::FullAccessFirstWord;
+                    return true;
+                }
+            }
+            SimulatedSecurity::FullAccess => {}
+        }
+        return false;
+    }
+
+    fn execute_control(&mut self, word: u16) {
+        if self.check_keys(word) {
+            return;
+        }
+        let [subcommand, msb] = word.to_le_bytes();
+        if msb != 0 {
+            return;
+        }
+        let allowed_when_sealed = matches!(
+            subcommand,
+            CONTROL_STATUS
+                | DEVICE_TYPE
+                | FW_VERSION
+                | HW_VERSION
+                | RESET_DATA
+                | PREV_MACWRITE
+                | CHEM_ID
+                | DF_VERSION
+                | SET_FULLSLEEP
+                | SET_HIBERNATE
+                | CLEAR_HIBERNATE
+                | STATIC_CHEM_CHKSUM
+                | STATIC_DF_CHKSUM
+                | INSTRUCTION_FLASH_CHKSUM
+        );
+        if self.sealed() && !allowed_when_sealed {
+            return;
+        }
+        match subcommand {
+            CONTROL_STATUS => self.control_response = self.status_word(),
+            DEVICE_TYPE => self.control_response = 0x0100,
+            FW_VERSION => self.control_response = self.fw_version,
+            HW_VERSION => self.control_response = 0x0060,
+            RESET_DATA => self.control_response = self.reset_count,
+            PREV_MACWRITE => self.control_response = 0x0000,
+            CHEM_ID => self.control_response = 0x0100,
+            BOARD_OFFSET if self.control_status & CONTROL_STATUS_CALEN > 0 => {
+                self.control_status |= CONTROL_STATUS_CCA | CONTROL_STATUS_BCA;
+            }
+            CC_OFFSET if self.control_status & CONTROL_STATUS_CALEN > 0 => {
+                self.control_status |= CONTROL_STATUS_CCA;
+            }
+            DF_VERSION => self.control_response = 0x0001,
+            SET_FULLSLEEP => self.control_status |= CONTROL_STATUS_FULLSLEEP,
+            SET_HIBERNATE => self.hibernate = true,
+            CLEAR_HIBERNATE => self.hibernate = false,
+            STATIC_CHEM_CHKSUM | STATIC_DF_CHKSUM | INSTRUCTION_FLASH_CHKSUM => self.control_response = 0x0000,
+            SEALED => {
+                self.security = SimulatedSecurity::Sealed;
+                self.boot_security = SimulatedSecurity::Sealed;
+            }
+            IT_ENABLE => self.control_status |= CONTROL_STATUS_QEN,
+            CAL_ENABLE => self.calibration_enabled = !self.calibration_enabled,
+            RESET => self.reset(),
+            EXIT_CAL => self.control_status &= !CONTROL_STATUS_CALEN,
+            ENTER_CAL if self.calibration_enabled => self.control_status |= CONTROL_STATUS_CALEN,
+            OFFSET_CAL if self.control_status & CONTROL_STATUS_CALEN > 0 => {
+                self.control_response = self.cc_offset as u16;
+            }
+            // CC_OFFSET_SAVE and unknown subcommands have no visible effect, ENTER_ROM is not modelled
+            _ => {}
+        }
+    }
+
+    fn write_register(&mut self, register: u8, value: u8) {
+        match register {
+            CONTROL => self.control_lsb = value,
+            0x01 => self.execute_control(u16::from_le_bytes([self.control_lsb, value])),
+            DATA_FLASH_CLASS if self.data_flash_accessible() => self.data_flash_class = value,
+            DATA_FLASH_BLOCK if self.data_flash_accessible() => {
+                self.data_flash_block = value;
+                self.load_block();
+            }
+            BLOCK_DATA..=0x5f if self.data_flash_accessible() => {
+                self.block[(register - BLOCK_DATA) as usize] = value;
+            }
+            // the block is only committed if the checksum matches
+            BLOCK_DATA_CHECKSUM if self.data_flash_accessible() && value == flash_block_checksum(&self.block) => {
+                self.commit_block();
+            }
+            BLOCK_DATA_CONTROL => self.block_data_control = value,
+            _ => {}
+        }
+    }
+
+    fn read_register(&self, register: u8) -> u8 {
+        match register {
+            CONTROL => return self.control_response.to_le_bytes()[0],
+            0x01 => return self.control_response.to_le_bytes()[1],
+            DATA_FLASH_CLASS => return self.data_flash_class,
+            DATA_FLASH_BLOCK => return self.data_flash_block,
+            BLOCK_DATA..=0x5f => return self.block[(register - BLOCK_DATA) as usize],
+            BLOCK_DATA_CHECKSUM => return flash_block_checksum(&self.block),
+            BLOCK_DATA_CONTROL => return self.block_data_control,
+            _ => return self.standard[register as usize & 0x7f],
+        }
+    }
+}
+
+impl ErrorType for Bq34z100g1Simulator {
+    type Error = SimulatorError;
+}
+
+impl I2c for Bq34z100g1Simulator {
+    fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        if address != self.address {
+            return Err(SimulatorError::NoAcknowledge { address });
+        }
+        if self.nacks_pending > 0 {
+            self.nacks_pending -= 1;
+            return Err(SimulatorError::NoAcknowledge { address });
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    if let Some((register, values)) = bytes.split_first() {
+                        self.pointer = *register;
+                        for value in values {
+                            self.write_register(self.pointer, *value);
+                            self.pointer = self.pointer.wrapping_add(1);
+                        }
+                    }
+                }
+                Operation::Read(buffer) => {
+                    let glitch = self.pointer == BLOCK_DATA && self.glitches_pending > 0;
+                    for value in buffer.iter_mut() {
+                        *value = self.read_register(self.pointer);
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                    if glitch && !buffer.is_empty() {
+                        self.glitches_pending -= 1;
+                        buffer[0] ^= 0x01;
+                    }
+                }
+            }
+        }
+        return Ok(());
+    }
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::i2c::I2c for Bq34z100g1Simulator {
+    async fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), Self::Error> {
+        return I2c::transaction(self, address, operations);
+    }
+}
@@ -0,0 +1,256 @@
+//! Reading all standard commands at once.
+//!
+//! [`snapshot`](crate::GaugeStatus::snapshot) reads the selected [`SnapshotGroups`] with at most one incremental burst
+//! read per command range (0x02-0x3d and 0x62-0x75), so all values come from the same gauge update cycle and the bus
+//! only sees two transactions instead of about 30.
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::registers::*;
+use crate::{Flags, FlagsB};
+
+/// Selection of the standard commands included in a [`GaugeSnapshot`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotGroups(u8);
+
+impl SnapshotGroups {
+    /// StateOfCharge(), MaxError(), RemainingCapacity(), FullChargeCapacity(), AvailableEnergy(), PassedCharge()
+    pub const CHARGE: SnapshotGroups = SnapshotGroups(1 << 0);
+    /// Voltage(), Current(), AverageCurrent(), AveragePower(), Temperature(), InternalTemperature()
+    pub const MEASUREMENT: SnapshotGroups = SnapshotGroups(1 << 1);
+    /// Flags(), FlagsB()
+    pub const STATUS: SnapshotGroups = SnapshotGroups(1 << 2);
+    /// AverageTimeToEmpty(), AverageTimeToFull(), DOD0Time()
+    pub const TIME: SnapshotGroups = SnapshotGroups(1 << 3);
+    /// CycleCount(), StateOfHealth(), ChargeVoltage(), ChargeCurrent()
+    pub const HEALTH: SnapshotGroups = SnapshotGroups(1 << 4);
+    /// SerialNumber(), PackConfiguration(), DesignCapacity()
+    pub const CONFIGURATION: SnapshotGroups = SnapshotGroups(1 << 5);
+    /// The Impedance Track learning commands from 0x62 to 0x75
+    pub const LEARNING: SnapshotGroups = SnapshotGroups(1 << 6);
+
+    pub const NONE: SnapshotGroups = SnapshotGroups(0);
+    pub const ALL: SnapshotGroups = SnapshotGroups(0x7f);
+
+    pub fn contains(&self, other: SnapshotGroups) -> bool {
+        return self.0 & other.0 == other.0;
+    }
+}
+
+impl BitOr for SnapshotGroups {
+    type Output = SnapshotGroups;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        return SnapshotGroups(self.0 | rhs.0);
+    }
+}
+
+impl BitOrAssign for SnapshotGroups {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChargeSnapshot {
+    pub state_of_charge: u8,           // 0 to 100%
+    pub state_of_charge_max_error: u8, // 1 to 100%
+    pub remaining_capacity: u16,       // mAh
+    pub full_charge_capacity: u16,     // mAh
+    pub available_energy: u16,         // 10 mWh
+    pub passed_charge: i16,            // mAh
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeasurementSnapshot {
+    pub voltage: u16,              // mV
+    pub current: i16,              // mA
+    pub average_current: i16,      // mA
+    pub average_power: i16,        // 10 mW
+    pub temperature: u16,          // Unit of x10 K
+    pub internal_temperature: u16, // Unit of x10 K
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusSnapshot {
+    pub flags: Flags,
+    pub flags_b: FlagsB,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSnapshot {
+    pub average_time_to_empty: u16, // Minutes
+    pub average_time_to_full: u16,  // Minutes
+    pub do_d0_time: u16,            // Minutes
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthSnapshot {
+    pub cycle_count: u16,     // Counts
+    pub state_of_health: u16, // 0 to 100%
+    pub charge_voltage: u16,  // mV
+    pub charge_current: u16,  // mA
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationSnapshot {
+    pub serial_number: u16,
+    pub pack_configuration: u16,
+    pub design_capacity: u16, // mAh
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LearningSnapshot {
+    pub grid_number: u8,
+    pub learned_status: u8,
+    pub dod_at_eoc: u16,
+    pub q_start: u16,  // mAh
+    pub true_rc: u16,  // mAh
+    pub true_fcc: u16, // mAh
+    pub state_time: u16, // s
+    pub q_max_passed_q: u16, // mAh
+    pub dod_0: u16,
+    pub q_max_dod_0: u16,
+    pub q_max_time: u16,
+}
+
+/// Standard commands read in one go, groups that were not selected are None
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GaugeSnapshot {
+    pub charge: Option<ChargeSnapshot>,
+    pub measurement: Option<MeasurementSnapshot>,
+    pub status: Option<StatusSnapshot>,
+    pub time: Option<TimeSnapshot>,
+    pub health: Option<HealthSnapshot>,
+    pub configuration: Option<ConfigurationSnapshot>,
+    pub learning: Option<LearningSnapshot>,
+}
+
+/// Image of the standard command space, a burst read is placed at the offset of its first command
+pub(crate) type SnapshotImage = [u8; 0x76];
+
+/// First command and length of every standard command of a group
+fn group_commands(group: SnapshotGroups) -> &'static [(u8, u8)] {
+    match group {
+        SnapshotGroups::CHARGE => &[
+            (STATE_OF_CHARGE, 1),
+            (MAX_ERROR, 1),
+            (REMAINING_CAPACITY, 2),
+            (FULL_CHARGE_CAPACITY, 2),
+            (AVAILABLE_ENERGY, 2),
+            (PASSED_CHARGE, 2),
+        ],
+        SnapshotGroups::MEASUREMENT => &[
+            (VOLTAGE, 2),
+            (CURRENT, 2),
+            (AVERAGE_CURRENT, 2),
+            (AVERAGE_POWER, 2),
+            (TEMPERATURE, 2),
+            (INTERNAL_TEMPERATURE, 2),
+        ],
+        SnapshotGroups::STATUS => &[(FLAGS, 2), (FLAGS_B, 2)],
+        SnapshotGroups::TIME => &[(AVERAGE_TIME_TO_EMPTY, 2), (AVERAGE_TIME_TO_FULL, 2), (DOD0_TIME, 2)],
+        SnapshotGroups::HEALTH => &[(CYCLE_COUNT, 2), (STATE_OF_HEALTH, 2), (CHARGE_VOLTAGE, 2), (CHARGE_CURRENT, 2)],
+        SnapshotGroups::CONFIGURATION => &[(SERIAL_NUMBER, 2), (PACK_CONFIGURATION, 2), (DESIGN_CAPACITY, 2)],
+        SnapshotGroups::LEARNING => &[
+            (GRID_NUMBER, 1),
+            (LEARNED_STATUS, 1),
+            (DOD_AT_EOC, 2),
+            (Q_START, 2),
+            (TRUE_RC, 2),
+            (TRUE_FCC, 2),
+            (STATE_TIME, 2),
+            (Q_MAX_PASSED_Q, 2),
+            (DOD_0, 2),
+            (Q_MAX_DOD_0, 2),
+            (Q_MAX_TIME, 2),
+        ],
+        _ => &[],
+    }
+}
+
+const GROUPS: [SnapshotGroups; 7] = [
+    SnapshotGroups::CHARGE,
+    SnapshotGroups::MEASUREMENT,
+    SnapshotGroups::STATUS,
+    SnapshotGroups::TIME,
+    SnapshotGroups::HEALTH,
+    SnapshotGroups::CONFIGURATION,
+    SnapshotGroups::LEARNING,
+];
+
+/// The burst reads (first command, length) needed for the groups, at most one below and one above the data flash interface
+pub(crate) fn burst_reads(groups: SnapshotGroups) -> [Option<(u8, u8)>; 2] {
+    let mut bursts: [Option<(u8, u8)>; 2] = [None; 2];
+    for group in GROUPS.iter().filter(|group| groups.contains(**group)) {
+        for (command, len) in group_commands(*group) {
+            let burst = &mut bursts[if *command < DATA_FLASH_CLASS { 0 } else { 1 }];
+            let end = command + len;
+            *burst = match *burst {
+                None => Some((*command, end)),
+                Some((start, burst_end)) => Some((start.min(*command), burst_end.max(end))),
+            };
+        }
+    }
+    return bursts.map(|burst| burst.map(|(start, end)| (start, end - start)));
+}
+
+fn u16_at(image: &SnapshotImage, command: u8) -> u16 {
+    return u16::from_le_bytes([image[command as usize], image[command as usize + 1]]);
+}
+
+impl GaugeSnapshot {
+    pub(crate) fn decode(groups: SnapshotGroups, image: &SnapshotImage) -> GaugeSnapshot {
+        let read = |group: SnapshotGroups| groups.contains(group);
+        return GaugeSnapshot {
+            charge: read(SnapshotGroups::CHARGE).then(|| ChargeSnapshot {
+                state_of_charge: image[STATE_OF_CHARGE as usize],
+                state_of_charge_max_error: image[MAX_ERROR as usize],
+                remaining_capacity: u16_at(image, REMAINING_CAPACITY),
+                full_charge_capacity: u16_at(image, FULL_CHARGE_CAPACITY),
+                available_energy: u16_at(image, AVAILABLE_ENERGY),
+                passed_charge: u16_at(image, PASSED_CHARGE) as i16,
+            }),
+            measurement: read(SnapshotGroups::MEASUREMENT).then(|| MeasurementSnapshot {
+                voltage: u16_at(image, VOLTAGE),
+                current: u16_at(image, CURRENT) as i16,
+                average_current: u16_at(image, AVERAGE_CURRENT) as i16,
+                average_power: u16_at(image, AVERAGE_POWER) as i16,
+                temperature: u16_at(image, TEMPERATURE),
+                internal_temperature: u16_at(image, INTERNAL_TEMPERATURE),
+            }),
+            status: read(SnapshotGroups::STATUS).then(|| StatusSnapshot {
+                flags: Flags::from(u16_at(image, FLAGS)),
+                flags_b: FlagsB::from(u16_at(image, FLAGS_B)),
+            }),
+            time: read(SnapshotGroups::TIME).then(|| TimeSnapshot {
+                average_time_to_empty: u16_at(image, AVERAGE_TIME_TO_EMPTY),
+                average_time_to_full: u16_at(image, AVERAGE_TIME_TO_FULL),
+                do_d0_time: u16_at(image, DOD0_TIME),
+            }),
+            health: read(SnapshotGroups::HEALTH).then(|| HealthSnapshot {
+                cycle_count: u16_at(image, CYCLE_COUNT),
+                state_of_health: u16_at(image, STATE_OF_HEALTH),
+                charge_voltage: u16_at(image, CHARGE_VOLTAGE),
+                charge_current: u16_at(image, CHARGE_CURRENT),
+            }),
+            configuration: read(SnapshotGroups::CONFIGURATION).then(|| ConfigurationSnapshot {
+                serial_number: u16_at(image, SERIAL_NUMBER),
+                pack_configuration: u16_at(image, PACK_CONFIGURATION),
+                design_capacity: u16_at(image, DESIGN_CAPACITY),
+            }),
+            learning: read(SnapshotGroups::LEARNING).then(|| LearningSnapshot {
+                grid_number: image[GRID_NUMBER as usize],
+                learned_status: image[LEARNED_STATUS as usize],
+                dod_at_eoc: u16_at(image, DOD_AT_EOC),
+                q_start: u16_at(image, Q_START),
+                true_rc: u16_at(image, TRUE_RC),
+                true_fcc: u16_at(image, TRUE_FCC),
+                state_time: u16_at(image, STATE_TIME),
+                q_max_passed_q: u16_at(image, Q_MAX_PASSED_Q),
+                dod_0: u16_at(image, DOD_0),
+                q_max_dod_0: u16_at(image, Q_MAX_DOD_0),
+                q_max_time: u16_at(image, Q_MAX_TIME),
+            }),
+        };
+    }
+}
@@ -0,0 +1,277 @@
+//! Readings of the standard commands and the status subcommands.
+//!
+//! [`GaugeStatus`] only needs the register access of [`Bq34z100g1`], so it is implemented for every gauge implementing
+//! it. Applications only reading the gauge can depend on this trait alone and use a fake implementation in tests.
+//! [`GaugeStatusAsync`](crate::asynch::GaugeStatusAsync) is declared from the same macro for the async gauges.
+use crate::registers::*;
+use crate::snapshot::{self, SnapshotImage};
+use crate::{
+    Bq34Z100Error, Bq34z100g1, ControlCommand, ControlStatus, Flags, FlagsB, GaugeSnapshot, PackConfiguration, SnapshotGroups,
+};
+
+/// Declares the status trait with its implementation for every gauge, blocking with [] [] and async with
+/// [async] [.await]
+macro_rules! gauge_status {
+    ([$($async:tt)?] [$($await:tt)*] $Status:ident for $Gauge:ident) => {
+        #[allow(async_fn_in_trait)]
+        pub trait $Status<E> {
+            /** Reads the selected groups of standard commands with at most two burst reads */
+            $($async)? fn snapshot(&mut self, groups: SnapshotGroups) -> Result<GaugeSnapshot, Bq34Z100Error<E>>;
+            $($async)? fn control_status(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn control_status_decoded(&mut self) -> Result<ControlStatus, Bq34Z100Error<E>>;
+            $($async)? fn device_type(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn fw_version(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn hw_version(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn reset_data(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn prev_macwrite(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn chem_id(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn df_version(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn static_chem_chksum(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn state_of_charge(&mut self) -> Result<u8, Bq34Z100Error<E>>; // 0 to 100%
+            $($async)? fn state_of_charge_max_error(&mut self) -> Result<u8, Bq34Z100Error<E>>; // 1 to 100%
+            $($async)? fn remaining_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn full_charge_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn voltage(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mV
+            $($async)? fn average_current(&mut self) -> Result<i16, Bq34Z100Error<E>>; // mA
+            $($async)? fn temperature(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Unit of x10 K
+            $($async)? fn flags(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn flags_b(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn current(&mut self) -> Result<i16, Bq34Z100Error<E>>; // mA
+            $($async)? fn average_time_to_empty(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Minutes
+            $($async)? fn average_time_to_full(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Minutes
+            $($async)? fn passed_charge(&mut self) -> Result<i16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn do_d0_time(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Minutes
+            $($async)? fn available_energy(&mut self) -> Result<u16, Bq34Z100Error<E>>; // 10 mWh
+            $($async)? fn average_power(&mut self) -> Result<i16, Bq34Z100Error<E>>; // 10 mW
+            $($async)? fn serial_number(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn internal_temperature(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Unit of x10 K
+            $($async)? fn cycle_count(&mut self) -> Result<u16, Bq34Z100Error<E>>; // Counts
+            $($async)? fn state_of_health(&mut self) -> Result<u16, Bq34Z100Error<E>>; // 0 to 100%
+            $($async)? fn charge_voltage(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mV
+            $($async)? fn charge_current(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mA
+            $($async)? fn pack_configuration(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn pack_configuration_decoded(&mut self) -> Result<PackConfiguration, Bq34Z100Error<E>>;
+            $($async)? fn design_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn grid_number(&mut self) -> Result<u8, Bq34Z100Error<E>>;
+            $($async)? fn learned_status(&mut self) -> Result<u8, Bq34Z100Error<E>>;
+            $($async)? fn dod_at_eoc(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn q_start(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn true_rc(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn true_fcc(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn state_time(&mut self) -> Result<u16, Bq34Z100Error<E>>; // s
+            $($async)? fn q_max_passed_q(&mut self) -> Result<u16, Bq34Z100Error<E>>; // mAh
+            $($async)? fn dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn q_max_dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn q_max_time(&mut self) -> Result<u16, Bq34Z100Error<E>>;
+            $($async)? fn get_flags_decoded(&mut self) -> Result<Flags, Bq34Z100Error<E>>;
+            $($async)? fn flags_b_decoded(&mut self) -> Result<FlagsB, Bq34Z100Error<E>>;
+        }
+
+        impl<G: $Gauge<E>, E> $Status<E> for G {
+            $($async)? fn snapshot(&mut self, groups: SnapshotGroups) -> Result<GaugeSnapshot, Bq34Z100Error<E>> {
+                let mut image: SnapshotImage = [0; 0x76];
+                for (start, len) in snapshot::burst_reads(groups).into_iter().flatten() {
+                    let start = start as usize;
+                    self.read_registers(start as u8, &mut image[start..start + len as usize])$($await)*?;
+                }
+                return Ok(GaugeSnapshot::decode(groups, &image));
+            }
+
+            $($async)? fn control_status(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::ControlStatus)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn control_status_decoded(&mut self) -> Result<ControlStatus, Bq34Z100Error<E>> {
+                return Ok(ControlStatus::from(self.control_status()$($await)*?));
+            }
+
+            $($async)? fn device_type(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::DeviceType)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn fw_version(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::FwVersion)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn hw_version(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::HwVersion)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn reset_data(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::ResetData)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn prev_macwrite(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::PrevMacwrite)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn chem_id(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::ChemId)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn df_version(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::DfVersion)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn static_chem_chksum(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return Ok(self.send_control(ControlCommand::StaticChemChecksum)$($await)*?.unwrap_or_default());
+            }
+
+            $($async)? fn state_of_charge(&mut self) -> Result<u8, Bq34Z100Error<E>> {
+                return self.read_1_register_as_u8(STATE_OF_CHARGE)$($await)*;
+            }
+
+            $($async)? fn state_of_charge_max_error(&mut self) -> Result<u8, Bq34Z100Error<E>> {
+                return self.read_1_register_as_u8(MAX_ERROR)$($await)*;
+            }
+
+            $($async)? fn remaining_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(REMAINING_CAPACITY)$($await)*;
+            }
+
+            $($async)? fn full_charge_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(FULL_CHARGE_CAPACITY)$($await)*;
+            }
+
+            $($async)? fn voltage(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(VOLTAGE)$($await)*;
+            }
+
+            $($async)? fn average_current(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+                return Ok(self.read_2_register_as_u16(AVERAGE_CURRENT)$($await)*? as i16);
+            }
+
+            $($async)? fn temperature(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(TEMPERATURE)$($await)*;
+            }
+
+            $($async)? fn flags(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(FLAGS)$($await)*;
+            }
+
+            $($async)? fn flags_b(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(FLAGS_B)$($await)*;
+            }
+
+            $($async)? fn current(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+                return Ok(self.read_2_register_as_u16(CURRENT)$($await)*? as i16);
+            }
+
+            $($async)? fn average_time_to_empty(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(AVERAGE_TIME_TO_EMPTY)$($await)*;
+            }
+
+            $($async)? fn average_time_to_full(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(AVERAGE_TIME_TO_FULL)$($await)*;
+            }
+
+            $($async)? fn passed_charge(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+                return Ok(self.read_2_register_as_u16(PASSED_CHARGE)$($await)*? as i16);
+            }
+
+            $($async)? fn do_d0_time(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(DOD0_TIME)$($await)*;
+            }
+
+            $($async)? fn available_energy(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(AVAILABLE_ENERGY)$($await)*;
+            }
+
+            $($async)? fn average_power(&mut self) -> Result<i16, Bq34Z100Error<E>> {
+                return Ok(self.read_2_register_as_u16(AVERAGE_POWER)$($await)*? as i16);
+            }
+
+            $($async)? fn serial_number(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(SERIAL_NUMBER)$($await)*;
+            }
+
+            $($async)? fn internal_temperature(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(INTERNAL_TEMPERATURE)$($await)*;
+            }
+
+            $($async)? fn cycle_count(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(CYCLE_COUNT)$($await)*;
+            }
+
+            $($async)? fn state_of_health(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(STATE_OF_HEALTH)$($await)*;
+            }
+
+            $($async)? fn charge_voltage(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(CHARGE_VOLTAGE)$($await)*;
+            }
+
+            $($async)? fn charge_current(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(CHARGE_CURRENT)$($await)*;
+            }
+
+            $($async)? fn pack_configuration(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(PACK_CONFIGURATION)$($await)*;
+            }
+
+            $($async)? fn pack_configuration_decoded(&mut self) -> Result<PackConfiguration, Bq34Z100Error<E>> {
+                return Ok(PackConfiguration::from(self.pack_configuration()$($await)*?));
+            }
+
+            $($async)? fn design_capacity(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(DESIGN_CAPACITY)$($await)*;
+            }
+
+            $($async)? fn grid_number(&mut self) -> Result<u8, Bq34Z100Error<E>> {
+                return self.read_1_register_as_u8(GRID_NUMBER)$($await)*;
+            }
+
+            $($async)? fn learned_status(&mut self) -> Result<u8, Bq34Z100Error<E>> {
+                return self.read_1_register_as_u8(LEARNED_STATUS)$($await)*;
+            }
+
+            $($async)? fn dod_at_eoc(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(DOD_AT_EOC)$($await)*;
+            }
+
+            $($async)? fn q_start(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(Q_START)$($await)*;
+            }
+
+            $($async)? fn true_rc(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(TRUE_RC)$($await)*;
+            }
+
+            $($async)? fn true_fcc(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(TRUE_FCC)$($await)*;
+            }
+
+            $($async)? fn state_time(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(STATE_TIME)$($await)*;
+            }
+
+            $($async)? fn q_max_passed_q(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(Q_MAX_PASSED_Q)$($await)*;
+            }
+
+            $($async)? fn dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(DOD_0)$($await)*;
+            }
+
+            $($async)? fn q_max_dod_0(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(Q_MAX_DOD_0)$($await)*;
+            }
+
+            $($async)? fn q_max_time(&mut self) -> Result<u16, Bq34Z100Error<E>> {
+                return self.read_2_register_as_u16(Q_MAX_TIME)$($await)*;
+            }
+
+            $($async)? fn get_flags_decoded(&mut self) -> Result<Flags, Bq34Z100Error<E>> {
+                return Ok(Flags::from(self.flags()$($await)*?));
+            }
+
+            $($async)? fn flags_b_decoded(&mut self) -> Result<FlagsB, Bq34Z100Error<E>> {
+                return Ok(FlagsB::from(self.flags_b()$($await)*?));
+            }
+        }
+    };
+}
+#[cfg(feature = "async")]
+pub(crate) use gauge_status;
+
+gauge_status!([] [] GaugeStatus for Bq34z100g1);
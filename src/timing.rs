@@ -0,0 +1,24 @@
+//! Minimum spacing of the I2C transactions.
+//!
+//! The gauge needs a bus free time between all packets addressed to it, and time to process a Control() subcommand
+//! before its result is read back. Without it fast MCUs intermittently read stale data or 0xffff.
+
+/// Idle times the driver waits with its `DelayNs` after every transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BusTiming {
+    pub bus_free_us: u32, // after every transaction, t(BUF) of the datasheet
+    pub control_us: u32,  // after writing a Control() subcommand or key word
+}
+
+impl BusTiming {
+    /// The waiting times of the datasheet, for a gauge directly on the bus
+    pub const DATASHEET: BusTiming = BusTiming { bus_free_us: 66, control_us: 2000 };
+    /// No waiting, for buses that already space the transactions (eg. a slow USB bridge) and for tests
+    pub const NONE: BusTiming = BusTiming { bus_free_us: 0, control_us: 0 };
+}
+
+impl Default for BusTiming {
+    fn default() -> Self {
+        return BusTiming::DATASHEET;
+    }
+}
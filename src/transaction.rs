@@ -0,0 +1,160 @@
+//! Batched data flash updates with a single reset.
+//!
+//! A [`DataFlashTransaction`] collects parameter edits, each one is checked against the kind and range of its
+//! parameter when it is added. `commit_transaction()` of the drivers then reads every touched block once, writes the
+//! edited bytes of all parameters in it followed by one checksum, resets the gauge once and reads every parameter back.
+//! The [`TransactionReport`] tells which parameters were stored. The capacity is a const generic, so no allocator is
+//! needed.
+//!
+//! ```
+//! use bq34z100::transaction::DataFlashTransaction;
+//! use bq34z100::{DataFlash, DataFlashValue};
+//!
+//! let mut transaction: DataFlashTransaction = DataFlashTransaction::new();
+//! transaction.set(&DataFlash::DESIGN_CAPACITY, DataFlashValue::Integer(3000)).unwrap();
+//! transaction.set(&DataFlash::DESIGN_ENERGY, DataFlashValue::Integer(11100)).unwrap();
+//! transaction.set(&DataFlash::NUMBER_OF_SERIES_CELLS, DataFlashValue::Integer(3)).unwrap();
+//! assert_eq!(transaction.len(), 3);
+//! ```
+use crate::registers::*;
+use crate::{DataFlashParameter, DataFlashValue};
+
+/// Edits a transaction holds unless another capacity is given
+pub const DEFAULT_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionError {
+    /// The value has the wrong kind for the data flash parameter or is out of its range
+    InvalidValue { parameter: &'static str },
+    /// All edits are in use
+    Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Edit {
+    pub(crate) parameter: DataFlashParameter,
+    pub(crate) bytes: [u8; FLASH_BLOCK_SIZE], // encoded value, only the size of the parameter is used
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataFlashTransaction<const N: usize = DEFAULT_CAPACITY> {
+    edits: [Option<Edit>; N],
+}
+
+impl<const N: usize> Default for DataFlashTransaction<N> {
+    fn default() -> Self {
+        return DataFlashTransaction::new();
+    }
+}
+
+impl<const N: usize> DataFlashTransaction<N> {
+    pub fn new() -> Self {
+        return DataFlashTransaction { edits: [None; N] };
+    }
+
+    /// Adds an edit, a parameter set before is replaced
+    pub fn set(&mut self, parameter: &DataFlashParameter, value: DataFlashValue) -> Result<(), TransactionError> {
+        let mut bytes = [0u8; FLASH_BLOCK_SIZE];
+        parameter.encode(value, &mut bytes).ok_or(TransactionError::InvalidValue { parameter: parameter.name })?;
+        let edit = Edit { parameter: *parameter, bytes };
+
+        let same = self.edits.iter().position(|slot| slot.is_some_and(|edit| edit.parameter == *parameter));
+        let slot = same.or_else(|| self.edits.iter().position(Option::is_none)).ok_or(TransactionError::Full)?;
+        self.edits[slot] = Some(edit);
+        return Ok(());
+    }
+
+    pub fn len(&self) -> usize {
+        return self.edits().count();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+
+    pub fn contains(&self, parameter: &DataFlashParameter) -> bool {
+        return self.edits().any(|edit| edit.parameter == *parameter);
+    }
+
+    pub fn clear(&mut self) {
+        self.edits = [None; N];
+    }
+
+    pub(crate) fn edits(&self) -> impl Iterator<Item = &Edit> {
+        return self.edits.iter().flatten();
+    }
+
+    /// Blocks touched by the edits as (subclass, offset of the block), each once, in the order of the edits
+    pub(crate) fn blocks(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        let blocks = || {
+            self.edits().flat_map(|edit| {
+                let sub_class = edit.parameter.sub_class;
+                edit.parameter.block_parts().map(move |(offset, _)| (sub_class, block_start(offset)))
+            })
+        };
+        return blocks()
+            .enumerate()
+            .filter(move |(i, block)| !blocks().take(*i).any(|other| other == *block))
+            .map(|(_, block)| block);
+    }
+
+    /// Edited bytes of a block as (position in the block, byte)
+    pub(crate) fn block_bytes(&self, sub_class: u8, block: u8) -> impl Iterator<Item = (u8, u8)> + '_ {
+        return self.edits().filter(move |edit| edit.parameter.sub_class == sub_class).flat_map(move |edit| {
+            edit.parameter.block_parts().filter(move |(offset, _)| block_start(*offset) == block).flat_map(
+                move |(offset, part)| {
+                    let position = offset % FLASH_BLOCK_SIZE as u8;
+                    part.enumerate().map(move |(i, index)| (position + i as u8, edit.bytes[index]))
+                },
+            )
+        });
+    }
+}
+
+fn block_start(offset: u8) -> u8 {
+    return offset / FLASH_BLOCK_SIZE as u8 * FLASH_BLOCK_SIZE as u8;
+}
+
+/// Result of one edit, read back after the reset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterOutcome {
+    Stored,
+    NotStored { actual: DataFlashValue },
+}
+
+/// Outcomes of the edits of a committed transaction, in the order of the edits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransactionReport<const N: usize = DEFAULT_CAPACITY> {
+    outcomes: [Option<(DataFlashParameter, ParameterOutcome)>; N],
+}
+
+impl<const N: usize> TransactionReport<N> {
+    pub(crate) fn new() -> Self {
+        return TransactionReport { outcomes: [None; N] };
+    }
+
+    /// Compares the bytes read back with the written ones
+    pub(crate) fn record(&mut self, edit: &Edit, stored: &[u8]) {
+        let size = edit.parameter.size() as usize;
+        let outcome = if edit.bytes[..size] == stored[..size] {
+            ParameterOutcome::Stored
+        } else {
+            ParameterOutcome::NotStored { actual: edit.parameter.decode(stored) }
+        };
+        if let Some(slot) = self.outcomes.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some((edit.parameter, outcome));
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(DataFlashParameter, ParameterOutcome)> {
+        return self.outcomes.iter().flatten();
+    }
+
+    pub fn outcome(&self, parameter: &DataFlashParameter) -> Option<ParameterOutcome> {
+        return self.iter().find(|(edited, _)| edited == parameter).map(|(_, outcome)| *outcome);
+    }
+
+    pub fn all_stored(&self) -> bool {
+        return self.iter().all(|(_, outcome)| *outcome == ParameterOutcome::Stored);
+    }
+}
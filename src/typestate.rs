@@ -0,0 +1,457 @@
+//! Driver that tracks the security mode of the gauge in its type.
+//!
+//! [`Bq34z100g1TypestateDriver`] wraps a [`Bq34z100g1Driver`] and only exposes what the gauge accepts in the
+//! current mode: reading is possible in every mode through [`status()`](Bq34z100g1TypestateDriver::status), data
+//! flash access, updates and calibration only when [`Unsealed`] or in [`FullAccess`], where they skip the unseal of
+//! the plain driver as the type already guarantees it. Mode changes consume the driver and hand back the new one, on
+//! failure the old one is returned inside [`TransitionError`].
+//!
+//! ```compile_fail
+//! use bq34z100::typestate::{Bq34z100g1TypestateDriver, Sealed};
+//!
+//! fn update<I2C: embedded_hal::i2c::I2c, D: embedded_hal::delay::DelayNs>(
+//!     gauge: &mut Bq34z100g1TypestateDriver<I2C, D, Sealed>,
+//! ) {
+//!     // data flash writes need an unsealed gauge
+//!     gauge.update_design_capacity(3000).unwrap();
+//! }
+//! ```
+use core::fmt::{Debug, Formatter};
+use core::marker::PhantomData;
+
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+use crate::{
+    Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, DeviceInfo, GaugeStatus, Measurements, Scale, SecurityKey,
+    SecurityMode,
+};
+#[cfg(feature = "write")]
+use crate::image::DataFlashImage;
+#[cfg(feature = "write")]
+use crate::transaction::{DataFlashTransaction, TransactionReport};
+#[cfg(feature = "write")]
+use crate::{
+    DataFlashParameter, DataFlashValue, GaugeCalibrator, GaugeConfigurator, PackConfiguration, PackConfigurationB,
+    PackConfigurationC,
+};
+#[cfg(feature = "flashstream")]
+use crate::FlashStreamProgrammer;
+
+#[derive(Debug)]
+pub struct Sealed;
+#[derive(Debug)]
+pub struct Unsealed;
+#[derive(Debug)]
+pub struct FullAccess;
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for super::Sealed {}
+    impl Sealed for super::Unsealed {}
+    impl Sealed for super::FullAccess {}
+}
+
+/// Security mode a [`Bq34z100g1TypestateDriver`] can be in
+pub trait SecurityState: private::Sealed {
+    const MODE: SecurityMode;
+}
+
+/// Modes that allow data flash access and calibration
+pub trait Unlocked: SecurityState {}
+
+impl SecurityState for Sealed {
+    const MODE: SecurityMode = SecurityMode::Sealed;
+}
+impl SecurityState for Unsealed {
+    const MODE: SecurityMode = SecurityMode::Unsealed;
+}
+impl SecurityState for FullAccess {
+    const MODE: SecurityMode = SecurityMode::FullAccess;
+}
+impl Unlocked for Unsealed {}
+impl Unlocked for FullAccess {}
+
+/// A failed mode change, gives back the driver in the mode it was before
+pub struct TransitionError<D, E> {
+    pub driver: D,
+    pub error: Bq34Z100Error<E>,
+}
+
+impl<D, E: Debug> Debug for TransitionError<D, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        return f.debug_struct("TransitionError").field("error", &self.error).finish_non_exhaustive();
+    }
+}
+
+/// The driver in whatever mode the gauge reported
+pub enum DetectedMode<I2C, Delay> {
+    Sealed(Bq34z100g1TypestateDriver<I2C, Delay, Sealed>),
+    Unsealed(Bq34z100g1TypestateDriver<I2C, Delay, Unsealed>),
+    FullAccess(Bq34z100g1TypestateDriver<I2C, Delay, FullAccess>),
+}
+
+pub struct Bq34z100g1TypestateDriver<I2C, Delay, S> {
+    driver: Bq34z100g1Driver<I2C, Delay>,
+    full_access_key: Option<SecurityKey>,
+    state: PhantomData<S>,
+}
+
+impl<I2C, Delay, S: SecurityState> Debug for Bq34z100g1TypestateDriver<I2C, Delay, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        return f
+            .debug_struct("Bq34z100g1TypestateDriver")
+            .field("address", &self.driver.address())
+            .field("mode", &S::MODE)
+            .finish_non_exhaustive();
+    }
+}
+
+type Transition<I2C, Delay, From, To> = Result<
+    Bq34z100g1TypestateDriver<I2C, Delay, To>,
+    TransitionError<Bq34z100g1TypestateDriver<I2C, Delay, From>, <I2C as embedded_hal::i2c::ErrorType>::Error>,
+>;
+
+type DriverMeasurements<'a, I2C, Delay> =
+    Measurements<'a, Bq34z100g1Driver<I2C, Delay>, <I2C as embedded_hal::i2c::ErrorType>::Error>;
+
+impl<I2C: I2c, Delay: DelayNs> Bq34z100g1Driver<I2C, Delay> {
+    /// Reads the security mode from the gauge and moves the driver into the matching typestate driver. A gauge in full
+    /// access is tracked as [`Unsealed`] here, as the updates reset the gauge and only the key brings it back to full
+    /// access, see into_typestate_with_key()
+    pub fn into_typestate(self) -> Result<DetectedMode<I2C, Delay>, TransitionError<Self, I2C::Error>> {
+        return self.detect_typestate(None);
+    }
+
+    /// into_typestate() for a gauge that may be in full access, the key is kept to return to it after updates
+    pub fn into_typestate_with_key(
+        self,
+        full_access_key: SecurityKey,
+    ) -> Result<DetectedMode<I2C, Delay>, TransitionError<Self, I2C::Error>> {
+        return self.detect_typestate(Some(full_access_key));
+    }
+
+    fn detect_typestate(
+        mut self,
+        full_access_key: Option<SecurityKey>,
+    ) -> Result<DetectedMode<I2C, Delay>, TransitionError<Self, I2C::Error>> {
+        let key = full_access_key;
+        return match (self.security_mode(), key) {
+            (Ok(SecurityMode::Sealed), _) => Ok(DetectedMode::Sealed(Bq34z100g1TypestateDriver::wrap(self, key))),
+            (Ok(SecurityMode::FullAccess), Some(_)) => {
+                Ok(DetectedMode::FullAccess(Bq34z100g1TypestateDriver::wrap(self, key)))
+            }
+            (Ok(SecurityMode::Unsealed | SecurityMode::FullAccess), _) => {
+                Ok(DetectedMode::Unsealed(Bq34z100g1TypestateDriver::wrap(self, key)))
+            }
+            (Err(error), _) => Err(TransitionError { driver: self, error }),
+        };
+    }
+}
+
+impl<I2C, Delay, S: SecurityState> Bq34z100g1TypestateDriver<I2C, Delay, S> {
+    /// In the unlocked modes the driver skips the unseal of the update paths until their reset
+    fn wrap(mut driver: Bq34z100g1Driver<I2C, Delay>, full_access_key: Option<SecurityKey>) -> Self {
+        driver.unsealed_known = S::MODE != SecurityMode::Sealed;
+        return Bq34z100g1TypestateDriver { driver, full_access_key, state: PhantomData };
+    }
+
+    fn into_state<T: SecurityState>(self) -> Bq34z100g1TypestateDriver<I2C, Delay, T> {
+        return Bq34z100g1TypestateDriver::wrap(self.driver, self.full_access_key);
+    }
+
+    /// Gives up the mode tracking and hands back the plain driver
+    pub fn into_inner(mut self) -> Bq34z100g1Driver<I2C, Delay> {
+        self.driver.unsealed_known = false;
+        return self.driver;
+    }
+}
+
+impl<I2C: I2c, Delay: DelayNs, S: SecurityState> Bq34z100g1TypestateDriver<I2C, Delay, S> {
+    /// The mode tracked by the type, see security_mode() for the one the gauge reports
+    pub fn mode(&self) -> SecurityMode {
+        return S::MODE;
+    }
+
+    pub fn security_mode(&mut self) -> Result<SecurityMode, Bq34Z100Error<I2C::Error>> {
+        return self.driver.security_mode();
+    }
+
+    pub fn measurements(&mut self) -> Result<DriverMeasurements<'_, I2C, Delay>, Bq34Z100Error<I2C::Error>> {
+        return self.driver.measurements();
+    }
+
+    /// Detected from the data flash only in the unlocked modes, give the scale of a sealed SCALED pack with
+    /// with_scale() on the driver before into_typestate()
+    pub fn scale(&mut self) -> Result<Scale, Bq34Z100Error<I2C::Error>> {
+        return self.driver.scale();
+    }
+
+    pub fn set_scale(&mut self, scale: Scale) {
+        self.driver.set_scale(scale);
+    }
+
+    pub fn forget_scale(&mut self) {
+        self.driver.forget_scale();
+    }
+
+    /// The readings of [`GaugeStatus`], available in every mode. Only the reading methods can be reached through it,
+    /// so the mode tracked by the type still holds.
+    pub fn status(&mut self) -> &mut impl GaugeStatus<I2C::Error> {
+        return &mut self.driver;
+    }
+
+    pub fn identify(&mut self) -> Result<DeviceInfo, Bq34Z100Error<I2C::Error>> {
+        return self.driver.identify();
+    }
+}
+
+
+impl<I2C: I2c, Delay: DelayNs> Bq34z100g1TypestateDriver<I2C, Delay, Sealed> {
+    pub fn unseal(mut self, key: SecurityKey) -> Transition<I2C, Delay, Sealed, Unsealed> {
+        return match self.driver.unseal(key) {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError { driver: self, error }),
+        };
+    }
+}
+
+impl<I2C: I2c, Delay: DelayNs> Bq34z100g1TypestateDriver<I2C, Delay, Unsealed> {
+    /// The key is kept to return to full access after updates, as they reset the gauge
+    pub fn full_access(mut self, key: SecurityKey) -> Transition<I2C, Delay, Unsealed, FullAccess> {
+        return match self.driver.full_access(key) {
+            Ok(()) => {
+                self.full_access_key = Some(key);
+                Ok(self.into_state())
+            }
+            Err(error) => Err(TransitionError { driver: self, error }),
+        };
+    }
+
+    pub fn seal(mut self) -> Transition<I2C, Delay, Unsealed, Sealed> {
+        return match self.driver.seal() {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError { driver: self, error }),
+        };
+    }
+}
+
+impl<I2C: I2c, Delay: DelayNs> Bq34z100g1TypestateDriver<I2C, Delay, FullAccess> {
+    pub fn seal(mut self) -> Transition<I2C, Delay, FullAccess, Sealed> {
+        return match self.driver.seal() {
+            Ok(()) => Ok(self.into_state()),
+            Err(error) => Err(TransitionError { driver: self, error }),
+        };
+    }
+}
+
+impl<I2C: I2c, Delay: DelayNs, S: Unlocked> Bq34z100g1TypestateDriver<I2C, Delay, S> {
+    /// The updates and calibrations end with a reset, which leaves the gauge unsealed at best
+    #[cfg(feature = "write")]
+    fn restore_mode(&mut self) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        // FullAccess is only entered with the key, see full_access() and into_typestate_with_key()
+        if let (SecurityMode::FullAccess, Some(key)) = (S::MODE, self.full_access_key) {
+            self.driver.full_access(key)?;
+        }
+        self.driver.unsealed_known = true;
+        return Ok(());
+    }
+
+    pub fn it_enable(&mut self) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        return self.driver.it_enable();
+    }
+
+    #[cfg(feature = "flashstream")]
+    pub fn write_flash_stream_i2c(&mut self, line: &str, dryrun: bool) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        // the stream may reset the gauge, the next update unseals it again
+        self.driver.unsealed_known = false;
+        return self.driver.write_flash_stream_i2c(line, dryrun);
+    }
+
+    #[cfg(feature = "write")]
+    pub fn read_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        return self.driver.read_flash_block(sub_class, offset);
+    }
+
+    #[cfg(feature = "write")]
+    pub fn write_flash_block(&mut self, sub_class: u8, offset: u8) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        return self.driver.write_flash_block(sub_class, offset);
+    }
+
+    #[cfg(feature = "write")]
+    pub fn flash_block_data(&self) -> &[u8; 32] {
+        return self.driver.flash_block_data();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn flash_block_data_mut(&mut self) -> &mut [u8; 32] {
+        return self.driver.flash_block_data_mut();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_design_capacity(&mut self, capacity: u16) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_design_capacity(capacity)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_q_max(&mut self, capacity: i16) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_q_max(capacity)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_design_energy(&mut self, energy: i16, scale: u8) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_design_energy(energy, scale)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_cell_charge_voltage_range(
+        &mut self,
+        t1_t2: u16,
+        t2_t3: u16,
+        t3_t4: u16,
+    ) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_cell_charge_voltage_range(t1_t2, t2_t3, t3_t4)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_number_of_series_cells(&mut self, cells: u8) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_number_of_series_cells(cells)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_pack_configuration(&mut self, config: PackConfiguration) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_pack_configuration(config)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn pack_configuration_b(&mut self) -> Result<PackConfigurationB, Bq34Z100Error<I2C::Error>> {
+        return self.driver.pack_configuration_b();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_pack_configuration_b(&mut self, config: PackConfigurationB) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_pack_configuration_b(config)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn pack_configuration_c(&mut self) -> Result<PackConfigurationC, Bq34Z100Error<I2C::Error>> {
+        return self.driver.pack_configuration_c();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn update_pack_configuration_c(&mut self, config: PackConfigurationC) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_pack_configuration_c(config)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_charge_termination_parameters(
+        &mut self,
+        taper_current: i16,
+        min_taper_capacity: i16,
+        cell_taper_voltage: i16,
+        taper_window: u8,
+        tca_set: i8,
+        tca_clear: i8,
+        fc_set: i8,
+        fc_clear: i8,
+    ) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.update_charge_termination_parameters(
+            taper_current,
+            min_taper_capacity,
+            cell_taper_voltage,
+            taper_window,
+            tca_set,
+            tca_clear,
+            fc_set,
+            fc_clear,
+        )?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn set_led_mode(&mut self, led_config: u8) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.set_led_mode(led_config)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn read_parameter(&mut self, parameter: &DataFlashParameter) -> Result<DataFlashValue, Bq34Z100Error<I2C::Error>> {
+        return self.driver.read_parameter(parameter);
+    }
+
+    #[cfg(feature = "write")]
+    pub fn write_parameter(
+        &mut self,
+        parameter: &DataFlashParameter,
+        value: DataFlashValue,
+    ) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.write_parameter(parameter, value)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn commit_transaction<const N: usize>(
+        &mut self,
+        transaction: &DataFlashTransaction<N>,
+    ) -> Result<TransactionReport<N>, Bq34Z100Error<I2C::Error>> {
+        let report = self.driver.commit_transaction(transaction)?;
+        self.restore_mode()?;
+        return Ok(report);
+    }
+
+    #[cfg(feature = "write")]
+    pub fn dump_data_flash(&mut self) -> Result<DataFlashImage, Bq34Z100Error<I2C::Error>> {
+        return self.driver.dump_data_flash();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn restore_data_flash(&mut self, image: &DataFlashImage) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.restore_data_flash(image)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn restore_data_flash_strict(&mut self, image: &DataFlashImage) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.restore_data_flash_strict(image)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn calibrate_cc_offset(&mut self) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.calibrate_cc_offset()?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn calibrate_board_offset(&mut self) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.calibrate_board_offset()?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn measure_cc_offset(&mut self) -> Result<i16, Bq34Z100Error<I2C::Error>> {
+        let offset = self.driver.measure_cc_offset()?;
+        self.restore_mode()?;
+        return Ok(offset);
+    }
+
+    #[cfg(feature = "write")]
+    pub fn calibrate_voltage_divider(&mut self, applied_voltage: f32) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.calibrate_voltage_divider(applied_voltage)?;
+        return self.restore_mode();
+    }
+
+    #[cfg(feature = "write")]
+    pub fn calibrate_sense_resistor(&mut self, applied_current: i16) -> Result<(), Bq34Z100Error<I2C::Error>> {
+        self.driver.calibrate_sense_resistor(applied_current)?;
+        return self.restore_mode();
+    }
+}
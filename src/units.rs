@@ -0,0 +1,311 @@
+//! Physical units of the readings.
+//!
+//! The raw accessors of [`GaugeStatus`] return the register content, [`Measurements`] (and `MeasurementsAsync` with
+//! the "async" feature) returns the same readings as newtypes with the unit and sign applied. With the "uom" feature
+//! the newtypes convert into the matching `uom` quantities.
+use core::marker::PhantomData;
+
+use crate::{Bq34Z100Error, GaugeStatus};
+
+/// Voltage in mV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Millivolts(pub i32);
+
+/// Current in mA, negative while discharging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Milliamps(pub i32);
+
+/// Charge in mAh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MilliampHours(pub i32);
+
+/// Energy in mWh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MilliwattHours(pub i32);
+
+/// Power in mW, negative while discharging
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Milliwatts(pub i32);
+
+/// Temperature in 0.1 K, the unit the gauge reports in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DeciKelvin(pub u16);
+
+/// Time in minutes, 65535 means not available (eg. time to empty while charging)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Minutes(pub u16);
+
+impl Millivolts {
+    pub fn from_register(raw: u16) -> Millivolts {
+        return Millivolts(raw as i32);
+    }
+}
+
+impl Milliamps {
+    pub fn from_register(raw: i16) -> Milliamps {
+        return Milliamps(raw as i32);
+    }
+}
+
+impl MilliampHours {
+    pub fn from_register(raw: u16) -> MilliampHours {
+        return MilliampHours(raw as i32);
+    }
+
+    /// For the signed PassedCharge()
+    pub fn from_signed_register(raw: i16) -> MilliampHours {
+        return MilliampHours(raw as i32);
+    }
+}
+
+impl MilliwattHours {
+    /// AvailableEnergy() is reported in 10 mWh
+    pub fn from_register(raw: u16) -> MilliwattHours {
+        return MilliwattHours(raw as i32 * 10);
+    }
+}
+
+impl Milliwatts {
+    /// AveragePower() is reported in 10 mW
+    pub fn from_register(raw: i16) -> Milliwatts {
+        return Milliwatts(raw as i32 * 10);
+    }
+}
+
+impl DeciKelvin {
+    pub fn from_register(raw: u16) -> DeciKelvin {
+        return DeciKelvin(raw);
+    }
+
+    pub fn to_celsius(self) -> f32 {
+        return self.0 as f32 / 10.0 - 273.15;
+    }
+
+    /// Integer variant of to_celsius() in 0.1 °C, 0 °C is 2731.5 dK so the result is rounded half away from zero
+    pub fn to_decicelsius(self) -> i32 {
+        let doubled = 2 * self.0 as i32 - 5463; // in 0.05 °C, always odd
+        return (doubled + doubled.signum()) / 2;
+    }
+}
+
+impl Minutes {
+    pub fn from_register(raw: u16) -> Minutes {
+        return Minutes(raw);
+    }
+
+    pub fn is_available(self) -> bool {
+        return self.0 != u16::MAX;
+    }
+}
+
+/// Multipliers of the readings of a pack using the SCALED bit of Pack Configuration.
+///
+/// Packs beyond the 16 bit range of the standard commands report energy and power in units of Design Energy Scale,
+/// which the driver reads from the data flash. Current and capacity are reported in the unit the coulomb counter was
+/// calibrated to (eg. a sense resistor calibrated 10 times too small gives 10 mA and mAh), the gauge does not store
+/// that factor so `current` is configuration, see `with_scale()` of the drivers (the way for sealed packs too).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scale {
+    pub current: u16, // current, capacity and passed charge
+    pub energy: u16,  // available energy and average power
+}
+
+impl Scale {
+    pub const UNSCALED: Scale = Scale { current: 1, energy: 1 };
+
+    pub fn is_scaled(&self) -> bool {
+        return *self != Scale::UNSCALED;
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        return Scale::UNSCALED;
+    }
+}
+
+/// Declares the typed view on the readings over the status trait, blocking with [] [] and async with [async] [.await]
+macro_rules! measurements {
+    ([$($async:tt)?] [$($await:tt)*] $(#[$doc:meta])* $Measurements:ident over $Status:ident) => {
+        $(#[$doc])*
+        pub struct $Measurements<'a, G, E> {
+            gauge: &'a mut G,
+            scale: Option<Scale>,
+            error: PhantomData<E>,
+        }
+
+        impl<'a, G: $Status<E>, E> $Measurements<'a, G, E> {
+            /// Readings without any scale applied
+            pub fn new(gauge: &'a mut G) -> Self {
+                return $Measurements::with_scale(gauge, Scale::UNSCALED);
+            }
+
+            pub fn with_scale(gauge: &'a mut G, scale: Scale) -> Self {
+                return $Measurements::with_detected_scale(gauge, Some(scale));
+            }
+
+            pub(crate) fn with_detected_scale(gauge: &'a mut G, scale: Option<Scale>) -> Self {
+                return $Measurements { gauge, scale, error: PhantomData };
+            }
+
+            /// None if the scale of the SCALED pack is unknown, the scaled readings fail with UnknownScale then
+            pub fn scale(&self) -> Option<Scale> {
+                return self.scale;
+            }
+
+            fn known_scale(&self) -> Result<Scale, Bq34Z100Error<E>> {
+                return self.scale.ok_or(Bq34Z100Error::UnknownScale);
+            }
+
+            pub $($async)? fn voltage(&mut self) -> Result<Millivolts, Bq34Z100Error<E>> {
+                return Ok(Millivolts::from_register(self.gauge.voltage()$($await)*?));
+            }
+
+            pub $($async)? fn charge_voltage(&mut self) -> Result<Millivolts, Bq34Z100Error<E>> {
+                return Ok(Millivolts::from_register(self.gauge.charge_voltage()$($await)*?));
+            }
+
+            pub $($async)? fn current(&mut self) -> Result<Milliamps, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = Milliamps::from_register(self.gauge.current()$($await)*?);
+                return Ok(Milliamps(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn average_current(&mut self) -> Result<Milliamps, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = Milliamps::from_register(self.gauge.average_current()$($await)*?);
+                return Ok(Milliamps(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn charge_current(&mut self) -> Result<Milliamps, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                // ChargeCurrent() is unsigned
+                let value = Milliamps(self.gauge.charge_current()$($await)*? as i32);
+                return Ok(Milliamps(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn remaining_capacity(&mut self) -> Result<MilliampHours, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = MilliampHours::from_register(self.gauge.remaining_capacity()$($await)*?);
+                return Ok(MilliampHours(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn full_charge_capacity(&mut self) -> Result<MilliampHours, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = MilliampHours::from_register(self.gauge.full_charge_capacity()$($await)*?);
+                return Ok(MilliampHours(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn design_capacity(&mut self) -> Result<MilliampHours, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = MilliampHours::from_register(self.gauge.design_capacity()$($await)*?);
+                return Ok(MilliampHours(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn passed_charge(&mut self) -> Result<MilliampHours, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = MilliampHours::from_signed_register(self.gauge.passed_charge()$($await)*?);
+                return Ok(MilliampHours(value.0 * scale.current as i32));
+            }
+
+            pub $($async)? fn available_energy(&mut self) -> Result<MilliwattHours, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = MilliwattHours::from_register(self.gauge.available_energy()$($await)*?);
+                return Ok(MilliwattHours(value.0 * scale.energy as i32));
+            }
+
+            pub $($async)? fn average_power(&mut self) -> Result<Milliwatts, Bq34Z100Error<E>> {
+                let scale = self.known_scale()?;
+                let value = Milliwatts::from_register(self.gauge.average_power()$($await)*?);
+                return Ok(Milliwatts(value.0 * scale.energy as i32));
+            }
+
+            pub $($async)? fn temperature(&mut self) -> Result<DeciKelvin, Bq34Z100Error<E>> {
+                return Ok(DeciKelvin::from_register(self.gauge.temperature()$($await)*?));
+            }
+
+            pub $($async)? fn internal_temperature(&mut self) -> Result<DeciKelvin, Bq34Z100Error<E>> {
+                return Ok(DeciKelvin::from_register(self.gauge.internal_temperature()$($await)*?));
+            }
+
+            pub $($async)? fn average_time_to_empty(&mut self) -> Result<Minutes, Bq34Z100Error<E>> {
+                return Ok(Minutes::from_register(self.gauge.average_time_to_empty()$($await)*?));
+            }
+
+            pub $($async)? fn average_time_to_full(&mut self) -> Result<Minutes, Bq34Z100Error<E>> {
+                return Ok(Minutes::from_register(self.gauge.average_time_to_full()$($await)*?));
+            }
+
+            pub $($async)? fn dod0_time(&mut self) -> Result<Minutes, Bq34Z100Error<E>> {
+                return Ok(Minutes::from_register(self.gauge.do_d0_time()$($await)*?));
+            }
+        }
+    };
+}
+#[cfg(feature = "async")]
+pub(crate) use measurements;
+
+measurements!(
+    [] []
+    /// Typed view on the readings of a gauge, see
+    /// [`Bq34z100g1Driver::measurements`](crate::Bq34z100g1Driver::measurements)
+    Measurements over GaugeStatus
+);
+
+#[cfg(feature = "uom")]
+mod quantities {
+    use uom::si::electric_charge::milliampere_hour;
+    use uom::si::electric_current::milliampere;
+    use uom::si::electric_potential::millivolt;
+    use uom::si::energy::milliwatt_hour;
+    use uom::si::f32::{
+        ElectricCharge, ElectricCurrent, ElectricPotential, Energy, Power, ThermodynamicTemperature, Time,
+    };
+    use uom::si::power::milliwatt;
+    use uom::si::thermodynamic_temperature::kelvin;
+    use uom::si::time::minute;
+
+    use super::*;
+
+    impl From<Millivolts> for ElectricPotential {
+        fn from(value: Millivolts) -> Self {
+            return ElectricPotential::new::<millivolt>(value.0 as f32);
+        }
+    }
+
+    impl From<Milliamps> for ElectricCurrent {
+        fn from(value: Milliamps) -> Self {
+            return ElectricCurrent::new::<milliampere>(value.0 as f32);
+        }
+    }
+
+    impl From<MilliampHours> for ElectricCharge {
+        fn from(value: MilliampHours) -> Self {
+            return ElectricCharge::new::<milliampere_hour>(value.0 as f32);
+        }
+    }
+
+    impl From<MilliwattHours> for Energy {
+        fn from(value: MilliwattHours) -> Self {
+            return Energy::new::<milliwatt_hour>(value.0 as f32);
+        }
+    }
+
+    impl From<Milliwatts> for Power {
+        fn from(value: Milliwatts) -> Self {
+            return Power::new::<milliwatt>(value.0 as f32);
+        }
+    }
+
+    impl From<DeciKelvin> for ThermodynamicTemperature {
+        fn from(value: DeciKelvin) -> Self {
+            return ThermodynamicTemperature::new::<kelvin>(value.0 as f32 / 10.0);
+        }
+    }
+
+    impl From<Minutes> for Time {
+        fn from(value: Minutes) -> Self {
+            return Time::new::<minute>(value.0 as f32);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Xemics floating point format of the data flash, eg. CC Gain and CC Delta.
+//!
+//! Byte 3 holds the exponent + 128, bytes 2..0 the mantissa with its sign in place of the always set leading bit:
+//! value = ±(0x800000 | mantissa) * 2^(exponent - 24). That is an IEEE single with a different exponent bias, so the
+//! conversion only moves bits around and needs neither std nor libm. Both directions are exact.
+
+const SIGN: u32 = 0x0080_0000;
+const MANTISSA: u32 = 0x007f_ffff;
+// the IEEE exponent is biased by 127 with the leading bit in front of the point, Xemics by 128 behind it
+const EXPONENT_OFFSET: u32 = 2;
+
+/// Decodes a Xemics value, exponents below the smallest normal f32 read as zero
+pub fn decode(raw: u32) -> f32 {
+    let exponent = raw >> 24;
+    let sign = (raw & SIGN) << 8;
+    if exponent <= EXPONENT_OFFSET {
+        return f32::from_bits(sign);
+    }
+    return f32::from_bits(sign | ((exponent - EXPONENT_OFFSET) << 23) | (raw & MANTISSA));
+}
+
+/// Encodes a value, None if it is not finite or beyond the Xemics range (2^127). Zero and values below the smallest
+/// normal f32 are stored with exponent byte 0, which the gauge treats as zero.
+pub fn encode(value: f32) -> Option<u32> {
+    let bits = value.to_bits();
+    let sign = (bits >> 8) & SIGN;
+    let exponent = (bits >> 23) & 0xff;
+    if exponent == 0xff || exponent + EXPONENT_OFFSET > 0xff {
+        return None;
+    }
+    if exponent == 0 {
+        return Some(sign);
+    }
+    return Some(((exponent + EXPONENT_OFFSET) << 24) | sign | (bits & MANTISSA));
+}
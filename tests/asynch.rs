@@ -0,0 +1,234 @@
+#![cfg(feature = "async")]
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+use bq34z100::simulator::{Bq34z100g1Simulator, SimulatedSecurity};
+use bq34z100::transaction::DataFlashTransaction;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Async, Bq34z100g1AsyncDriver, Capabilities, DataFlash, DataFlashText, DataFlashValue,
+    FlashStreamProgrammerAsync, GaugeCalibratorAsync, GaugeConfiguratorAsync, GaugeStatusAsync, MilliampHours,
+    Milliamps, Millivolts, MilliwattHours, Scale, SecurityKey, SecurityMode, Variant,
+};
+use embedded_hal::i2c::I2c;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+// the simulator never pends, so polling once is enough
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("simulator future pending"),
+    }
+}
+
+fn driver(simulator: Bq34z100g1Simulator) -> Bq34z100g1AsyncDriver<Bq34z100g1Simulator, NoopDelay> {
+    Bq34z100g1AsyncDriver::new(simulator, NoopDelay::new(), None)
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+#[test]
+fn led_mode_is_refused_on_r2_firmware() {
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_fw_version(0x0020));
+
+    let result = block_on(gauge.set_led_mode(0x02));
+
+    assert!(matches!(
+        result,
+        Err(Bq34Z100Error::Unsupported { capability: Capabilities::LED_CONFIGURATION, variant: Variant::Bq34z100R2 })
+    ));
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 0);
+    assert_eq!(simulator.data_flash(64, 4, 1), &[0]);
+}
+
+// only needs the register access, like applications that only read the gauge
+async fn charge_and_voltage<G: GaugeStatusAsync<E>, E>(gauge: &mut G) -> Result<(u8, u16), Bq34Z100Error<E>> {
+    Ok((gauge.state_of_charge().await?, gauge.voltage().await?))
+}
+
+#[test]
+fn status_is_available_for_every_async_gauge() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x02, 87);
+    simulator.set_standard_command(0x08, 3700);
+    let mut gauge = driver(simulator);
+
+    assert_eq!(block_on(charge_and_voltage(&mut gauge)).unwrap(), (87, 3700));
+    assert_eq!(block_on(gauge.fw_version()).unwrap(), 0x0017);
+    assert!(block_on(gauge.control_status_decoded()).unwrap().sealed);
+    block_on(gauge.unseal(SecurityKey::DEFAULT_UNSEAL)).unwrap();
+    assert_eq!(block_on(gauge.security_mode()).unwrap(), SecurityMode::Unsealed);
+}
+
+#[test]
+fn update_design_capacity() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    block_on(gauge.update_design_capacity(3000)).unwrap();
+    assert_eq!(block_on(gauge.design_capacity()).unwrap(), 3000);
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 8, 2)), 3000);
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 3000);
+    assert_eq!(simulator.reset_count(), 1);
+    assert_eq!(simulator.security(), SimulatedSecurity::Unsealed);
+}
+
+#[test]
+fn parameters_and_transactions_are_written() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    let name = DataFlashValue::Text(DataFlashText::new("pack-17").unwrap());
+    block_on(gauge.write_parameter(&DataFlash::DEVICE_NAME, name)).unwrap();
+    assert_eq!(block_on(gauge.read_parameter(&DataFlash::DEVICE_NAME)).unwrap(), name);
+    let mut transaction: DataFlashTransaction = DataFlashTransaction::new();
+    transaction.set(&DataFlash::TERMINATE_VOLTAGE, DataFlashValue::Integer(9000)).unwrap();
+    transaction.set(&DataFlash::NUMBER_OF_SERIES_CELLS, DataFlashValue::Integer(3)).unwrap();
+
+    assert!(block_on(gauge.commit_transaction(&transaction)).unwrap().all_stored());
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(48, 31, 8), b"\x07pack-17");
+    assert_eq!(be_u16(simulator.data_flash(80, 53, 2)), 9000);
+    assert_eq!(simulator.data_flash(64, 7, 1), [3]);
+    assert_eq!(simulator.reset_count(), 2);
+}
+
+#[test]
+fn image_is_restored_onto_a_replacement() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    block_on(gauge.update_design_capacity(3000)).unwrap();
+    let image = block_on(gauge.dump_data_flash()).unwrap();
+
+    let mut replacement = driver(Bq34z100g1Simulator::new());
+    block_on(replacement.restore_data_flash(&image)).unwrap();
+
+    assert_eq!(block_on(replacement.dump_data_flash()).unwrap(), image);
+    let (simulator, _) = replacement.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 3000);
+    assert_eq!(simulator.reset_count(), 1);
+}
+
+#[test]
+fn update_fails_with_wrong_unseal_key() {
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_unseal_key([0x1234, 0x5678]));
+    assert!(matches!(
+        block_on(gauge.update_design_capacity(3000)),
+        Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Unsealed, actual: SecurityMode::Sealed })
+    ));
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 1000);
+}
+
+#[test]
+fn security_modes() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    assert!(matches!(
+        block_on(gauge.full_access(SecurityKey::DEFAULT_FULL_ACCESS)),
+        Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::FullAccess, actual: SecurityMode::Sealed })
+    ));
+
+    block_on(gauge.unseal(SecurityKey::DEFAULT_UNSEAL)).unwrap();
+    block_on(gauge.full_access(SecurityKey::DEFAULT_FULL_ACCESS)).unwrap();
+    assert_eq!(block_on(gauge.security_mode()).unwrap(), SecurityMode::FullAccess);
+
+    block_on(gauge.seal()).unwrap();
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.security(), SimulatedSecurity::Sealed);
+}
+
+#[test]
+fn calibrate_offsets() {
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_cc_offset(-25));
+    block_on(gauge.calibrate_cc_offset()).unwrap();
+    block_on(gauge.calibrate_board_offset()).unwrap();
+    assert_eq!(block_on(gauge.measure_cc_offset()).unwrap(), -25);
+
+    block_on(gauge.unsealed()).unwrap();
+    // CALEN, CCA and BCA are cleared again
+    assert_eq!(block_on(gauge.control_status()).unwrap() & 0x1c00, 0);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 3);
+}
+
+#[test]
+fn calibrate_sense_resistor() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x10, 500);
+    let mut gauge = driver(simulator);
+    block_on(gauge.calibrate_sense_resistor(1000)).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(104, 0, 2), [0x80, 0x74]);
+    assert_eq!(simulator.data_flash(104, 4, 2), [0x95, 0x0a]);
+    assert_eq!(simulator.reset_count(), 1);
+}
+
+#[test]
+fn flash_stream() {
+    let stream = [
+        ";unseal",
+        "W: AA 00 14 04",
+        "W: AA 00 72 36",
+        "W: AA 61 00",
+        "W: AA 3E 30",
+        "W: AA 3F 00",
+        "W: AA 4B 07 D0",
+        "X: 10",
+        "C: AA 4B 07 D0",
+    ];
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    for line in stream {
+        block_on(gauge.write_flash_stream_i2c(line, false)).unwrap();
+    }
+
+    let (mut simulator, _) = gauge.release();
+    let mut checksum = [0];
+    simulator.write_read(0x55, &[0x60], &mut checksum).unwrap();
+    let mut gauge = driver(simulator);
+    block_on(gauge.write_flash_stream_i2c(&format!("W: AA 60 {:02X}", checksum[0]), false)).unwrap();
+    assert!(matches!(
+        block_on(gauge.write_flash_stream_i2c("C: AA 4B 07 D1", false)),
+        Err(Bq34Z100Error::ChecksumError { register: 0x4c, expected: 0xd1, actual: 0xd0 })
+    ));
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 2000);
+}
+
+#[test]
+fn scaled_readings() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_data_flash(64, 0, &0x01e1_u16.to_be_bytes()); // SCALED
+    simulator.set_data_flash(48, 30, &[10]);
+    simulator.set_standard_command(0x3a, 0x01e1);
+    simulator.set_standard_command(0x04, 20000);
+    simulator.set_standard_command(0x10, -3000i16 as u16);
+    simulator.set_standard_command(0x24, 960);
+    let mut gauge = driver(simulator);
+
+    let result = block_on(gauge.scale());
+    assert!(matches!(result, Err(Bq34Z100Error::SecurityAccessDenied { actual: SecurityMode::Sealed, .. })));
+    block_on(async {
+        let mut measurements = gauge.measurements().await.unwrap();
+        assert_eq!(measurements.voltage().await.unwrap(), Millivolts(3700));
+        assert!(matches!(measurements.remaining_capacity().await, Err(Bq34Z100Error::UnknownScale)));
+    });
+
+    block_on(gauge.unsealed()).unwrap();
+    assert_eq!(block_on(gauge.scale()).unwrap(), Scale { current: 1, energy: 10 });
+    gauge.set_scale(Scale { current: 10, energy: 10 });
+    block_on(async {
+        let mut measurements = gauge.measurements().await.unwrap();
+        assert_eq!(measurements.current().await.unwrap(), Milliamps(-30_000));
+        assert_eq!(measurements.remaining_capacity().await.unwrap(), MilliampHours(200_000));
+        assert_eq!(measurements.available_energy().await.unwrap(), MilliwattHours(96_000));
+    });
+
+    block_on(gauge.update_design_energy(9600, 1)).unwrap();
+    assert_eq!(block_on(gauge.scale()).unwrap(), Scale::UNSCALED);
+}
@@ -0,0 +1,90 @@
+use core::cell::RefCell;
+
+use bq34z100::simulator::{Bq34z100g1Simulator, SimulatedSecurity};
+use bq34z100::{Bq34z100g1, Bq34z100g1Driver, BusTiming, GaugeConfigurator, GaugeStatus, SecurityMode};
+use embedded_hal_bus::i2c::RefCellDevice;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn shared_driver(
+    bus: &RefCell<Bq34z100g1Simulator>,
+) -> Bq34z100g1Driver<RefCellDevice<'_, Bq34z100g1Simulator>, NoopDelay> {
+    let mut driver = Bq34z100g1Driver::new(RefCellDevice::new(bus), NoopDelay::new(), None);
+    driver.set_bus_timing(BusTiming::NONE);
+    driver
+}
+
+#[test]
+fn static_values_are_read_once() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None).into_cached();
+
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+    assert_eq!(gauge.device_type().unwrap(), 0x0100);
+    let transactions = gauge.driver().retry_statistics().transactions;
+
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+    assert_eq!(gauge.device_type().unwrap(), 0x0100);
+    assert_eq!(gauge.identify().unwrap().device_type, 0x0100);
+    let statistics = gauge.statistics();
+    assert_eq!((statistics.hits, statistics.misses), (3, 5));
+    // fw, hw and df version were read by identify()
+    assert_eq!(gauge.driver().retry_statistics().transactions, transactions + 6);
+
+    // measurements are always read from the gauge
+    gauge.voltage().unwrap();
+    assert_eq!(gauge.driver().retry_statistics().transactions, transactions + 7);
+}
+
+#[test]
+fn data_flash_updates_invalidate() {
+    let simulator = Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None).into_cached();
+
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+    gauge.update_design_capacity(2000).unwrap();
+    assert_eq!(gauge.design_capacity().unwrap(), 2000);
+    assert!(gauge.statistics().invalidations > 0);
+}
+
+#[test]
+fn external_reset_is_detected_by_reset_data() {
+    let bus = RefCell::new(Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed));
+    let mut gauge = shared_driver(&bus).into_cached();
+    let mut other = shared_driver(&bus);
+
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+    assert!(!gauge.check_reset().unwrap());
+
+    bus.borrow_mut().set_data_flash(48, 11, &1500_u16.to_be_bytes()); // Design Capacity
+    other.reset().unwrap();
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+    assert!(gauge.check_reset().unwrap());
+    assert_eq!(gauge.design_capacity().unwrap(), 1500);
+}
+
+#[test]
+fn external_reset_is_detected_by_a_mode_drop() {
+    let bus = RefCell::new(Bq34z100g1Simulator::new());
+    let mut gauge = shared_driver(&bus).into_cached();
+    let mut other = shared_driver(&bus);
+
+    gauge.unsealed().unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Unsealed);
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+
+    bus.borrow_mut().set_data_flash(48, 11, &1500_u16.to_be_bytes()); // Design Capacity
+    other.reset().unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Sealed);
+    assert_eq!(gauge.design_capacity().unwrap(), 1500);
+}
+
+#[test]
+fn sealing_is_no_reset() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None).into_cached();
+    gauge.unsealed().unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Unsealed);
+    gauge.design_capacity().unwrap();
+
+    gauge.seal().unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Sealed);
+    assert_eq!(gauge.statistics().invalidations, 0);
+}
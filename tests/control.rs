@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use bq34z100::simulator::{Bq34z100g1Simulator, SimulatedSecurity};
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, ControlCommand, GaugeCalibrator, GaugeStatus, SecurityMode,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn driver(simulator: Bq34z100g1Simulator) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    Bq34z100g1Driver::new(simulator, NoopDelay::new(), None)
+}
+
+#[test]
+fn subcommands_are_distinct() {
+    let subcommands: HashSet<u16> = ControlCommand::ALL.iter().map(|command| command.subcommand()).collect();
+    assert_eq!(subcommands.len(), ControlCommand::ALL.len());
+    assert_eq!(ControlCommand::EnterRom.subcommand(), 0x0f00);
+    assert_eq!(ControlCommand::OffsetCal.subcommand(), 0x0082);
+}
+
+#[test]
+fn security_levels() {
+    assert!(ControlCommand::SetHibernate.allowed_in(SecurityMode::Sealed));
+    assert!(ControlCommand::Sealed.allowed_in(SecurityMode::Sealed));
+    assert!(!ControlCommand::Reset.allowed_in(SecurityMode::Sealed));
+    assert!(ControlCommand::Reset.allowed_in(SecurityMode::FullAccess));
+    assert!(!ControlCommand::EnterRom.allowed_in(SecurityMode::Unsealed));
+}
+
+#[test]
+fn data_is_only_read_back_where_there_is_some() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    assert_eq!(gauge.send_control(ControlCommand::DeviceType).unwrap(), Some(0x0100));
+    assert_eq!(gauge.send_control(ControlCommand::SetFullsleep).unwrap(), None);
+    assert!(gauge.control_status_decoded().unwrap().full_sleep);
+}
+
+#[test]
+fn privileged_subcommands_are_refused_when_sealed() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    let error = gauge.send_control(ControlCommand::Reset).unwrap_err();
+    assert!(matches!(
+        error,
+        Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Unsealed, actual: SecurityMode::Sealed }
+    ));
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 0);
+}
+
+#[test]
+fn hibernate_is_accepted_when_sealed() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.set_hibernate().unwrap();
+    let (simulator, delay) = gauge.release();
+    assert!(simulator.hibernate());
+
+    let mut gauge = Bq34z100g1Driver::new(simulator, delay, None);
+    gauge.clear_hibernate().unwrap();
+    assert!(!gauge.release().0.hibernate());
+}
+
+#[test]
+fn cc_offset_is_measured_in_calibration_mode() {
+    let simulator = Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed).with_cc_offset(-25);
+    let mut gauge = driver(simulator);
+    assert_eq!(gauge.measure_cc_offset().unwrap(), -25);
+    assert!(!gauge.control_status_decoded().unwrap().calibration_enabled);
+}
+
+#[test]
+fn true_rc() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x68, 420);
+    let mut gauge = driver(simulator);
+    assert_eq!(gauge.true_rc().unwrap(), 420);
+}
@@ -0,0 +1,172 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Driver, DataFlash, DataFlashText, DataFlashType, DataFlashValue, GaugeConfigurator,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+const SUBCLASS_SIZE: u16 = 4 * 32;
+
+#[test]
+fn parameters_fit_their_subclass() {
+    for parameter in DataFlash::ALL {
+        assert!(parameter.offset as u16 + parameter.size() as u16 <= SUBCLASS_SIZE, "{}", parameter.name);
+        assert!(parameter.block() < 4, "{}", parameter.name);
+        let subclass = DataFlash::subclass(parameter.sub_class).unwrap();
+        assert!(parameter.offset + parameter.size() <= subclass.size, "{} beyond {}", parameter.name, subclass.name);
+    }
+}
+
+#[test]
+fn subclasses_are_sorted_and_fit_the_simulator() {
+    for pair in DataFlash::SUBCLASSES.windows(2) {
+        assert!(pair[0].id < pair[1].id, "{} and {} are not in order", pair[0].name, pair[1].name);
+    }
+    for subclass in DataFlash::SUBCLASSES {
+        assert!(subclass.size as u16 <= SUBCLASS_SIZE, "{}", subclass.name);
+    }
+    assert_eq!(DataFlash::subclass(88).map(|subclass| subclass.blocks()), Some(1));
+    assert_eq!(DataFlash::subclass(80).map(|subclass| subclass.blocks()), Some(3));
+    assert_eq!(DataFlash::subclass(3), None);
+}
+
+#[test]
+fn parameters_do_not_overlap() {
+    for pair in DataFlash::ALL.windows(2) {
+        let (previous, next) = (&pair[0], &pair[1]);
+        assert!(
+            (previous.sub_class, previous.offset) < (next.sub_class, next.offset),
+            "{} and {} are not in order",
+            previous.name,
+            next.name
+        );
+        if previous.sub_class == next.sub_class {
+            assert!(previous.offset + previous.size() <= next.offset, "{} overlaps {}", previous.name, next.name);
+        }
+    }
+}
+
+#[test]
+fn names_are_unique() {
+    for (index, parameter) in DataFlash::ALL.iter().enumerate() {
+        assert!(DataFlash::ALL[index + 1..].iter().all(|other| other.name != parameter.name), "{}", parameter.name);
+    }
+}
+
+#[test]
+fn integer_defaults_are_within_range() {
+    for parameter in DataFlash::ALL {
+        if let DataFlashValue::Integer(default) = parameter.default {
+            assert!(parameter.accepts(default), "{}", parameter.name);
+        }
+    }
+}
+
+#[test]
+fn integer_ranges_fit_their_type() {
+    for parameter in DataFlash::ALL {
+        let (min, max) = match parameter.data_type {
+            DataFlashType::I1 => (i8::MIN as i64, i8::MAX as i64),
+            DataFlashType::U1 => (0, u8::MAX as i64),
+            DataFlashType::I2 => (i16::MIN as i64, i16::MAX as i64),
+            DataFlashType::U2 => (0, u16::MAX as i64),
+            DataFlashType::U4 => (0, u32::MAX as i64),
+            DataFlashType::F4 | DataFlashType::S(_) => continue,
+        };
+        let (DataFlashValue::Integer(low), DataFlashValue::Integer(high)) = (parameter.min, parameter.max) else {
+            panic!("{} has no integer range", parameter.name);
+        };
+        assert!(min <= low && high <= max, "{}", parameter.name);
+    }
+}
+
+#[test]
+fn terminate_voltage() {
+    let terminate_voltage = DataFlash::TERMINATE_VOLTAGE;
+    assert_eq!(terminate_voltage.name, "Terminate Voltage");
+    assert_eq!((terminate_voltage.sub_class, terminate_voltage.offset), (80, 53));
+    assert_eq!(terminate_voltage.data_type, DataFlashType::U2);
+    assert_eq!(terminate_voltage.unit, "mV");
+    assert_eq!(terminate_voltage.default, DataFlashValue::Integer(3000));
+    assert_eq!(terminate_voltage.block(), 1);
+    assert!(terminate_voltage.accepts(2800));
+    assert!(!terminate_voltage.accepts(-1));
+    assert!(!DataFlash::CC_GAIN.accepts(0));
+}
+
+#[test]
+fn simulator_matches_the_map() {
+    let simulator = Bq34z100g1Simulator::new();
+    for (parameter, value) in [
+        (DataFlash::DESIGN_CAPACITY, 1000),
+        (DataFlash::CELL_CHARGE_VOLTAGE_T1_T2, 4200),
+        (DataFlash::PACK_CONFIGURATION, 0x0161),
+        (DataFlash::NUMBER_OF_SERIES_CELLS, 1),
+    ] {
+        let bytes = simulator.data_flash(parameter.sub_class, parameter.offset, parameter.size() as usize);
+        let stored = bytes.iter().fold(0, |stored, &byte| stored << 8 | byte as i64);
+        assert_eq!(stored, value, "{}", parameter.name);
+    }
+}
+
+#[test]
+fn values_are_encoded_big_endian() {
+    let mut bytes = [0u8; 32];
+    DataFlash::TERMINATE_VOLTAGE.encode(DataFlashValue::Integer(2800), &mut bytes).unwrap();
+    assert_eq!(bytes[..2], [0x0a, 0xf0]);
+    DataFlash::FC_SET.encode(DataFlashValue::Integer(-1), &mut bytes).unwrap();
+    assert_eq!(DataFlash::FC_SET.decode(&bytes), DataFlashValue::Integer(-1));
+    DataFlash::SEALED_TO_UNSEALED.encode(DataFlash::SEALED_TO_UNSEALED.default, &mut bytes).unwrap();
+    assert_eq!(bytes[..4], [0x36, 0x72, 0x04, 0x14]);
+    DataFlash::CC_GAIN.encode(DataFlashValue::Float(0.4768), &mut bytes).unwrap();
+    assert_eq!(DataFlash::CC_GAIN.decode(&bytes), DataFlashValue::Float(0.4768));
+    DataFlash::DEVICE_CHEMISTRY.encode(DataFlash::DEVICE_CHEMISTRY.default, &mut bytes).unwrap();
+    assert_eq!(bytes[..5], [4, b'L', b'I', b'O', b'N']);
+}
+
+#[test]
+fn invalid_values_are_not_encoded() {
+    let mut bytes = [0u8; 32];
+    assert_eq!(DataFlash::TERMINATE_VOLTAGE.encode(DataFlashValue::Integer(-1), &mut bytes), None);
+    assert_eq!(DataFlash::TERMINATE_VOLTAGE.encode(DataFlashValue::Float(3000.0), &mut bytes), None);
+    assert_eq!(DataFlash::CC_GAIN.encode(DataFlashValue::Integer(1), &mut bytes), None);
+    let text = DataFlashValue::Text(DataFlashText::new("LiFePO4").unwrap());
+    assert_eq!(DataFlash::DEVICE_CHEMISTRY.encode(text, &mut bytes), None);
+    assert_eq!(DataFlashText::new("a text longer than thirty-one bytes"), None);
+}
+
+#[test]
+fn parameter_spanning_two_blocks_is_written() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    let name = DataFlashText::new("pack-17").unwrap();
+
+    gauge.write_parameter(&DataFlash::DEVICE_NAME, DataFlashValue::Text(name)).unwrap();
+
+    let value = gauge.read_parameter(&DataFlash::DEVICE_NAME).unwrap();
+    assert_eq!(value, DataFlashValue::Text(name));
+    let DataFlashValue::Text(text) = value else { unreachable!() };
+    assert_eq!(text.as_str(), Some("pack-17"));
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(DataFlash::DEVICE_NAME.sub_class, 31, 8), b"\x07pack-17");
+}
+
+#[test]
+fn parameter_beyond_the_first_block_is_written() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+
+    gauge.write_parameter(&DataFlash::TERMINATE_VOLTAGE, DataFlashValue::Integer(2800)).unwrap();
+
+    assert_eq!(gauge.read_parameter(&DataFlash::TERMINATE_VOLTAGE).unwrap(), DataFlashValue::Integer(2800));
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(DataFlash::TERMINATE_VOLTAGE.sub_class, 53, 2), 2800_u16.to_be_bytes());
+}
+
+#[test]
+fn invalid_value_is_refused() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+
+    let error = gauge.write_parameter(&DataFlash::TERMINATE_VOLTAGE, DataFlashValue::Integer(70000)).unwrap_err();
+
+    assert!(matches!(error, Bq34Z100Error::InvalidValue { parameter: "Terminate Voltage" }));
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(DataFlash::TERMINATE_VOLTAGE.sub_class, 53, 2), [0, 0]);
+}
@@ -0,0 +1,50 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, Capabilities, DeviceInfo, GaugeConfigurator, PackConfigurationB,
+    Variant,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+#[test]
+fn identifies_g1() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    let device = gauge.identify().unwrap();
+
+    assert_eq!(device.variant, Variant::Bq34z100G1);
+    assert_eq!(device.device_type, 0x0100);
+    assert_eq!(device.firmware(), (0, 17));
+    assert_eq!(device.df_version, 0x0001);
+    assert!(device.supports(Capabilities::LED_CONFIGURATION | Capabilities::PACK_CONFIGURATION_BC));
+    assert_eq!(device.variant.to_string(), "bq34z100-G1");
+}
+
+#[test]
+fn capability_table() {
+    assert_eq!(DeviceInfo::new(0x0100, 0x0002, 0, 0).variant, Variant::Bq34z100);
+    assert!(!DeviceInfo::new(0x0100, 0x0002, 0, 0).supports(Capabilities::PACK_CONFIGURATION_BC));
+    assert_eq!(DeviceInfo::new(0x0100, 0x0020, 0, 0).variant, Variant::Bq34z100R2);
+    assert!(!DeviceInfo::new(0x0100, 0x0020, 0, 0).supports(Capabilities::LED_CONFIGURATION));
+
+    let unknown = DeviceInfo::new(0x0420, 0x0017, 0, 0);
+    assert_eq!(unknown.variant, Variant::Unknown);
+    assert!(unknown.capabilities().is_empty());
+}
+
+#[test]
+fn unsupported_operations_are_refused_before_writing() {
+    let simulator = Bq34z100g1Simulator::new().with_fw_version(0x0020);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+
+    let result = gauge.set_led_mode(0x02);
+    assert!(matches!(
+        result,
+        Err(Bq34Z100Error::Unsupported { capability: Capabilities::LED_CONFIGURATION, variant: Variant::Bq34z100R2 })
+    ));
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 0);
+    assert_eq!(simulator.data_flash(64, 4, 1), &[0]);
+
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    gauge.update_pack_configuration_b(PackConfigurationB::FCONV_EN).unwrap();
+    assert_eq!(gauge.pack_configuration_b().unwrap(), PackConfigurationB::FCONV_EN);
+}
@@ -0,0 +1,63 @@
+use core::cell::RefCell;
+
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, BusOperation, BusTiming, GaugeStatus};
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_bus::i2c::RefCellDevice;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+#[test]
+fn bus_error_names_the_command() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.nack_next(1);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+
+    let error = gauge.voltage().unwrap_err();
+    assert_eq!(error.operation(), Some(BusOperation::Read { command: 0x08 }));
+    assert_eq!(error.i2c_kind(), Some(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address)));
+    assert!(error.to_string().starts_with("I2C error in read of command 0x08"));
+}
+
+#[test]
+fn control_error_names_the_subcommand() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.nack_next(1);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+
+    let error = gauge.device_type().unwrap_err();
+    assert_eq!(error.operation(), Some(BusOperation::Control { subcommand: 0x0001 }));
+}
+
+#[test]
+fn block_data_write_names_subclass_and_offset() {
+    let bus = RefCell::new(Bq34z100g1Simulator::new());
+    let mut gauge = Bq34z100g1Driver::new(RefCellDevice::new(&bus), NoopDelay::new(), None);
+    gauge.set_bus_timing(BusTiming::NONE);
+
+    gauge.write_reg(0x3e, 48).unwrap(); // Data subclass
+    gauge.write_reg(0x3f, 1).unwrap(); // second block
+    bus.borrow_mut().nack_next(1);
+    let error = gauge.write_reg(0x40 + 2, 0x12).unwrap_err();
+    assert_eq!(error.operation(), Some(BusOperation::DataFlashWrite { sub_class: 48, offset: 34 }));
+
+    bus.borrow_mut().nack_next(1);
+    let error = gauge.write_reg(0x61, 0x00).unwrap_err();
+    assert_eq!(error.operation(), Some(BusOperation::Write { register: 0x61 }));
+}
+
+#[test]
+fn not_stored_is_a_std_error() {
+    let error: Bq34Z100Error<()> = Bq34Z100Error::NotStored {
+        parameter: "Design Capacity",
+        sub_class: 48,
+        offset: 11,
+        expected: 3000,
+        actual: 2800,
+    };
+    let error: Box<dyn std::error::Error> = Box::new(error);
+    assert_eq!(
+        error.to_string(),
+        "Design Capacity (subclass 48 offset 11) not stored, expected 3000 but read 2800"
+    );
+    assert!(error.source().is_none());
+}
@@ -0,0 +1,41 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{Bq34z100g1Driver, Flags, FlagsB, GaugeStatus};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+#[test]
+fn flags_decode_every_documented_bit() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x0e, 0xf713);
+    simulator.set_standard_command(0x12, 0x00e6);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+
+    let low = Flags::CHG | Flags::FC | Flags::BATLOW;
+    let high = Flags::DSG | Flags::SOCF | Flags::SOC1 | Flags::CF | Flags::TDD | Flags::ISD | Flags::OCVTAKEN;
+    assert_eq!(gauge.get_flags_decoded().unwrap(), low | high);
+
+    let flags_b = gauge.flags_b_decoded().unwrap();
+    assert_eq!(flags_b, FlagsB::DTRC | FlagsB::DODEOC | FlagsB::FIRSTDOD | FlagsB::LIFE | FlagsB::SOH);
+}
+
+#[test]
+fn reserved_bits_are_kept() {
+    let flags = Flags::from(0x0800);
+    assert_eq!(flags.bits(), 0x0800);
+    assert!(flags.iter_names().next().is_none());
+}
+
+#[test]
+fn diff_reports_set_and_cleared_bits() {
+    let previous = Flags::CHG | Flags::SOC1;
+    let current = Flags::CHG | Flags::FC;
+
+    let changes = current.diff(previous);
+    assert_eq!(changes.set, Flags::FC);
+    assert_eq!(changes.cleared, Flags::SOC1);
+    assert_eq!(changes.changed(), Flags::FC | Flags::SOC1);
+    assert!(current.diff(current).is_empty());
+
+    let changes = FlagsB::SOH.diff(FlagsB::LIFE);
+    assert_eq!(changes.set, FlagsB::SOH);
+    assert_eq!(changes.cleared, FlagsB::LIFE);
+}
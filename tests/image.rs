@@ -0,0 +1,159 @@
+use bq34z100::image::{DataFlashImage, ImageError};
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Driver, DataFlash, DataFlashText, DataFlashValue, GaugeConfigurator, GaugeStatus,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn driver(simulator: Bq34z100g1Simulator) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    Bq34z100g1Driver::new(simulator, NoopDelay::new(), None)
+}
+
+fn configured_image() -> DataFlashImage {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.update_design_capacity(3000).unwrap();
+    let name = DataFlashValue::Text(DataFlashText::new("pack-17").unwrap());
+    gauge.write_parameter(&DataFlash::DEVICE_NAME, name).unwrap();
+    gauge.write_parameter(&DataFlash::TERMINATE_VOLTAGE, DataFlashValue::Integer(9000)).unwrap();
+    gauge.dump_data_flash().unwrap()
+}
+
+#[test]
+fn image_is_restored_onto_a_replacement() {
+    let image = configured_image();
+    assert_eq!(image.value(&DataFlash::DESIGN_CAPACITY), Some(DataFlashValue::Integer(3000)));
+    assert_eq!(image.device().fw_version, 0x0017);
+
+    let mut replacement = driver(Bq34z100g1Simulator::new());
+    replacement.restore_data_flash(&image).unwrap();
+
+    assert_eq!(replacement.design_capacity().unwrap(), 3000);
+    assert_eq!(replacement.dump_data_flash().unwrap(), image);
+    let (simulator, _) = replacement.release();
+    assert_eq!(simulator.reset_count(), 1);
+    assert_eq!(simulator.data_flash(48, 31, 8), b"\x07pack-17");
+    assert_eq!(simulator.data_flash(80, 53, 2), 9000_u16.to_be_bytes());
+}
+
+#[test]
+fn blocks_the_gauge_updates_are_not_verified() {
+    let image = configured_image();
+    // the gauge updates Update Status of the State subclass right after the reset
+    let learning = || Bq34z100g1Simulator::new().with_reset_hook(|simulator| simulator.set_data_flash(82, 2, &[0x06]));
+
+    let mut replacement = driver(learning());
+    replacement.restore_data_flash(&image).unwrap();
+    assert_eq!(replacement.design_capacity().unwrap(), 3000);
+
+    let mut replacement = driver(learning());
+    let error = replacement.restore_data_flash_strict(&image).unwrap_err();
+    assert!(matches!(error, Bq34Z100Error::BlockNotStored { sub_class: 82, block: 0 }));
+}
+
+#[test]
+fn identical_image_is_not_written() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    let image = gauge.dump_data_flash().unwrap();
+
+    gauge.restore_data_flash(&image).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 0);
+}
+
+#[test]
+fn image_of_other_firmware_is_refused() {
+    let image = configured_image();
+    let mut replacement = driver(Bq34z100g1Simulator::new().with_fw_version(0x0015));
+
+    let error = replacement.restore_data_flash(&image).unwrap_err();
+
+    assert!(matches!(
+        error,
+        Bq34Z100Error::IncompatibleImage { image, device } if image.fw_version == 0x0017 && device.fw_version == 0x0015
+    ));
+    let (simulator, _) = replacement.release();
+    assert_eq!(simulator.reset_count(), 0);
+    assert_eq!(simulator.data_flash(48, 11, 2), 1000_u16.to_be_bytes());
+}
+
+#[test]
+fn security_keys_are_not_in_the_image() {
+    let image = configured_image();
+
+    assert_eq!(image.value(&DataFlash::SEALED_TO_UNSEALED), None);
+    assert!(DataFlashImage::BLOCKS.iter().all(|&(sub_class, _)| sub_class != 112));
+    assert!(DataFlashImage::BLOCKS.contains(&(48, 1)));
+}
+
+#[test]
+fn ra_tables_and_reserved_bytes_are_restored() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_data_flash(88, 0, &[0x00, 0x41, 0x00, 0x3e]);
+    simulator.set_data_flash(89, 30, &[0x00, 0x55]);
+    simulator.set_data_flash(80, 90, &[0x5a]);
+    let image = driver(simulator).dump_data_flash().unwrap();
+    assert!(DataFlashImage::BLOCKS.contains(&(88, 0)));
+    assert!(DataFlashImage::BLOCKS.contains(&(89, 0)));
+    assert!(DataFlashImage::BLOCKS.contains(&(80, 2)));
+
+    let mut replacement = driver(Bq34z100g1Simulator::new());
+    replacement.restore_data_flash(&image).unwrap();
+
+    let (simulator, _) = replacement.release();
+    assert_eq!(simulator.data_flash(88, 0, 4), [0x00, 0x41, 0x00, 0x3e]);
+    assert_eq!(simulator.data_flash(89, 30, 2), [0x00, 0x55]);
+    assert_eq!(simulator.data_flash(80, 90, 1), [0x5a]);
+}
+
+#[test]
+fn binary_format_round_trip() {
+    let image = configured_image();
+
+    let bytes = image.to_bytes();
+
+    assert_eq!(bytes.len(), DataFlashImage::BINARY_SIZE);
+    assert_eq!(&bytes[..7], b"BQ34DF\x01");
+    assert_eq!(bytes[7..11], [0x01, 0x00, 0x00, 0x17]);
+    assert_eq!(DataFlashImage::from_bytes(&bytes), Ok(image));
+}
+
+#[test]
+fn broken_binary_images_are_rejected() {
+    let bytes = configured_image().to_bytes();
+
+    let mut corrupted = bytes;
+    corrupted[100] ^= 0x01;
+    assert_eq!(DataFlashImage::from_bytes(&corrupted), Err(ImageError::ChecksumMismatch));
+    assert_eq!(DataFlashImage::from_bytes(&bytes[..bytes.len() - 1]), Err(ImageError::Truncated));
+    assert_eq!(DataFlashImage::from_bytes(b"BQ34REC\x01"), Err(ImageError::InvalidFormat));
+    let mut future = bytes;
+    future[6] = 2;
+    assert_eq!(DataFlashImage::from_bytes(&future), Err(ImageError::UnsupportedVersion(2)));
+}
+
+#[test]
+fn json_round_trip() {
+    let image = configured_image();
+
+    let json = image.to_json();
+
+    assert!(json.contains("\"fw_version\": 23"));
+    assert!(json.contains("{ \"sub_class\": 48, \"block\": 0, \"data\": \"0000"));
+    assert_eq!(DataFlashImage::from_json(&json), Ok(image));
+}
+
+#[test]
+fn broken_json_images_are_rejected() {
+    let json = configured_image().to_json();
+
+    let unknown = json.replacen("\"sub_class\": 2,", "\"sub_class\": 3,", 1);
+    assert_eq!(DataFlashImage::from_json(&unknown), Err(ImageError::UnknownBlock { sub_class: 3, block: 0 }));
+    let start = json.find("    { \"sub_class\": 48, \"block\": 0").unwrap();
+    let end = start + json[start..].find('\n').unwrap() + 1;
+    let missing = format!("{}{}", &json[..start], &json[end..]);
+    assert_eq!(DataFlashImage::from_json(&missing), Err(ImageError::MissingBlock { sub_class: 48, block: 0 }));
+    assert_eq!(DataFlashImage::from_json("{\"format\": 1,}"), Err(ImageError::InvalidJson { position: 13 }));
+    let future = json.replacen("\"format\": 1", "\"format\": 2", 1);
+    assert_eq!(DataFlashImage::from_json(&future), Err(ImageError::UnsupportedVersion(2)));
+}
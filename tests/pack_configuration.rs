@@ -0,0 +1,86 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Driver, GaugeConfigurator, GaugeStatus, PackConfiguration, PackConfigurationB,
+    PackConfigurationC, PackConfigurationError,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn driver(simulator: Bq34z100g1Simulator) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    Bq34z100g1Driver::new(simulator, NoopDelay::new(), None)
+}
+
+#[test]
+fn decodes_live_register() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    let config = gauge.pack_configuration_decoded().unwrap();
+
+    assert!(config.shutdown_enabled && config.sleep_enabled && config.remaining_capacity_fcc);
+    assert!(config.external_temperature);
+    assert!(!config.external_voltage_divider && !config.scaled);
+    assert_eq!(config.wake_sense, 0);
+    assert_eq!(config.bits(), 0x0161);
+}
+
+#[test]
+fn every_named_bit_round_trips() {
+    // all bits but the reserved bit 1
+    let config = PackConfiguration::from(0xfffd);
+    assert_eq!(config.wake_sense, 3);
+    assert_eq!(u16::from(config), 0xfffd);
+    assert_eq!(PackConfiguration::from(0x0002), PackConfiguration::default());
+}
+
+#[test]
+fn builder_rejects_illegal_combinations() {
+    assert_eq!(
+        PackConfiguration::builder().wake(0, true).build(),
+        Err(PackConfigurationError::WakeThresholdWithoutSense)
+    );
+    assert_eq!(
+        PackConfiguration::builder().wake(4, false).build(),
+        Err(PackConfigurationError::WakeSenseOutOfRange { rsns: 4 })
+    );
+
+    let single_cell = PackConfiguration::builder().external_temperature(true).build().unwrap();
+    assert_eq!(single_cell.validate_for_cells(1), Ok(()));
+    assert_eq!(single_cell.validate_for_cells(3), Err(PackConfigurationError::ExternalDividerRequired { cells: 3 }));
+    let multi_cell = single_cell.to_builder().external_voltage_divider(true).build().unwrap();
+    assert_eq!(multi_cell.validate_for_cells(3), Ok(()));
+}
+
+#[test]
+fn update_checks_series_cells() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.update_number_of_series_cells(4).unwrap();
+
+    let internal_divider = PackConfiguration::builder().sleep_enabled(true).build().unwrap();
+    assert!(matches!(
+        gauge.update_pack_configuration(internal_divider),
+        Err(Bq34Z100Error::InvalidPackConfiguration { error: PackConfigurationError::ExternalDividerRequired { cells: 4 } })
+    ));
+    assert_eq!(gauge.pack_configuration().unwrap(), 0x0161);
+}
+
+#[test]
+fn update_keeps_reserved_bits() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_data_flash(64, 0, &[0x01, 0x63]);
+    let mut gauge = driver(simulator);
+
+    gauge.update_pack_configuration(PackConfiguration::default()).unwrap();
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(64, 0, 2), [0x00, 0x02]);
+}
+
+#[test]
+fn update_pack_configuration_b_and_c() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    let config_b = PackConfigurationB::CHG_DOD_EOC | PackConfigurationB::LFP_RELAX | PackConfigurationB::FCONV_EN;
+    gauge.update_pack_configuration_b(config_b).unwrap();
+    gauge.update_pack_configuration_c(PackConfigurationC::SMOOTH_EN).unwrap();
+
+    assert_eq!(gauge.pack_configuration_b().unwrap(), config_b);
+    assert_eq!(gauge.pack_configuration_c().unwrap(), PackConfigurationC::SMOOTH_EN);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(64, 2, 2), [0x85, 0x08]);
+}
@@ -0,0 +1,111 @@
+use bq34z100::recording::{I2cCall, RecordedOperation, Recorder, RecordingDelay, RecordingI2c, Replay};
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{Bq34Z100Error, Bq34z100g1Driver, BusOperation, BusTiming, GaugeConfigurator, GaugeStatus};
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+type RecordingGauge = Bq34z100g1Driver<RecordingI2c<Bq34z100g1Simulator>, RecordingDelay<NoopDelay>>;
+
+fn record_session(session: impl FnOnce(&mut RecordingGauge)) -> Vec<u8> {
+    let recorder = Recorder::new();
+    let mut gauge = Bq34z100g1Driver::new(
+        recorder.i2c(Bq34z100g1Simulator::new()),
+        recorder.delay(NoopDelay::new()),
+        None,
+    );
+    session(&mut gauge);
+    let mut file = Vec::new();
+    recorder.save(&mut file).unwrap();
+    file
+}
+
+#[test]
+fn records_calls_with_delay_timestamps() {
+    let recorder = Recorder::new();
+    let mut gauge = Bq34z100g1Driver::new(
+        recorder.i2c(Bq34z100g1Simulator::new()),
+        recorder.delay(NoopDelay::new()),
+        None,
+    );
+    // only the delays of the update, the bus timing is covered in tests/timing.rs
+    gauge.set_bus_timing(BusTiming::NONE);
+    gauge.voltage().unwrap();
+    gauge.update_design_capacity(3000).unwrap();
+
+    let events = recorder.events();
+    assert_eq!(events[0].call, I2cCall::WriteRead);
+    assert_eq!(events[0].address, 0x55);
+    assert_eq!(
+        events[0].operations,
+        [RecordedOperation::Write(vec![0x08]), RecordedOperation::Read(vec![0x74, 0x0e])]
+    );
+    assert_eq!(events[0].time_ns, 0);
+    // two times 150 ms around the reset
+    assert_eq!(events.last().unwrap().time_ns, 300_000_000);
+}
+
+#[test]
+fn replays_saved_session() {
+    let file = record_session(|gauge| {
+        assert_eq!(gauge.device_type().unwrap(), 0x0100);
+        gauge.update_design_capacity(3000).unwrap();
+    });
+
+    let replay = Replay::load(&mut file.as_slice()).unwrap();
+    let mut gauge = Bq34z100g1Driver::new(replay, NoopDelay::new(), None);
+    assert_eq!(gauge.device_type().unwrap(), 0x0100);
+    gauge.update_design_capacity(3000).unwrap();
+
+    let (replay, _) = gauge.release();
+    replay.done();
+}
+
+#[test]
+fn replays_bus_errors() {
+    let recorder = Recorder::new();
+    let mut gauge = Bq34z100g1Driver::new(recorder.i2c(Bq34z100g1Simulator::new()), NoopDelay::new(), Some(0x56));
+    assert!(gauge.voltage().is_err());
+    let mut file = Vec::new();
+    recorder.save(&mut file).unwrap();
+
+    let replay = Replay::load(&mut file.as_slice()).unwrap();
+    let mut gauge = Bq34z100g1Driver::new(replay, NoopDelay::new(), Some(0x56));
+    match gauge.voltage() {
+        Err(Bq34Z100Error::I2C { error, operation }) => {
+            assert_eq!(error.kind, ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+            assert_eq!(operation, BusOperation::Read { command: 0x08 });
+        }
+        _ => panic!("expected the recorded error"),
+    }
+}
+
+#[test]
+#[should_panic(expected = "call 0: different bytes written")]
+fn replay_detects_deviation() {
+    let file = record_session(|gauge| {
+        gauge.voltage().unwrap();
+    });
+
+    let replay = Replay::load(&mut file.as_slice()).unwrap();
+    let mut gauge = Bq34z100g1Driver::new(replay, NoopDelay::new(), None);
+    let _ = gauge.current();
+}
+
+#[test]
+#[should_panic(expected = "replay ended after 1 of 2 recorded calls")]
+fn replay_detects_missing_calls() {
+    let file = record_session(|gauge| {
+        gauge.voltage().unwrap();
+        gauge.current().unwrap();
+    });
+
+    let replay = Replay::load(&mut file.as_slice()).unwrap();
+    let mut gauge = Bq34z100g1Driver::new(replay, NoopDelay::new(), None);
+    gauge.voltage().unwrap();
+    gauge.release().0.done();
+}
+
+#[test]
+fn rejects_foreign_files() {
+    assert!(Replay::load(&mut b"not a recording".as_slice()).is_err());
+}
@@ -0,0 +1,66 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Driver, FlashStreamProgrammer, GaugeStatus, RetryOperations, RetryPolicy, RetryStatistics,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn busy_gauge(nacks: u16, policy: RetryPolicy) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.nack_next(nacks);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+    gauge.set_retry_policy(policy);
+    gauge
+}
+
+#[test]
+fn nack_surfaces_without_policy() {
+    let mut gauge = busy_gauge(1, RetryPolicy::NONE);
+    assert!(matches!(gauge.voltage(), Err(Bq34Z100Error::I2C { .. })));
+    assert_eq!(gauge.voltage().unwrap(), 3700);
+    assert_eq!(
+        gauge.retry_statistics(),
+        RetryStatistics { transactions: 2, retries: 0, recovered: 0, failed: 1 }
+    );
+}
+
+#[test]
+fn transient_nacks_are_retried() {
+    let mut gauge = busy_gauge(2, RetryPolicy::DEFAULT);
+    assert_eq!(gauge.voltage().unwrap(), 3700);
+    assert_eq!(
+        gauge.retry_statistics(),
+        RetryStatistics { transactions: 1, retries: 2, recovered: 1, failed: 0 }
+    );
+
+    gauge.reset_retry_statistics();
+    assert_eq!(gauge.retry_statistics(), RetryStatistics::default());
+}
+
+#[test]
+fn gives_up_after_the_configured_retries() {
+    let mut gauge = busy_gauge(10, RetryPolicy::DEFAULT);
+    assert!(gauge.voltage().is_err());
+    assert_eq!(
+        gauge.retry_statistics(),
+        RetryStatistics { transactions: 1, retries: 3, recovered: 0, failed: 1 }
+    );
+}
+
+#[test]
+fn only_selected_operations_are_retried() {
+    let reads_only = RetryPolicy { operations: RetryOperations::READ, ..RetryPolicy::DEFAULT };
+    let mut gauge = busy_gauge(1, reads_only);
+    // the Control() subcommand write is not retried
+    assert!(gauge.device_type().is_err());
+    assert_eq!(gauge.retry_statistics().retries, 0);
+}
+
+#[test]
+fn flash_stream_write_errors_are_returned() {
+    let mut gauge = busy_gauge(1, RetryPolicy::NONE);
+    assert!(matches!(gauge.write_flash_stream_i2c("W: AA 61 00", false), Err(Bq34Z100Error::I2C { .. })));
+
+    let mut gauge = busy_gauge(1, RetryPolicy::DEFAULT);
+    gauge.write_flash_stream_i2c("W: AA 61 00", false).unwrap();
+    assert_eq!(gauge.retry_statistics().recovered, 1);
+}
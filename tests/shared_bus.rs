@@ -0,0 +1,96 @@
+use core::cell::RefCell;
+
+use bq34z100::{Bq34z100g1Driver, GaugeStatus, BQ34Z100_G1_ADDRESS};
+use embedded_hal::i2c::I2c;
+use embedded_hal_bus::i2c::RefCellDevice;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+#[test]
+fn default_address_is_used_without_override() {
+    let expectations = [Transaction::write_read(BQ34Z100_G1_ADDRESS, vec![0x02], vec![87])];
+    let mut gauge = Bq34z100g1Driver::new(Mock::new(&expectations), NoopDelay::new(), None);
+
+    assert_eq!(gauge.address(), 0x55);
+    assert_eq!(gauge.state_of_charge().unwrap(), 87);
+
+    let (mut i2c, _delay) = gauge.release();
+    i2c.done();
+}
+
+#[test]
+fn two_gauges_on_one_bus() {
+    // eg. the second gauge sits behind an address translator
+    let expectations = [
+        Transaction::write_read(0x55, vec![0x08], vec![0x10, 0x0e]),
+        Transaction::write_read(0x56, vec![0x08], vec![0x20, 0x1c]),
+        Transaction::write(0x56, vec![0x00, 0x01, 0x00]),
+        Transaction::write_read(0x56, vec![0x00], vec![0x00, 0x01]),
+    ];
+    let bus = RefCell::new(Mock::new(&expectations));
+
+    let mut first = Bq34z100g1Driver::new(RefCellDevice::new(&bus), NoopDelay::new(), None);
+    let mut second = Bq34z100g1Driver::new(RefCellDevice::new(&bus), NoopDelay::new(), Some(0x56));
+
+    assert_eq!(first.voltage().unwrap(), 3600);
+    assert_eq!(second.voltage().unwrap(), 7200);
+    assert_eq!(second.device_type().unwrap(), 0x0100);
+
+    let (_first_device, _) = first.release();
+    let (_second_device, _) = second.release();
+    bus.into_inner().done();
+}
+
+#[test]
+fn gauge_shares_bus_with_other_sensor() {
+    let expectations = [
+        Transaction::write_read(0x48, vec![0x00], vec![0x19, 0x00]),
+        Transaction::write_read(0x55, vec![0x10], vec![0x38, 0xff]),
+        Transaction::write_read(0x48, vec![0x00], vec![0x1a, 0x00]),
+    ];
+    let bus = RefCell::new(Mock::new(&expectations));
+
+    let mut sensor = RefCellDevice::new(&bus);
+    let mut gauge = Bq34z100g1Driver::new(RefCellDevice::new(&bus), NoopDelay::new(), None);
+
+    let mut buffer = [0; 2];
+    sensor.write_read(0x48, &[0x00], &mut buffer).unwrap();
+    assert_eq!(gauge.current().unwrap(), -200);
+    sensor.write_read(0x48, &[0x00], &mut buffer).unwrap();
+    assert_eq!(buffer, [0x1a, 0x00]);
+
+    let (_gauge_device, _) = gauge.release();
+    bus.into_inner().done();
+}
+
+#[cfg(feature = "async")]
+mod asynch {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+
+    use bq34z100::{Bq34z100g1AsyncDriver, GaugeStatusAsync};
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+    // the mocks never pend, so polling once is enough
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("mock future pending"),
+        }
+    }
+
+    #[test]
+    fn async_driver_uses_configured_address() {
+        let expectations = [Transaction::write_read(0x56, vec![0x02], vec![42])];
+        let mut gauge = Bq34z100g1AsyncDriver::new(Mock::new(&expectations), NoopDelay::new(), Some(0x56));
+
+        assert_eq!(block_on(gauge.state_of_charge()).unwrap(), 42);
+
+        let (mut i2c, _delay) = gauge.release();
+        i2c.done();
+    }
+}
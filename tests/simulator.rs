@@ -0,0 +1,337 @@
+use bq34z100::simulator::{Bq34z100g1Simulator, SimulatedSecurity};
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, ControlStatus, FlashStreamProgrammer, GaugeCalibrator,
+    GaugeConfigurator, GaugeStatus, PackConfiguration, SecurityKey, SecurityMode,
+};
+use embedded_hal::i2c::I2c;
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn driver(simulator: Bq34z100g1Simulator) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    Bq34z100g1Driver::new(simulator, NoopDelay::new(), None)
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+#[test]
+fn standard_commands() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x02, 77);
+    simulator.set_standard_command(0x03, 3);
+    simulator.set_standard_command(0x08, 7400);
+    simulator.set_standard_command(0x10, (-1500_i16) as u16);
+    let mut gauge = driver(simulator);
+
+    assert_eq!(gauge.state_of_charge().unwrap(), 77);
+    assert_eq!(gauge.state_of_charge_max_error().unwrap(), 3);
+    assert_eq!(gauge.voltage().unwrap(), 7400);
+    assert_eq!(gauge.current().unwrap(), -1500);
+    assert_eq!(gauge.temperature().unwrap(), 2982);
+    assert_eq!(gauge.design_capacity().unwrap(), 1000);
+}
+
+#[test]
+fn control_subcommands() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+
+    assert_eq!(gauge.device_type().unwrap(), 0x0100);
+    assert_eq!(gauge.chem_id().unwrap(), 0x0100);
+    // sealed: FAS and SS
+    assert_eq!(gauge.control_status().unwrap(), 0x6000);
+
+    gauge.unsealed().unwrap();
+    assert_eq!(gauge.control_status().unwrap(), 0x4000);
+    gauge.it_enable().unwrap();
+    assert_eq!(gauge.control_status().unwrap(), 0x4001);
+
+    let status = gauge.control_status_decoded().unwrap();
+    assert!(status.full_access_sealed && !status.sealed);
+    assert!(status.impedance_track_enabled);
+    assert!(!status.calibration_enabled && !status.offset_calibration_running());
+    assert_eq!(status.security_mode(), SecurityMode::Unsealed);
+}
+
+#[test]
+fn control_status_decodes_every_bit() {
+    let status = ControlStatus::from(0x7e3f);
+    assert!(status.full_access_sealed && status.sealed && status.calibration_enabled);
+    assert!(status.cc_offset_calibration && status.board_offset_calibration && status.checksum_valid);
+    assert!(status.full_sleep && status.sleep && status.constant_power_load);
+    assert!(status.resistance_update_disabled && status.voltage_ok && status.impedance_track_enabled);
+    assert_eq!(ControlStatus::from(0x0000).security_mode(), SecurityMode::FullAccess);
+    assert_eq!(ControlStatus::from(0x0001), ControlStatus { impedance_track_enabled: true, ..ControlStatus::from(0) });
+}
+
+#[test]
+fn wrong_address_is_not_acknowledged() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), Some(0x56));
+    assert!(matches!(gauge.voltage(), Err(Bq34Z100Error::I2C { .. })));
+}
+
+#[test]
+fn data_flash_needs_matching_checksum() {
+    let mut simulator = Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed);
+    simulator.write(0x55, &[0x61, 0x00]).unwrap();
+    simulator.write(0x55, &[0x3e, 48]).unwrap();
+    simulator.write(0x55, &[0x3f, 0x00]).unwrap();
+    simulator.write(0x55, &[0x4b, 0x07, 0xd0]).unwrap();
+
+    let mut checksum = [0];
+    simulator.write_read(0x55, &[0x60], &mut checksum).unwrap();
+    simulator.write(0x55, &[0x60, checksum[0].wrapping_add(1)]).unwrap();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 1000);
+
+    simulator.write(0x55, &[0x60, checksum[0]]).unwrap();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 2000);
+}
+
+#[test]
+fn sealed_gauge_ignores_data_flash_access() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.write(0x55, &[0x61, 0x00]).unwrap();
+    simulator.write(0x55, &[0x3e, 48]).unwrap();
+    simulator.write(0x55, &[0x3f, 0x00]).unwrap();
+
+    let mut block = [0xaa; 32];
+    simulator.write_read(0x55, &[0x40], &mut block).unwrap();
+    assert_eq!(block, [0; 32]);
+}
+
+#[test]
+fn glitched_block_read_is_retried() {
+    let mut simulator = Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed);
+    simulator.glitch_next_block_reads(2);
+    let mut gauge = driver(simulator);
+
+    gauge.read_flash_block(48, 0).unwrap();
+
+    assert_eq!(be_u16(&gauge.flash_block_data()[11..]), 1000);
+}
+
+#[test]
+fn glitched_block_read_fails_after_all_attempts() {
+    let mut simulator = Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed);
+    simulator.glitch_next_block_reads(3);
+    let mut gauge = driver(simulator);
+
+    let error = gauge.read_flash_block(48, 0).unwrap_err();
+
+    assert!(matches!(error, Bq34Z100Error::BlockChecksumMismatch { sub_class: 48, block: 0, .. }));
+}
+
+#[test]
+fn sealed_gauge_block_read_is_not_selected() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+
+    let error = gauge.read_flash_block(48, 32).unwrap_err();
+
+    assert!(matches!(
+        error,
+        Bq34Z100Error::BlockNotSelected { sub_class: 48, block: 1, selected_sub_class: 0, selected_block: 0 }
+    ));
+}
+
+#[test]
+fn update_after_glitched_block_read_keeps_the_block() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.glitch_next_block_reads(1);
+    let mut gauge = driver(simulator);
+
+    gauge.update_design_capacity(3000).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 3000);
+    assert_eq!(be_u16(simulator.data_flash(48, 13, 2)), 3700);
+}
+
+#[test]
+fn whole_block_is_written() {
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed));
+    gauge.read_flash_block(80, 32).unwrap();
+    gauge.flash_block_data_mut()[21..23].copy_from_slice(&2800_u16.to_be_bytes()); // Terminate Voltage
+
+    gauge.write_flash_block(80, 32).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(80, 53, 2)), 2800);
+    // nothing went to Control()
+    assert_eq!(simulator.security(), SimulatedSecurity::Unsealed);
+    assert_eq!(simulator.reset_count(), 0);
+}
+
+#[test]
+fn update_design_capacity() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.update_design_capacity(3000).unwrap();
+    assert_eq!(gauge.design_capacity().unwrap(), 3000);
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 8, 2)), 3000);
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 3000);
+    assert_eq!(simulator.reset_count(), 1);
+    // the reset sealed the gauge, the driver unsealed it again for verifying
+    assert_eq!(simulator.security(), SimulatedSecurity::Unsealed);
+}
+
+#[test]
+fn update_fails_with_wrong_unseal_key() {
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_unseal_key([0x1234, 0x5678]));
+    assert!(matches!(
+        gauge.update_design_capacity(3000),
+        Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Unsealed, actual: SecurityMode::Sealed })
+    ));
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 1000);
+}
+
+#[test]
+fn security_modes() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Sealed);
+    assert!(matches!(
+        gauge.full_access(SecurityKey::DEFAULT_FULL_ACCESS),
+        Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::FullAccess, actual: SecurityMode::Sealed })
+    ));
+
+    gauge.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Unsealed);
+    gauge.full_access(SecurityKey::DEFAULT_FULL_ACCESS).unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::FullAccess);
+
+    gauge.seal().unwrap();
+    assert_eq!(gauge.security_mode().unwrap(), SecurityMode::Sealed);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.security(), SimulatedSecurity::Sealed);
+}
+
+#[test]
+fn custom_unseal_key_is_used_for_updates() {
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_unseal_key([0x1234, 0x5678]));
+    assert!(gauge.unseal(SecurityKey::DEFAULT_UNSEAL).is_err());
+    gauge.unseal(SecurityKey::new(0x1234, 0x5678)).unwrap();
+    gauge.update_design_capacity(3000).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 3000);
+}
+
+#[test]
+fn update_design_energy_and_charge_voltages() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.update_design_energy(22200, 10).unwrap();
+    gauge.update_cell_charge_voltage_range(4100, 4150, 4000).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 13, 2)), 22200);
+    assert_eq!(simulator.data_flash(48, 30, 1), [10]);
+    assert_eq!(be_u16(simulator.data_flash(48, 17, 2)), 4100);
+    assert_eq!(be_u16(simulator.data_flash(48, 19, 2)), 4150);
+    assert_eq!(be_u16(simulator.data_flash(48, 21, 2)), 4000);
+    // both edits of subclass 48 kept the design capacity
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 1000);
+}
+
+#[test]
+fn update_registers_subclass() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.update_number_of_series_cells(4).unwrap();
+    let config = PackConfiguration::builder()
+        .external_voltage_divider(true)
+        .external_temperature(true)
+        .sleep_enabled(true)
+        .wake(1, true)
+        .build()
+        .unwrap();
+    gauge.update_pack_configuration(config).unwrap();
+    gauge.set_led_mode(0x02).unwrap();
+    assert_eq!(gauge.pack_configuration().unwrap(), 0x0a49);
+    assert_eq!(gauge.pack_configuration_decoded().unwrap(), config);
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(64, 7, 1), [4]);
+    assert_eq!(simulator.data_flash(64, 4, 1), [0x02]);
+    assert_eq!(simulator.reset_count(), 3);
+}
+
+#[test]
+fn update_charge_termination_and_q_max() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.update_charge_termination_parameters(100, 25, 100, 40, -1, -1, 100, 98).unwrap();
+    gauge.update_q_max(2500).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(36, 0, 11), [0, 100, 0, 25, 0, 100, 40, 0xff, 0xff, 100, 98]);
+    assert_eq!(be_u16(simulator.data_flash(82, 0, 2)), 2500);
+}
+
+#[test]
+fn calibrate_offsets() {
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    gauge.calibrate_cc_offset().unwrap();
+    gauge.calibrate_board_offset().unwrap();
+
+    gauge.unsealed().unwrap();
+    // CALEN, CCA and BCA are cleared again
+    assert_eq!(gauge.control_status().unwrap() & 0x1c00, 0);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 2);
+}
+
+#[test]
+fn calibrate_voltage_divider() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x08, 3700);
+    let mut gauge = driver(simulator);
+    gauge.calibrate_voltage_divider(3885.0).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(104, 14, 2)), 5250);
+}
+
+#[test]
+fn calibrate_sense_resistor() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x10, 500);
+    let mut gauge = driver(simulator);
+    // measured half the applied current, so the gain doubles
+    gauge.calibrate_sense_resistor(1000).unwrap();
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(104, 0, 2), [0x80, 0x74]);
+    assert_eq!(simulator.data_flash(104, 4, 2), [0x95, 0x0a]);
+    assert_eq!(simulator.reset_count(), 1);
+}
+
+#[test]
+fn flash_stream() {
+    let stream = [
+        ";unseal",
+        "W: AA 00 14 04",
+        "W: AA 00 72 36",
+        "W: AA 61 00",
+        "W: AA 3E 30",
+        "W: AA 3F 00",
+        "W: AA 4B 07 D0",
+        "X: 10",
+        "C: AA 4B 07 D0",
+    ];
+    let mut gauge = driver(Bq34z100g1Simulator::new());
+    for line in stream {
+        gauge.write_flash_stream_i2c(line, false).unwrap();
+    }
+
+    let (mut simulator, _) = gauge.release();
+    let mut checksum = [0];
+    simulator.write_read(0x55, &[0x60], &mut checksum).unwrap();
+    let mut gauge = driver(simulator);
+    gauge.write_flash_stream_i2c(&format!("W: AA 60 {:02X}", checksum[0]), false).unwrap();
+    assert!(matches!(
+        gauge.write_flash_stream_i2c("C: AA 4B 07 D1", false),
+        Err(Bq34Z100Error::ChecksumError { register: 0x4c, expected: 0xd1, actual: 0xd0 })
+    ));
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 2000);
+}
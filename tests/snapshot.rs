@@ -0,0 +1,67 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{Bq34z100g1Driver, GaugeStatus, SnapshotGroups};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+
+#[test]
+fn snapshot_reads_all_groups() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x02, 64);
+    simulator.set_standard_command(0x08, 7400);
+    simulator.set_standard_command(0x0a, (-750_i16) as u16);
+    simulator.set_standard_command(0x2c, 12);
+    simulator.set_standard_command(0x6a, 2480);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+
+    let snapshot = gauge.snapshot(SnapshotGroups::ALL).unwrap();
+    let charge = snapshot.charge.unwrap();
+    assert_eq!(charge.state_of_charge, 64);
+    assert_eq!(charge.remaining_capacity, gauge.remaining_capacity().unwrap());
+    let measurement = snapshot.measurement.unwrap();
+    assert_eq!(measurement.voltage, 7400);
+    assert_eq!(measurement.average_current, -750);
+    assert_eq!(measurement.temperature, 2982);
+    assert_eq!(snapshot.health.unwrap().cycle_count, 12);
+    assert_eq!(snapshot.configuration.unwrap().design_capacity, 1000);
+    assert_eq!(snapshot.learning.unwrap().true_fcc, 2480);
+}
+
+#[test]
+fn snapshot_uses_one_burst_per_range() {
+    let mut response = vec![0; 0x3e - 0x02];
+    response[0x08 - 0x02..0x0a - 0x02].copy_from_slice(&3600_u16.to_le_bytes());
+    response[0x3c - 0x02..].copy_from_slice(&2000_u16.to_le_bytes());
+    let mut learning = vec![0; 0x76 - 0x62];
+    learning[0] = 3;
+    let expectations = [
+        Transaction::write_read(0x55, vec![0x02], response),
+        Transaction::write_read(0x55, vec![0x62], learning),
+    ];
+    let mut gauge = Bq34z100g1Driver::new(Mock::new(&expectations), NoopDelay::new(), None);
+
+    let snapshot = gauge.snapshot(SnapshotGroups::ALL).unwrap();
+    assert_eq!(snapshot.measurement.unwrap().voltage, 3600);
+    assert_eq!(snapshot.configuration.unwrap().design_capacity, 2000);
+    assert_eq!(snapshot.learning.unwrap().grid_number, 3);
+
+    let (mut i2c, _) = gauge.release();
+    i2c.done();
+}
+
+#[test]
+fn snapshot_reads_only_selected_groups() {
+    // Flags() to DOD0Time()
+    let mut response = vec![0; 0x20 - 0x0e];
+    response[0x1a - 0x0e] = 90;
+    let expectations = [Transaction::write_read(0x55, vec![0x0e], response)];
+    let mut gauge = Bq34z100g1Driver::new(Mock::new(&expectations), NoopDelay::new(), None);
+
+    let snapshot = gauge.snapshot(SnapshotGroups::STATUS | SnapshotGroups::TIME).unwrap();
+    assert!(snapshot.charge.is_none());
+    assert!(snapshot.learning.is_none());
+    assert!(snapshot.status.unwrap().flags_b.is_empty());
+    assert_eq!(snapshot.time.unwrap().average_time_to_full, 90);
+
+    let (mut i2c, _) = gauge.release();
+    i2c.done();
+}
@@ -0,0 +1,114 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Driver, ControlStatus, Flags, FlagsB, GaugeSnapshot, GaugeStatus, Measurements, Millivolts,
+    PackConfiguration, SnapshotGroups,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+// what an application would write against the status trait only
+fn needs_charging<G: GaugeStatus<E>, E>(gauge: &mut G) -> Result<bool, Bq34Z100Error<E>> {
+    let flags = gauge.get_flags_decoded()?;
+    Ok(flags.contains(Flags::SOC1) || gauge.state_of_charge()? < 20)
+}
+
+struct FakeGauge {
+    state_of_charge: u8,
+    voltage: u16,
+}
+
+macro_rules! not_read {
+    ($($name:ident -> $value:ty),* $(,)?) => {
+        $(
+            fn $name(&mut self) -> Result<$value, Bq34Z100Error<()>> {
+                unimplemented!()
+            }
+        )*
+    };
+}
+
+impl GaugeStatus<()> for FakeGauge {
+    fn snapshot(&mut self, _groups: SnapshotGroups) -> Result<GaugeSnapshot, Bq34Z100Error<()>> {
+        unimplemented!()
+    }
+
+    fn voltage(&mut self) -> Result<u16, Bq34Z100Error<()>> {
+        Ok(self.voltage)
+    }
+
+    fn current(&mut self) -> Result<i16, Bq34Z100Error<()>> {
+        Ok(-250)
+    }
+
+    fn state_of_charge(&mut self) -> Result<u8, Bq34Z100Error<()>> {
+        Ok(self.state_of_charge)
+    }
+
+    fn get_flags_decoded(&mut self) -> Result<Flags, Bq34Z100Error<()>> {
+        Ok(Flags::empty())
+    }
+
+    not_read! {
+        control_status -> u16,
+        control_status_decoded -> ControlStatus,
+        device_type -> u16,
+        fw_version -> u16,
+        hw_version -> u16,
+        reset_data -> u16,
+        prev_macwrite -> u16,
+        chem_id -> u16,
+        df_version -> u16,
+        static_chem_chksum -> u16,
+        state_of_charge_max_error -> u8,
+        remaining_capacity -> u16,
+        full_charge_capacity -> u16,
+        average_current -> i16,
+        temperature -> u16,
+        flags -> u16,
+        flags_b -> u16,
+        average_time_to_empty -> u16,
+        average_time_to_full -> u16,
+        passed_charge -> i16,
+        do_d0_time -> u16,
+        available_energy -> u16,
+        average_power -> i16,
+        serial_number -> u16,
+        internal_temperature -> u16,
+        cycle_count -> u16,
+        state_of_health -> u16,
+        charge_voltage -> u16,
+        charge_current -> u16,
+        pack_configuration -> u16,
+        pack_configuration_decoded -> PackConfiguration,
+        design_capacity -> u16,
+        grid_number -> u8,
+        learned_status -> u8,
+        dod_at_eoc -> u16,
+        q_start -> u16,
+        true_rc -> u16,
+        true_fcc -> u16,
+        state_time -> u16,
+        q_max_passed_q -> u16,
+        dod_0 -> u16,
+        q_max_dod_0 -> u16,
+        q_max_time -> u16,
+        flags_b_decoded -> FlagsB,
+    }
+}
+
+#[test]
+fn fake_gauge_stands_in_for_the_driver() {
+    let mut fake = FakeGauge { state_of_charge: 15, voltage: 3600 };
+    assert!(needs_charging(&mut fake).unwrap());
+    fake.state_of_charge = 80;
+    assert!(!needs_charging(&mut fake).unwrap());
+
+    let mut measurements = Measurements::new(&mut fake);
+    assert_eq!(measurements.voltage().unwrap(), Millivolts(3600));
+    assert_eq!(measurements.current().unwrap().0, -250);
+}
+
+#[test]
+fn driver_implements_the_status_trait() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    assert!(!needs_charging(&mut gauge).unwrap());
+}
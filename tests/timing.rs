@@ -0,0 +1,54 @@
+use bq34z100::recording::{I2cCall, Recorder, RecordingDelay, RecordingI2c};
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::{Bq34z100g1Driver, BusTiming, GaugeStatus};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+type RecordingGauge = Bq34z100g1Driver<RecordingI2c<Bq34z100g1Simulator>, RecordingDelay<NoopDelay>>;
+
+/// Call and start time of every transaction of the session
+fn transaction_times(timing: BusTiming, session: impl FnOnce(&mut RecordingGauge)) -> Vec<(I2cCall, u64)> {
+    let recorder = Recorder::new();
+    let mut gauge = Bq34z100g1Driver::new(
+        recorder.i2c(Bq34z100g1Simulator::new()),
+        recorder.delay(NoopDelay::new()),
+        None,
+    );
+    gauge.set_bus_timing(timing);
+    session(&mut gauge);
+    recorder.events().into_iter().map(|event| (event.call, event.time_ns)).collect()
+}
+
+#[test]
+fn transactions_are_spaced_by_the_bus_free_time() {
+    let times = transaction_times(BusTiming::DATASHEET, |gauge| {
+        gauge.voltage().unwrap();
+        gauge.current().unwrap();
+    });
+    assert_eq!(times, [(I2cCall::WriteRead, 0), (I2cCall::WriteRead, 66_000)]);
+}
+
+#[test]
+fn control_subcommands_get_processing_time() {
+    let times = transaction_times(BusTiming::DATASHEET, |gauge| {
+        gauge.device_type().unwrap();
+    });
+    assert_eq!(times, [(I2cCall::Write, 0), (I2cCall::WriteRead, 2_000_000)]);
+}
+
+#[test]
+fn checksums_get_their_execution_time() {
+    let times = transaction_times(BusTiming::DATASHEET, |gauge| {
+        gauge.static_chem_chksum().unwrap();
+    });
+    assert_eq!(times, [(I2cCall::Write, 0), (I2cCall::WriteRead, 52_000_000)]);
+}
+
+#[test]
+fn timing_can_be_overridden() {
+    let slow = BusTiming { bus_free_us: 500, control_us: 5000 };
+    let times = transaction_times(slow, |gauge| {
+        gauge.device_type().unwrap();
+        gauge.voltage().unwrap();
+    });
+    assert_eq!(times, [(I2cCall::Write, 0), (I2cCall::WriteRead, 5_000_000), (I2cCall::WriteRead, 5_500_000)]);
+}
@@ -0,0 +1,84 @@
+use bq34z100::simulator::Bq34z100g1Simulator;
+use bq34z100::transaction::{DataFlashTransaction, ParameterOutcome, TransactionError};
+use bq34z100::{Bq34z100g1Driver, DataFlash, DataFlashText, DataFlashValue, GaugeConfigurator, GaugeStatus};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+#[test]
+fn pack_is_configured_with_one_reset() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    let mut transaction: DataFlashTransaction = DataFlashTransaction::new();
+    transaction.set(&DataFlash::DESIGN_CAPACITY, DataFlashValue::Integer(3000)).unwrap();
+    transaction.set(&DataFlash::DESIGN_ENERGY, DataFlashValue::Integer(11100)).unwrap();
+    transaction.set(&DataFlash::CELL_CHARGE_VOLTAGE_T1_T2, DataFlashValue::Integer(4150)).unwrap();
+    transaction.set(&DataFlash::NUMBER_OF_SERIES_CELLS, DataFlashValue::Integer(3)).unwrap();
+    transaction.set(&DataFlash::TERMINATE_VOLTAGE, DataFlashValue::Integer(9000)).unwrap();
+
+    let report = gauge.commit_transaction(&transaction).unwrap();
+
+    assert!(report.all_stored());
+    assert_eq!(report.iter().count(), 5);
+    assert_eq!(report.outcome(&DataFlash::TERMINATE_VOLTAGE), Some(ParameterOutcome::Stored));
+    assert_eq!(report.outcome(&DataFlash::DESIGN_ENERGY_SCALE), None);
+    assert_eq!(gauge.design_capacity().unwrap(), 3000);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 1);
+    assert_eq!(be_u16(simulator.data_flash(48, 11, 2)), 3000);
+    assert_eq!(be_u16(simulator.data_flash(48, 13, 2)), 11100);
+    assert_eq!(be_u16(simulator.data_flash(48, 17, 2)), 4150);
+    assert_eq!(be_u16(simulator.data_flash(48, 19, 2)), 4200);
+    assert_eq!(simulator.data_flash(64, 7, 1), [3]);
+    assert_eq!(be_u16(simulator.data_flash(80, 53, 2)), 9000);
+}
+
+#[test]
+fn parameter_spanning_two_blocks_is_committed() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    let name = DataFlashValue::Text(DataFlashText::new("pack-17").unwrap());
+    let mut transaction: DataFlashTransaction<4> = DataFlashTransaction::new();
+    transaction.set(&DataFlash::DEVICE_NAME, name).unwrap();
+    transaction.set(&DataFlash::MANUFACTURER_NAME, DataFlash::MANUFACTURER_NAME.default).unwrap();
+
+    let report = gauge.commit_transaction(&transaction).unwrap();
+
+    assert!(report.all_stored());
+    assert_eq!(gauge.read_parameter(&DataFlash::DEVICE_NAME).unwrap(), name);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.data_flash(48, 31, 8), b"\x07pack-17");
+    assert_eq!(simulator.data_flash(48, 43, 12), b"\x0bTexas Inst.");
+}
+
+#[test]
+fn empty_transaction_does_not_reset() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+
+    let report = gauge.commit_transaction(&DataFlashTransaction::<4>::new()).unwrap();
+
+    assert_eq!(report.iter().count(), 0);
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.reset_count(), 0);
+}
+
+#[test]
+fn edits_are_checked_and_replaced() {
+    let mut transaction: DataFlashTransaction<2> = DataFlashTransaction::new();
+    transaction.set(&DataFlash::DESIGN_CAPACITY, DataFlashValue::Integer(3000)).unwrap();
+    transaction.set(&DataFlash::DESIGN_CAPACITY, DataFlashValue::Integer(2000)).unwrap();
+    transaction.set(&DataFlash::DESIGN_ENERGY, DataFlashValue::Integer(7400)).unwrap();
+    assert_eq!(transaction.len(), 2);
+
+    assert_eq!(
+        transaction.set(&DataFlash::NUMBER_OF_SERIES_CELLS, DataFlashValue::Integer(3)),
+        Err(TransactionError::Full)
+    );
+    assert_eq!(
+        transaction.set(&DataFlash::DESIGN_CAPACITY, DataFlashValue::Float(1.0)),
+        Err(TransactionError::InvalidValue { parameter: "Design Capacity" })
+    );
+
+    transaction.clear();
+    assert!(transaction.is_empty());
+}
@@ -0,0 +1,138 @@
+use bq34z100::simulator::{Bq34z100g1Simulator, SimulatedSecurity};
+use bq34z100::typestate::DetectedMode;
+use bq34z100::recording::{RecordedOperation, Recorder};
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1, Bq34z100g1Driver, GaugeStatus, MilliwattHours, Scale, SecurityKey, SecurityMode,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+fn driver(simulator: Bq34z100g1Simulator) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    Bq34z100g1Driver::new(simulator, NoopDelay::new(), None)
+}
+
+#[test]
+fn detects_mode_of_gauge() {
+    let detected = driver(Bq34z100g1Simulator::new()).into_typestate().unwrap();
+    assert!(matches!(detected, DetectedMode::Sealed(_)));
+
+    // without the key the typestate could not return to full access after an update
+    let detected = driver(Bq34z100g1Simulator::new().with_security(SimulatedSecurity::FullAccess))
+        .into_typestate()
+        .unwrap();
+    assert!(matches!(detected, DetectedMode::Unsealed(_)));
+
+    let detected = driver(Bq34z100g1Simulator::new().with_security(SimulatedSecurity::FullAccess))
+        .into_typestate_with_key(SecurityKey::DEFAULT_FULL_ACCESS)
+        .unwrap();
+    assert!(matches!(detected, DetectedMode::FullAccess(_)));
+}
+
+fn full_access_gauge(key: SecurityKey) -> Bq34z100g1Driver<Bq34z100g1Simulator, NoopDelay> {
+    // boots sealed, so only the key brings it back to full access after a reset
+    let mut gauge = driver(Bq34z100g1Simulator::new().with_full_access_key([0x1111, 0x2222]));
+    gauge.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap();
+    gauge.full_access(key).unwrap();
+    gauge
+}
+
+#[test]
+fn detected_full_access_is_kept_with_a_custom_key() {
+    let key = SecurityKey::new(0x1111, 0x2222);
+    let DetectedMode::FullAccess(mut full_access) = full_access_gauge(key).into_typestate_with_key(key).unwrap() else {
+        panic!("gauge is in full access");
+    };
+    full_access.update_design_capacity(3000).unwrap();
+    assert_eq!(full_access.security_mode().unwrap(), SecurityMode::FullAccess);
+
+    let DetectedMode::Unsealed(mut unsealed) = full_access_gauge(key).into_typestate().unwrap() else {
+        panic!("a full access gauge without key is tracked as unsealed");
+    };
+    unsealed.update_design_capacity(3000).unwrap();
+    assert_eq!(unsealed.security_mode().unwrap(), SecurityMode::Unsealed);
+}
+
+#[test]
+fn unseal_update_and_seal() {
+    let DetectedMode::Sealed(mut sealed) = driver(Bq34z100g1Simulator::new()).into_typestate().unwrap() else {
+        panic!("simulator starts sealed");
+    };
+    assert_eq!(sealed.status().voltage().unwrap(), 3700);
+
+    let mut unsealed = sealed.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap();
+    assert_eq!(unsealed.mode(), SecurityMode::Unsealed);
+    unsealed.update_design_capacity(3000).unwrap();
+    assert_eq!(unsealed.security_mode().unwrap(), SecurityMode::Unsealed);
+
+    let mut sealed = unsealed.seal().unwrap();
+    assert_eq!(sealed.status().design_capacity().unwrap(), 3000);
+    assert_eq!(sealed.security_mode().unwrap(), SecurityMode::Sealed);
+}
+
+#[test]
+fn failed_unseal_returns_sealed_driver() {
+    let DetectedMode::Sealed(sealed) =
+        driver(Bq34z100g1Simulator::new().with_unseal_key([0x1234, 0x5678])).into_typestate().unwrap()
+    else {
+        panic!("simulator starts sealed");
+    };
+
+    let error = sealed.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap_err();
+    assert!(matches!(error.error, Bq34Z100Error::SecurityAccessDenied { actual: SecurityMode::Sealed, .. }));
+    let unsealed = error.driver.unseal(SecurityKey::new(0x1234, 0x5678)).unwrap();
+    assert_eq!(unsealed.mode(), SecurityMode::Unsealed);
+}
+
+#[test]
+fn full_access_survives_updates() {
+    let DetectedMode::Sealed(sealed) = driver(Bq34z100g1Simulator::new()).into_typestate().unwrap() else {
+        panic!("simulator starts sealed");
+    };
+    let unsealed = sealed.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap();
+    let mut full_access = unsealed.full_access(SecurityKey::DEFAULT_FULL_ACCESS).unwrap();
+
+    full_access.update_number_of_series_cells(3).unwrap();
+    assert_eq!(full_access.security_mode().unwrap(), SecurityMode::FullAccess);
+
+    let (simulator, _) = full_access.seal().unwrap().into_inner().release();
+    assert_eq!(simulator.data_flash(64, 7, 1), [3]);
+    assert_eq!(simulator.security(), SimulatedSecurity::Sealed);
+}
+
+#[test]
+fn sealed_driver_keeps_the_gauge_sealed_for_the_scale() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_data_flash(64, 0, &0x01e1_u16.to_be_bytes()); // SCALED
+    simulator.set_data_flash(48, 30, &[10]);
+    simulator.set_standard_command(0x3a, 0x01e1);
+    simulator.set_standard_command(0x24, 960);
+    let DetectedMode::Sealed(mut sealed) = driver(simulator).into_typestate().unwrap() else {
+        panic!("simulator starts sealed");
+    };
+
+    assert!(matches!(sealed.scale(), Err(Bq34Z100Error::SecurityAccessDenied { actual: SecurityMode::Sealed, .. })));
+    sealed.set_scale(Scale { current: 1, energy: 10 });
+    assert_eq!(sealed.measurements().unwrap().available_energy().unwrap(), MilliwattHours(96_000));
+
+    let mut unsealed = sealed.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap();
+    unsealed.forget_scale();
+    assert_eq!(unsealed.scale().unwrap(), Scale { current: 1, energy: 10 });
+}
+
+#[test]
+fn unlocked_updates_skip_the_unseal() {
+    let recorder = Recorder::new();
+    let gauge = Bq34z100g1Driver::new(recorder.i2c(Bq34z100g1Simulator::new()), NoopDelay::new(), None);
+    let DetectedMode::Sealed(sealed) = gauge.into_typestate().unwrap() else {
+        panic!("simulator starts sealed");
+    };
+    let mut unsealed = sealed.unseal(SecurityKey::DEFAULT_UNSEAL).unwrap();
+    let unseal_key = RecordedOperation::Write(vec![0x00, 0x14, 0x04]);
+    let key_writes = || recorder.events().iter().filter(|event| event.operations == [unseal_key.clone()]).count();
+    assert_eq!(key_writes(), 1);
+
+    // only the reset of each update seals the gauge and needs the key again
+    unsealed.update_design_capacity(3000).unwrap();
+    unsealed.update_design_capacity(3100).unwrap();
+    assert_eq!(key_writes(), 3);
+    assert_eq!(unsealed.status().design_capacity().unwrap(), 3100);
+}
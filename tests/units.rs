@@ -0,0 +1,170 @@
+use bq34z100::simulator::{Bq34z100g1Simulator, SimulatedSecurity};
+use bq34z100::transaction::DataFlashTransaction;
+use bq34z100::{
+    Bq34Z100Error, Bq34z100g1Driver, DataFlash, DataFlashValue, DeciKelvin, GaugeConfigurator, MilliampHours,
+    Milliamps, Millivolts, MilliwattHours, Milliwatts, Minutes, Scale, SecurityMode,
+};
+use embedded_hal_mock::eh1::delay::NoopDelay;
+
+#[test]
+fn readings_have_unit_and_sign_applied() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x08, 7400);
+    simulator.set_standard_command(0x10, -1500i16 as u16);
+    simulator.set_standard_command(0x1a, 0xffff);
+    simulator.set_standard_command(0x1c, -25i16 as u16);
+    simulator.set_standard_command(0x24, 120);
+    simulator.set_standard_command(0x26, -1110i16 as u16);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+    let mut measurements = gauge.measurements().unwrap();
+
+    assert_eq!(measurements.voltage().unwrap(), Millivolts(7400));
+    assert_eq!(measurements.current().unwrap(), Milliamps(-1500));
+    assert_eq!(measurements.available_energy().unwrap(), MilliwattHours(1200));
+    assert_eq!(measurements.passed_charge().unwrap(), MilliampHours(-25));
+    assert_eq!(measurements.average_power().unwrap(), Milliwatts(-11100));
+    assert_eq!(measurements.average_time_to_full().unwrap(), Minutes(0xffff));
+    assert!(!measurements.average_time_to_full().unwrap().is_available());
+}
+
+#[test]
+fn temperature_converts_to_celsius() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    let temperature = gauge.measurements().unwrap().temperature().unwrap();
+
+    assert_eq!(temperature, DeciKelvin(2982));
+    assert_eq!(temperature.to_decicelsius(), 251);
+    assert!((temperature.to_celsius() - 25.05).abs() < 0.01);
+    assert_eq!(DeciKelvin(2632).to_decicelsius(), -100);
+}
+
+#[test]
+fn decicelsius_covers_the_whole_register_range() {
+    assert_eq!(DeciKelvin(2731).to_decicelsius(), -1);
+    assert_eq!(DeciKelvin(2732).to_decicelsius(), 1);
+    assert_eq!(DeciKelvin(0).to_decicelsius(), -2732);
+    assert_eq!(DeciKelvin(32768).to_decicelsius(), 30037);
+    assert_eq!(DeciKelvin(35499).to_decicelsius(), 32768);
+    assert_eq!(DeciKelvin(u16::MAX).to_decicelsius(), 62804);
+}
+
+#[test]
+fn charge_current_is_unsigned() {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_standard_command(0x32, 40000);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+
+    assert_eq!(gauge.measurements().unwrap().charge_current().unwrap(), Milliamps(40000));
+}
+
+fn scaled_simulator() -> Bq34z100g1Simulator {
+    let mut simulator = Bq34z100g1Simulator::new();
+    simulator.set_data_flash(64, 0, &0x01e1_u16.to_be_bytes()); // SCALED
+    simulator.set_data_flash(48, 30, &[10]);
+    simulator.set_standard_command(0x3a, 0x01e1);
+    simulator.set_standard_command(0x04, 20000);
+    simulator.set_standard_command(0x10, -3000i16 as u16);
+    simulator.set_standard_command(0x24, 960);
+    simulator
+}
+
+#[test]
+fn scaled_pack_readings_are_multiplied() {
+    let simulator = scaled_simulator().with_security(SimulatedSecurity::Unsealed);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+    let mut measurements = gauge.measurements().unwrap();
+
+    // Design Energy Scale only scales energy and power
+    assert_eq!(measurements.scale(), Some(Scale { current: 1, energy: 10 }));
+    assert_eq!(measurements.remaining_capacity().unwrap(), MilliampHours(20_000));
+    assert_eq!(measurements.current().unwrap(), Milliamps(-3_000));
+    assert_eq!(measurements.available_energy().unwrap(), MilliwattHours(96_000));
+    assert_eq!(measurements.voltage().unwrap(), Millivolts(3700));
+}
+
+#[test]
+fn sealed_scaled_pack_fails_only_the_scaled_readings() {
+    let mut gauge = Bq34z100g1Driver::new(scaled_simulator(), NoopDelay::new(), None);
+    assert!(matches!(
+        gauge.scale(),
+        Err(Bq34Z100Error::SecurityAccessDenied { requested: SecurityMode::Unsealed, actual: SecurityMode::Sealed })
+    ));
+
+    let mut measurements = gauge.measurements().unwrap();
+    assert_eq!(measurements.scale(), None);
+    assert_eq!(measurements.voltage().unwrap(), Millivolts(3700));
+    assert_eq!(measurements.temperature().unwrap(), DeciKelvin(2982));
+    assert!(matches!(measurements.remaining_capacity(), Err(Bq34Z100Error::UnknownScale)));
+    assert!(matches!(measurements.current(), Err(Bq34Z100Error::UnknownScale)));
+    assert!(matches!(measurements.available_energy(), Err(Bq34Z100Error::UnknownScale)));
+    assert!(matches!(measurements.average_power(), Err(Bq34Z100Error::UnknownScale)));
+
+    // the scale is never detected by unsealing the gauge
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.security(), SimulatedSecurity::Sealed);
+    assert_eq!(simulator.reset_count(), 0);
+}
+
+#[test]
+fn sealed_scaled_pack_reads_with_the_given_scale() {
+    let scale = Scale { current: 10, energy: 10 };
+    let mut gauge = Bq34z100g1Driver::new(scaled_simulator(), NoopDelay::new(), None).with_scale(scale);
+    assert_eq!(gauge.scale().unwrap(), scale);
+
+    let mut measurements = gauge.measurements().unwrap();
+    assert_eq!(measurements.remaining_capacity().unwrap(), MilliampHours(200_000));
+    assert_eq!(measurements.current().unwrap(), Milliamps(-30_000));
+    assert_eq!(measurements.available_energy().unwrap(), MilliwattHours(96_000));
+
+    let (simulator, _) = gauge.release();
+    assert_eq!(simulator.security(), SimulatedSecurity::Sealed);
+}
+
+#[test]
+fn scale_is_cached_until_overridden_or_updated() {
+    let simulator = scaled_simulator().with_security(SimulatedSecurity::Unsealed);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+    assert_eq!(gauge.scale().unwrap(), Scale { current: 1, energy: 10 });
+
+    gauge.set_scale(Scale { current: 100, energy: 10 });
+    assert_eq!(gauge.measurements().unwrap().current().unwrap(), Milliamps(-300_000));
+
+    gauge.update_design_energy(9600, 1).unwrap();
+    assert_eq!(gauge.scale().unwrap(), Scale { current: 1, energy: 1 });
+}
+
+#[test]
+fn writing_pack_configuration_drops_the_cached_scale() {
+    let mut simulator = Bq34z100g1Simulator::new().with_security(SimulatedSecurity::Unsealed);
+    simulator.set_data_flash(48, 30, &[10]);
+    let mut gauge = Bq34z100g1Driver::new(simulator, NoopDelay::new(), None);
+    assert_eq!(gauge.scale().unwrap(), Scale::UNSCALED);
+
+    gauge.write_parameter(&DataFlash::PACK_CONFIGURATION, DataFlashValue::Integer(0x01e1)).unwrap(); // SCALED
+    assert_eq!(gauge.scale().unwrap(), Scale { current: 1, energy: 10 });
+
+    let mut transaction: DataFlashTransaction = DataFlashTransaction::new();
+    transaction.set(&DataFlash::PACK_CONFIGURATION, DataFlashValue::Integer(0x0161)).unwrap();
+    assert!(gauge.commit_transaction(&transaction).unwrap().all_stored());
+    assert_eq!(gauge.scale().unwrap(), Scale::UNSCALED);
+}
+
+#[test]
+fn unscaled_pack_has_unit_scale() {
+    let mut gauge = Bq34z100g1Driver::new(Bq34z100g1Simulator::new(), NoopDelay::new(), None);
+    assert_eq!(gauge.scale().unwrap(), Scale::UNSCALED);
+    assert!(!gauge.scale().unwrap().is_scaled());
+}
+
+#[cfg(feature = "uom")]
+#[test]
+fn converts_into_uom_quantities() {
+    use uom::si::electric_potential::volt;
+    use uom::si::f32::{ElectricPotential, Power};
+    use uom::si::power::watt;
+
+    let voltage: ElectricPotential = Millivolts(3700).into();
+    assert!((voltage.get::<volt>() - 3.7).abs() < 1e-6);
+    let power: Power = Milliwatts(-11100).into();
+    assert!((power.get::<watt>() + 11.1).abs() < 1e-5);
+}
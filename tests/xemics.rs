@@ -0,0 +1,90 @@
+use bq34z100::xemics::{decode, encode};
+
+// float implementation the driver used before, from https://github.com/Ralim/BQ34Z100
+fn reference_decode(x: u32) -> f32 {
+    let exponent = 2.0_f32.powf(((x >> 24) as i16 - 128) as f32);
+    let mantissa = ((x & 0x00ff_ffff) | 0x0080_0000) as f32 * 2.0_f32.powf(-24.0);
+    if x & 0x0080_0000 == 0 {
+        mantissa * exponent
+    } else {
+        -mantissa * exponent
+    }
+}
+
+fn reference_encode(x: f32) -> u32 {
+    let negative = x < 0.0;
+    let x = x.abs();
+    let exponent = (x.log2().floor() + 1.0) as i32;
+    let scaled_mantissa = (x / 2f32.powi(exponent) * 2f32.powi(24)) as u32;
+    let sign = if negative { 0x0080_0000 } else { 0 };
+    (((exponent + 128) as u32) << 24) | sign | (scaled_mantissa & 0x007f_ffff)
+}
+
+#[test]
+fn known_values() {
+    assert_eq!(encode(1.0), Some(0x8100_0000));
+    assert_eq!(encode(-1.0), Some(0x8180_0000));
+    assert_eq!(encode(0.5), Some(0x8000_0000));
+    assert_eq!(encode(0.4768), Some(0x7f74_1f21)); // CC Gain of a 10 mOhm sense resistor
+    assert_eq!(decode(0x8100_0000), 1.0);
+    assert_eq!(decode(0x8380_0000), -4.0);
+    assert_eq!(decode(0x7f74_1f21), 0.4768);
+}
+
+#[test]
+fn matches_the_float_implementation() {
+    for value in [0.4768_f32, 4.768, -3.3, 1e-30, 5677445.6, 1.0e30, -0.001, 12345.678] {
+        assert_eq!(encode(value), Some(reference_encode(value)), "{value}");
+        let raw = reference_encode(value);
+        assert_eq!(decode(raw), reference_decode(raw), "{raw:08x}");
+    }
+}
+
+#[test]
+fn zero_and_out_of_range() {
+    assert_eq!(encode(0.0), Some(0x0000_0000));
+    assert_eq!(encode(-0.0), Some(0x0080_0000));
+    assert_eq!(encode(f32::MIN_POSITIVE / 2.0), Some(0x0000_0000));
+    assert_eq!(decode(0x0000_0000), 0.0);
+    assert_eq!(decode(0x02ff_ffff), -0.0);
+    assert_eq!(encode(f32::INFINITY), None);
+    assert_eq!(encode(f32::NAN), None);
+    assert_eq!(encode(2.0_f32.powi(127)), None);
+    assert_eq!(encode(f32::MAX), None);
+    assert_eq!(encode(2.0_f32.powi(126)), Some(0xff00_0000));
+}
+
+fn round_trips(raw: u32) {
+    let value = decode(raw);
+    assert_eq!(encode(value), Some(raw), "{raw:08x}");
+    assert_eq!(decode(encode(value).unwrap()).to_bits(), value.to_bits(), "{raw:08x}");
+}
+
+#[test]
+fn every_exponent_round_trips() {
+    for exponent in 3..=0xff_u32 {
+        for mantissa in (0..=0x00ff_ffff).step_by(4099).chain([0x007f_ffff, 0x0080_0000, 0x00ff_ffff]) {
+            round_trips(exponent << 24 | mantissa);
+        }
+    }
+}
+
+#[test]
+fn every_mantissa_round_trips() {
+    for mantissa in 0..=0x00ff_ffff {
+        round_trips(0x7f00_0000 | mantissa);
+    }
+}
+
+#[test]
+fn codes_below_the_f32_range_read_as_zero() {
+    for exponent in 0..=2_u32 {
+        for mantissa in (0..=0x00ff_ffff).step_by(4099).chain([0x007f_ffff, 0x0080_0000, 0x00ff_ffff]) {
+            let raw = exponent << 24 | mantissa;
+            let zero = decode(raw);
+            assert_eq!(zero, 0.0, "{raw:08x}");
+            assert_eq!(zero.is_sign_negative(), mantissa & 0x0080_0000 != 0, "{raw:08x}");
+            assert_eq!(encode(zero), Some(mantissa & 0x0080_0000), "{raw:08x}");
+        }
+    }
+}